@@ -0,0 +1,382 @@
+// ============= src/bitbucket.rs =============
+use crate::github::client::{
+    content_skip_reason, filter_entries, normalized, split_generated, tree_size_exceeded, unless_cancelled, DEFAULT_USER_AGENT,
+};
+use crate::github::{ExportedFile, FailedFile, FetchOptions, GitTreeEntry, HttpSettings, RepoFiles};
+use crate::progress::{ProgressReporter, SilentProgress, SkipReason};
+use crate::utils::encode_path;
+use anyhow::{anyhow, Result};
+use reqwest::header::USER_AGENT;
+use reqwest::{Client, RequestBuilder, StatusCode};
+use serde::Deserialize;
+use std::sync::Arc;
+use std::time::Instant;
+
+/// API root for Bitbucket Cloud
+pub const BITBUCKET_API_URL: &str = "https://api.bitbucket.org/2.0";
+
+/// How to authenticate to Bitbucket Cloud. Public repositories can be fetched without.
+#[derive(Debug, Clone)]
+pub enum BitbucketAuth {
+    /// A username and app password, sent with HTTP Basic auth
+    AppPassword { username: String, password: String },
+    /// A repository, project or workspace access token, sent as a bearer token
+    Token(String),
+}
+
+#[derive(Debug, Deserialize)]
+struct BitbucketRepository {
+    mainbranch: Option<BitbucketBranch>,
+}
+
+#[derive(Debug, Deserialize)]
+struct BitbucketBranch {
+    name: String,
+}
+
+/// One page of a `src` directory listing
+#[derive(Debug, Deserialize)]
+struct SrcPage {
+    values: Vec<SrcEntry>,
+    /// URL of the next page, if there is one
+    next: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct SrcEntry {
+    path: String,
+    /// "commit_file" or "commit_directory"
+    #[serde(rename = "type")]
+    kind: String,
+    #[serde(default)]
+    size: Option<usize>,
+}
+
+#[derive(Debug, Deserialize)]
+struct BitbucketErrorResponse {
+    error: BitbucketErrorDetail,
+}
+
+#[derive(Debug, Deserialize)]
+struct BitbucketErrorDetail {
+    message: String,
+}
+
+/// Fetches repository files from Bitbucket Cloud through its `src` API, one directory
+/// listing per directory and one request per file.
+///
+/// Files are filtered as `GitHubClient` filters them: the path rules, generated files,
+/// the file, line, byte and total caps, `binary_threshold`, `content_pattern` and
+/// cancellation all apply. `since`, `raw_fallback` and the repository's own ignore files
+/// are GitHub-specific.
+#[derive(Clone)]
+pub struct BitbucketClient {
+    client: Client,
+    base_url: String,
+    auth: Option<BitbucketAuth>,
+    user_agent: String,
+    progress: Arc<dyn ProgressReporter>,
+}
+
+impl BitbucketClient {
+    pub fn new(auth: Option<BitbucketAuth>) -> Self {
+        Self {
+            client: HttpSettings::default().client().unwrap_or_default(),
+            base_url: BITBUCKET_API_URL.to_string(),
+            auth,
+            user_agent: DEFAULT_USER_AGENT.to_string(),
+            progress: Arc::new(SilentProgress),
+        }
+    }
+
+    /// Sets the `User-Agent` sent with every request; a blank value keeps the default
+    pub fn with_user_agent(mut self, user_agent: &str) -> Self {
+        if !user_agent.trim().is_empty() {
+            self.user_agent = user_agent.trim().to_string();
+        }
+        self
+    }
+
+    /// Sets the reporter that receives per-file progress events (silent by default)
+    pub fn with_progress(mut self, progress: Arc<dyn ProgressReporter>) -> Self {
+        self.progress = progress;
+        self
+    }
+
+    /// Sends requests to another API root, e.g. a mock server in tests
+    pub fn with_base_url(mut self, base_url: &str) -> Self {
+        self.base_url = base_url.trim_end_matches('/').to_string();
+        self
+    }
+
+    pub fn with_http_settings(mut self, settings: &HttpSettings) -> Result<Self> {
+        self.client = settings.client()?;
+        Ok(self)
+    }
+
+    /// The name of the repository's main branch
+    pub async fn main_branch(&self, workspace: &str, repo: &str) -> Result<String> {
+        let url = format!("{}/repositories/{}/{}", self.base_url, workspace, repo);
+        let response = self.get(&url).send().await?;
+        if response.status() == StatusCode::NOT_FOUND {
+            return Err(anyhow!(
+                "Bitbucket repository {}/{} not found, or your credentials can't access it",
+                workspace, repo
+            ));
+        }
+        if !response.status().is_success() {
+            return Err(error_from(response).await);
+        }
+
+        let repository: BitbucketRepository = response.json().await?;
+        repository
+            .mainbranch
+            .map(|branch| branch.name)
+            .ok_or_else(|| anyhow!("Bitbucket repository {}/{} is empty", workspace, repo))
+    }
+
+    /// Fetches every eligible file at `git_ref`, sorted by path. Files that can't be
+    /// fetched or aren't UTF-8 are returned in `RepoFiles::failed`. Cancelling
+    /// `options.cancel` stops early with the files fetched so far and
+    /// `RepoFiles::cancelled` set.
+    #[tracing::instrument(skip(self, options))]
+    pub async fn fetch_repo_files(&self, workspace: &str, repo: &str, git_ref: &str, options: &FetchOptions) -> Result<RepoFiles> {
+        let tree = self.list_files(workspace, repo, git_ref, options).await?;
+        let (mut eligible, generated) = split_generated(filter_entries(&tree, options, &[])?, options);
+        for entry in generated {
+            self.progress.on_file_skipped(&entry.path, &SkipReason::Generated);
+        }
+        eligible.sort_by(|a, b| a.path.cmp(&b.path));
+        if let Some(max_files) = options.max_files.filter(|max_files| eligible.len() > *max_files) {
+            self.progress.on_file_limit_reached(eligible.len() - max_files);
+            eligible.truncate(max_files);
+        }
+        self.progress.on_start(eligible.len());
+
+        let mut fetched = RepoFiles::default();
+        let mut total_bytes = 0;
+        for (index, entry) in eligible.iter().enumerate() {
+            if options.is_cancelled() {
+                break;
+            }
+            self.progress.on_file_start(&entry.path);
+            if let Some(reason) = tree_size_exceeded(entry, options) {
+                self.progress.on_file_skipped(&entry.path, &reason);
+                continue;
+            }
+
+            let url = format!("{}/repositories/{}/{}/src/{}/{}", self.base_url, workspace, repo, git_ref, encode_path(&entry.path));
+            let started = Instant::now();
+            let Some(content) = unless_cancelled(options, self.fetch_raw(&url)).await else {
+                break;
+            };
+            self.progress.on_file_fetched(&entry.path, started.elapsed());
+            let content = match content {
+                Ok(content) => normalized(&content, options),
+                Err(e) => {
+                    self.progress.on_file_skipped(&entry.path, &SkipReason::FetchFailed(e.to_string()));
+                    fetched.failed.push(FailedFile { path: entry.path.clone(), error: e.to_string() });
+                    continue;
+                }
+            };
+            match content_skip_reason(&content, options) {
+                Some(reason) => self.progress.on_file_skipped(&entry.path, &reason),
+                None if options.content_pattern.as_ref().is_some_and(|pattern| !pattern.is_match(&content)) => {
+                    self.progress.on_file_skipped(&entry.path, &SkipReason::NoContentMatch);
+                    fetched.unmatched += 1;
+                }
+                None if options.max_total_bytes.is_some_and(|limit| total_bytes + content.len() > limit) => {
+                    self.progress.on_total_limit_reached(eligible.len() - index);
+                    break;
+                }
+                None => {
+                    total_bytes += content.len();
+                    self.progress.on_file_done(&entry.path, content.len());
+                    fetched.files.push(ExportedFile::new(entry.path.clone(), content));
+                }
+            }
+        }
+        fetched.cancelled = options.is_cancelled();
+        Ok(fetched)
+    }
+
+    /// Walks the repository (or `options.subpath`) one directory at a time, following
+    /// each listing's pages, and returns its files as tree entries for `filter_entries`
+    async fn list_files(&self, workspace: &str, repo: &str, git_ref: &str, options: &FetchOptions) -> Result<Vec<GitTreeEntry>> {
+        let mut files = Vec::new();
+        // Only the subtree is walked, so there's nothing outside it to filter out
+        let mut pending = vec![options.subpath.as_ref().map_or_else(String::new, |subpath| format!("{}/", subpath))];
+        while let Some(dir) = pending.pop() {
+            let mut next = Some(format!("{}/repositories/{}/{}/src/{}/{}?pagelen=100", self.base_url, workspace, repo, git_ref, encode_path(&dir)));
+            while let Some(url) = next {
+                let page: SrcPage = self.get_json(&url).await?;
+                next = page.next;
+                for entry in page.values {
+                    match entry.kind.as_str() {
+                        "commit_directory" => pending.push(format!("{}/", entry.path)),
+                        "commit_file" => files.push(GitTreeEntry {
+                            path: entry.path,
+                            mode: "100644".to_string(),
+                            kind: "blob".to_string(),
+                            sha: String::new(),
+                            size: entry.size,
+                        }),
+                        _ => {}
+                    }
+                }
+            }
+        }
+        Ok(files)
+    }
+
+    async fn fetch_raw(&self, url: &str) -> Result<String> {
+        let response = self.get(url).send().await?;
+        if !response.status().is_success() {
+            return Err(error_from(response).await);
+        }
+        String::from_utf8(response.bytes().await?.to_vec()).map_err(|_| anyhow!("File is not valid UTF-8"))
+    }
+
+    async fn get_json<T: serde::de::DeserializeOwned>(&self, url: &str) -> Result<T> {
+        let response = self.get(url).send().await?;
+        if !response.status().is_success() {
+            return Err(error_from(response).await);
+        }
+        Ok(response.json().await?)
+    }
+
+    fn get(&self, url: &str) -> RequestBuilder {
+        let request = self.client.get(url).header(USER_AGENT, &self.user_agent);
+        match &self.auth {
+            Some(BitbucketAuth::AppPassword { username, password }) => request.basic_auth(username, Some(password)),
+            Some(BitbucketAuth::Token(token)) => request.bearer_auth(token),
+            None => request,
+        }
+    }
+}
+
+/// An error naming the response's status, with Bitbucket's message when it sent one
+async fn error_from(response: reqwest::Response) -> anyhow::Error {
+    let status = response.status();
+    match response.json::<BitbucketErrorResponse>().await {
+        Ok(body) => anyhow!("Bitbucket API error ({}): {}", status, body.error.message),
+        Err(_) => anyhow!("Bitbucket API returned status: {}", status),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wiremock::matchers::{header, method, path, query_param};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    #[tokio::test]
+    async fn test_fetch_repo_files_walks_directories_and_pages() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/repositories/team/repo"))
+            .and(header("authorization", "Bearer secret"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({ "mainbranch": { "name": "main" } })))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/repositories/team/repo/src/main/"))
+            .and(query_param("pagelen", "100"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "values": [
+                    { "path": "src", "type": "commit_directory" },
+                    { "path": "README.md", "type": "commit_file", "size": 8 }
+                ],
+                "next": format!("{}/repositories/team/repo/src/main/?pagelen=100&page=2", server.uri())
+            })))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/repositories/team/repo/src/main/"))
+            .and(query_param("page", "2"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "values": [{ "path": "app.exe", "type": "commit_file", "size": 4 }]
+            })))
+            .with_priority(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/repositories/team/repo/src/main/src/"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "values": [{ "path": "src/main.rs", "type": "commit_file", "size": 13 }]
+            })))
+            .mount(&server)
+            .await;
+        for (file, content) in [("README.md", "# Repo\r\n"), ("src/main.rs", "fn main() {}\n")] {
+            Mock::given(method("GET"))
+                .and(path(format!("/repositories/team/repo/src/main/{}", file)))
+                .respond_with(ResponseTemplate::new(200).set_body_string(content))
+                .mount(&server)
+                .await;
+        }
+
+        let client = BitbucketClient::new(Some(BitbucketAuth::Token("secret".to_string()))).with_base_url(&server.uri());
+        let git_ref = client.main_branch("team", "repo").await.unwrap();
+        let fetched = client.fetch_repo_files("team", "repo", &git_ref, &FetchOptions::default()).await.unwrap();
+
+        assert_eq!(git_ref, "main");
+        assert_eq!(
+            fetched.files,
+            vec![ExportedFile::new("README.md", "# Repo\n"), ExportedFile::new("src/main.rs", "fn main() {}\n")]
+        );
+        assert!(fetched.failed.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_fetch_options_apply_as_for_github() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/repositories/team/repo/src/main/"))
+            .and(header("user-agent", "acme-exporter/1.0"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "values": [
+                    { "path": "a.rs", "type": "commit_file", "size": 9 },
+                    { "path": "b.rs", "type": "commit_file", "size": 9 },
+                    { "path": "c.rs", "type": "commit_file", "size": 9 },
+                    { "path": "Cargo.lock", "type": "commit_file", "size": 9 },
+                    { "path": "fixtures/data.rs", "type": "commit_file", "size": 9 }
+                ]
+            })))
+            .mount(&server)
+            .await;
+        for (file, content) in [("a.rs", "fn a() {}"), ("b.rs", "// empty")] {
+            Mock::given(method("GET"))
+                .and(path(format!("/repositories/team/repo/src/main/{}", file)))
+                .respond_with(ResponseTemplate::new(200).set_body_string(content))
+                .expect(1)
+                .mount(&server)
+                .await;
+        }
+
+        let client = BitbucketClient::new(None).with_base_url(&server.uri()).with_user_agent("acme-exporter/1.0");
+        let options = FetchOptions {
+            exclude: vec!["fixtures/".to_string()],
+            max_files: Some(2),
+            content_pattern: Some(regex::Regex::new(r"\bfn\b").unwrap()),
+            ..FetchOptions::default()
+        };
+        let fetched = client.fetch_repo_files("team", "repo", "main", &options).await.unwrap();
+
+        // Cargo.lock is generated and fixtures/ excluded, so the cap leaves a.rs and b.rs
+        assert_eq!(fetched.files, vec![ExportedFile::new("a.rs", "fn a() {}")]);
+        assert_eq!(fetched.unmatched, 1);
+    }
+
+    #[tokio::test]
+    async fn test_missing_repository() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/repositories/team/missing"))
+            .respond_with(ResponseTemplate::new(404))
+            .mount(&server)
+            .await;
+
+        let error = BitbucketClient::new(None).with_base_url(&server.uri()).main_branch("team", "missing").await.unwrap_err();
+        assert!(error.to_string().contains("team/missing not found"));
+    }
+}
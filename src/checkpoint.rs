@@ -0,0 +1,297 @@
+// ============= src/checkpoint.rs =============
+use crate::github::ExportedFile;
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+struct CheckpointHeader {
+    owner: String,
+    repo: String,
+    git_ref: String,
+    /// `FetchOptions::filter_digest` of the run that wrote it; missing from checkpoints
+    /// written before it was recorded, which are resumed as they are
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    filters: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct CheckpointEntry {
+    path: String,
+    content: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    symlink_target: Option<String>,
+}
+
+impl CheckpointEntry {
+    fn from_file(file: &ExportedFile) -> Self {
+        Self {
+            path: file.path.clone(),
+            content: file.content.clone(),
+            symlink_target: file.symlink_target.clone(),
+        }
+    }
+
+    fn into_file(self) -> ExportedFile {
+        ExportedFile {
+            path: self.path,
+            content: self.content,
+            symlink_target: self.symlink_target,
+        }
+    }
+}
+
+/// Records fetched files so an interrupted export can pick up where it left off.
+///
+/// Stored as JSON lines: a header identifying owner/repo/ref and the filters in effect,
+/// then one line per fetched file. Appending one line per file keeps writes cheap and means a crash
+/// mid-write loses at most the last, partial line.
+pub struct Checkpoint {
+    path: PathBuf,
+    files: Vec<ExportedFile>,
+    writer: File,
+}
+
+impl Checkpoint {
+    /// Checkpoint file name for a target; distinct targets never share a file
+    pub fn file_name(owner: &str, repo: &str, git_ref: &str) -> String {
+        let sanitize = |s: &str| s.replace(['/', '\\', ':'], "_");
+        format!(
+            ".repo_exporter_{}_{}_{}.checkpoint.jsonl",
+            sanitize(owner),
+            sanitize(repo),
+            sanitize(git_ref)
+        )
+    }
+
+    /// Whether `dir` holds a checkpoint for any ref of the repository
+    pub fn exists_for_repo(dir: &Path, owner: &str, repo: &str) -> bool {
+        const SUFFIX: &str = ".checkpoint.jsonl";
+        let name = Self::file_name(owner, repo, "");
+        let prefix = name.trim_end_matches(SUFFIX);
+
+        fs::read_dir(dir).is_ok_and(|entries| {
+            entries.flatten().any(|entry| {
+                entry
+                    .file_name()
+                    .to_str()
+                    .is_some_and(|name| name.starts_with(prefix) && name.ends_with(SUFFIX))
+            })
+        })
+    }
+
+    /// Opens the checkpoint for a target in `dir`, loading any files recorded by a previous
+    /// run. A checkpoint written under different `filters` (see
+    /// `FetchOptions::filter_digest`) would mix two sets of files, so it's discarded with a
+    /// warning and the export starts over.
+    pub fn open(dir: &Path, owner: &str, repo: &str, git_ref: &str, filters: &str) -> Result<Self> {
+        let path = dir.join(Self::file_name(owner, repo, git_ref));
+        let header = CheckpointHeader {
+            owner: owner.to_string(),
+            repo: repo.to_string(),
+            git_ref: git_ref.to_string(),
+            filters: Some(filters.to_string()),
+        };
+
+        let files = if path.exists() {
+            Self::load(&path, &header)?
+        } else {
+            Vec::new()
+        };
+
+        // Rewrite the file from what was loaded so a torn trailing line doesn't linger
+        let mut writer = File::create(&path)?;
+        writeln!(writer, "{}", serde_json::to_string(&header)?)?;
+        for file in &files {
+            writeln!(writer, "{}", serde_json::to_string(&CheckpointEntry::from_file(file))?)?;
+        }
+        writer.flush()?;
+
+        let writer = OpenOptions::new().append(true).open(&path)?;
+
+        Ok(Self { path, files, writer })
+    }
+
+    fn load(path: &Path, expected: &CheckpointHeader) -> Result<Vec<ExportedFile>> {
+        let mut lines = BufReader::new(File::open(path)?).lines();
+
+        let header: CheckpointHeader = match lines.next() {
+            Some(line) => serde_json::from_str(&line?)
+                .map_err(|e| anyhow!("Corrupt checkpoint {}: {}", path.display(), e))?,
+            None => return Ok(Vec::new()),
+        };
+        if (&header.owner, &header.repo, &header.git_ref) != (&expected.owner, &expected.repo, &expected.git_ref) {
+            return Err(anyhow!("Checkpoint {} belongs to a different export target", path.display()));
+        }
+        if header.filters.is_some() && header.filters != expected.filters {
+            tracing::warn!(
+                checkpoint = %path.display(),
+                "checkpoint was written with different filters; starting the export over"
+            );
+            return Ok(Vec::new());
+        }
+
+        let mut files = Vec::new();
+        for line in lines {
+            match serde_json::from_str::<CheckpointEntry>(&line?) {
+                Ok(entry) => files.push(entry.into_file()),
+                // A partial trailing line from an interrupted write
+                Err(_) => break,
+            }
+        }
+
+        Ok(files)
+    }
+
+    /// Renames a checkpoint recorded under `from_ref` to `to_ref`, unless there's none or
+    /// one for `to_ref` already exists. Default-branch exports used to be recorded under
+    /// `HEAD` and now use the branch's name; this carries their progress over.
+    pub fn migrate(dir: &Path, owner: &str, repo: &str, from_ref: &str, to_ref: &str) -> Result<()> {
+        let from = dir.join(Self::file_name(owner, repo, from_ref));
+        let to = dir.join(Self::file_name(owner, repo, to_ref));
+        if from == to || !from.exists() || to.exists() {
+            return Ok(());
+        }
+
+        let content = fs::read_to_string(&from)?;
+        let (header, rest) = content.split_once('\n').unwrap_or((&content, ""));
+        let mut header: CheckpointHeader = serde_json::from_str(header)
+            .map_err(|e| anyhow!("Corrupt checkpoint {}: {}", from.display(), e))?;
+        header.git_ref = to_ref.to_string();
+        fs::write(&to, format!("{}\n{}", serde_json::to_string(&header)?, rest))?;
+        fs::remove_file(&from)?;
+        tracing::info!(from = %from.display(), to = %to.display(), "moved checkpoint to its branch name");
+        Ok(())
+    }
+
+    pub fn files(&self) -> &[ExportedFile] {
+        &self.files
+    }
+
+    pub fn paths(&self) -> HashSet<String> {
+        self.files.iter().map(|file| file.path.clone()).collect()
+    }
+
+    /// Appends a freshly fetched file to the checkpoint
+    pub fn record(&mut self, file: &ExportedFile) -> Result<()> {
+        writeln!(self.writer, "{}", serde_json::to_string(&CheckpointEntry::from_file(file))?)?;
+        self.writer.flush()?;
+        self.files.push(file.clone());
+        Ok(())
+    }
+
+    /// Every recorded file in path order, which matches the order of a git tree
+    pub fn sorted_files(&self) -> Vec<ExportedFile> {
+        let mut files = self.files.clone();
+        files.sort_by(|a, b| a.path.cmp(&b.path));
+        files
+    }
+
+    /// Deletes the checkpoint; call once the export has been written successfully
+    pub fn remove(self) -> Result<()> {
+        let Checkpoint { path, writer, .. } = self;
+        drop(writer);
+        fs::remove_file(&path)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn file(path: &str, content: &str) -> ExportedFile {
+        ExportedFile::new(path, content)
+    }
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("repo_exporter_checkpoint_{}_{}", name, std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_checkpoint_resumes_recorded_files() {
+        let dir = temp_dir("resume");
+
+        let mut checkpoint = Checkpoint::open(&dir, "owner", "repo", "HEAD", "filters").unwrap();
+        checkpoint.record(&file("src/main.rs", "fn main() {}")).unwrap();
+        drop(checkpoint);
+
+        let mut resumed = Checkpoint::open(&dir, "owner", "repo", "HEAD", "filters").unwrap();
+        assert!(resumed.paths().contains("src/main.rs"));
+        resumed.record(&file("README.md", "# Hi")).unwrap();
+
+        let files = resumed.sorted_files();
+        assert_eq!(files, vec![file("README.md", "# Hi"), file("src/main.rs", "fn main() {}")]);
+
+        resumed.remove().unwrap();
+        assert!(!dir.join(Checkpoint::file_name("owner", "repo", "HEAD")).exists());
+
+        fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_checkpoint_ignores_torn_trailing_line() {
+        let dir = temp_dir("torn");
+
+        let mut checkpoint = Checkpoint::open(&dir, "owner", "repo", "main", "filters").unwrap();
+        checkpoint.record(&file("a.rs", "a")).unwrap();
+        drop(checkpoint);
+
+        let path = dir.join(Checkpoint::file_name("owner", "repo", "main"));
+        let mut raw = OpenOptions::new().append(true).open(&path).unwrap();
+        write!(raw, "{{\"path\":\"b.rs\",\"cont").unwrap();
+        drop(raw);
+
+        let resumed = Checkpoint::open(&dir, "owner", "repo", "main", "filters").unwrap();
+        assert_eq!(resumed.files(), &[file("a.rs", "a")]);
+
+        fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_checkpoint_starts_over_under_different_filters() {
+        let dir = temp_dir("filters");
+
+        let mut checkpoint = Checkpoint::open(&dir, "owner", "repo", "main", "all").unwrap();
+        checkpoint.record(&file("a.rs", "a")).unwrap();
+        drop(checkpoint);
+
+        let resumed = Checkpoint::open(&dir, "owner", "repo", "main", "only-docs").unwrap();
+        assert!(resumed.files().is_empty());
+
+        fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_checkpoint_migrates_from_head_to_branch_name() {
+        let dir = temp_dir("migrate");
+
+        let mut checkpoint = Checkpoint::open(&dir, "owner", "repo", "HEAD", "filters").unwrap();
+        checkpoint.record(&file("a.rs", "a")).unwrap();
+        drop(checkpoint);
+
+        Checkpoint::migrate(&dir, "owner", "repo", "HEAD", "main").unwrap();
+        assert!(!dir.join(Checkpoint::file_name("owner", "repo", "HEAD")).exists());
+        let resumed = Checkpoint::open(&dir, "owner", "repo", "main", "filters").unwrap();
+        assert_eq!(resumed.files(), &[file("a.rs", "a")]);
+
+        fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_checkpoint_names_differ_per_target() {
+        assert_ne!(
+            Checkpoint::file_name("owner", "repo", "main"),
+            Checkpoint::file_name("owner", "repo", "v1.0")
+        );
+        assert_eq!(
+            Checkpoint::file_name("owner", "repo", "feature/x"),
+            ".repo_exporter_owner_repo_feature_x.checkpoint.jsonl"
+        );
+    }
+}
@@ -0,0 +1,310 @@
+// ============= src/cli.rs =============
+use clap::{ArgAction, Parser};
+use regex::Regex;
+use std::path::PathBuf;
+use repo_exporter::export::{ContentFilter, ExportFormat, JsonStyle, DEFAULT_LARGEST_FILES};
+
+/// Export GitHub repositories to Markdown format
+#[derive(Debug, Parser)]
+#[command(name = "repo_exporter", version, about)]
+pub struct Cli {
+    /// Repositories to export (URL or owner/repo). Prompts interactively when none are given.
+    #[arg(value_name = "REPO")]
+    pub repos: Vec<String>,
+
+    /// Also export the repositories listed in this file, one per line
+    /// (blank lines and lines starting with `#` are ignored)
+    #[arg(long, value_name = "PATH")]
+    pub repos_file: Option<PathBuf>,
+
+    /// Also export the repositories listed on stdin, one per line, like `--repos-file`;
+    /// e.g. `cat repos.txt | repo_exporter --stdin`
+    #[arg(long)]
+    pub stdin: bool,
+
+    /// Write all repositories into a single export with a section per repository,
+    /// instead of one export each
+    #[arg(long)]
+    pub combined: bool,
+
+    /// Branch, tag or commit to export instead of the default branch.
+    /// `latest` exports the most recent release (the default branch if there are none).
+    #[arg(long = "ref", value_name = "REF")]
+    pub git_ref: Option<String>,
+
+    /// Export the repository as it was at this point: the last commit on `--ref` (or the
+    /// default branch) at or before a date (2025-01-01, meaning the end of that day),
+    /// a timestamp (2025-01-01T12:00:00Z) or a relative age (7d, 2w)
+    #[arg(long, value_name = "WHEN")]
+    pub at: Option<String>,
+
+    /// Only export the files changed between two refs, as they are at the second,
+    /// e.g. `v1.0...v1.1`. Renamed and deleted files are listed in the header.
+    #[arg(long, value_name = "BASE...HEAD", value_parser = parse_compare, conflicts_with_all = ["git_ref", "at", "combined"])]
+    pub compare: Option<(String, String)>,
+
+    /// Output format: markdown, json, html, text or zip. Defaults to markdown, or to the
+    /// repository's `export.toml` preference. Repeat it or separate formats with commas
+    /// to write several from one fetch.
+    #[arg(long = "format", value_name = "FORMAT", value_delimiter = ',')]
+    pub formats: Vec<ExportFormat>,
+
+    /// JSON layout: pretty or compact. Defaults to compact when piping to stdout,
+    /// pretty otherwise.
+    #[arg(long, value_name = "STYLE")]
+    pub json_style: Option<JsonStyle>,
+
+    /// Write the export here instead of a timestamped file; `-` writes to stdout
+    #[arg(short, long, value_name = "PATH")]
+    pub output: Option<PathBuf>,
+
+    /// Write the export to stdout (same as `--output -`); status messages go to stderr
+    #[arg(long, conflicts_with = "output")]
+    pub stdout: bool,
+
+    /// Add to the end of the --output file instead of overwriting it, under a heading
+    /// for the repository (markdown and text only)
+    #[arg(long, requires = "output", conflicts_with = "combined")]
+    pub append: bool,
+
+    /// Compress the export with gzip, writing `<output>.gz` (or a gzip stream to stdout)
+    #[arg(long, conflicts_with = "append")]
+    pub gzip: bool,
+
+    /// Only export files last modified after this point: a date (2025-01-31),
+    /// a timestamp (2025-01-31T12:00:00Z) or a relative age (7d, 12h, 2w).
+    /// Costs one extra API request per eligible file.
+    #[arg(long)]
+    pub since: Option<String>,
+
+    /// Only export paths matching a gitignore-style pattern (repeatable).
+    /// Combines with `--lang` presets; `--exclude` still applies.
+    #[arg(long = "include", value_name = "PATTERN")]
+    pub includes: Vec<String>,
+
+    /// Only export files for a language preset (repeatable): rust, python, go, web or docs
+    #[arg(long = "lang", value_name = "PRESET")]
+    pub langs: Vec<String>,
+
+    /// Leave out paths matching a gitignore-style pattern (repeatable),
+    /// e.g. `--exclude '*.md' --exclude fixtures/`
+    #[arg(long = "exclude", value_name = "PATTERN")]
+    pub excludes: Vec<String>,
+
+    /// Only export files whose content matches this regular expression, e.g. `TODO|FIXME`.
+    /// Every eligible file is still downloaded, so this saves no API requests.
+    #[arg(long, value_name = "REGEX")]
+    pub content_grep: Option<Regex>,
+
+    /// Export lockfiles, source maps and minified files too; by default they're skipped
+    /// (and listed as skipped) as noise
+    #[arg(long)]
+    pub include_generated: bool,
+
+    /// Export files and directories whose name starts with `.`, such as `.github/` and
+    /// `.vscode/`; pass `--include-hidden false` to leave them out, apart from those
+    /// given to `--allow-hidden`
+    #[arg(long, value_name = "BOOL", default_value_t = true, action = ArgAction::Set)]
+    pub include_hidden: bool,
+
+    /// A hidden file or directory name to keep with `--include-hidden false`, e.g.
+    /// `.github`. Repeatable; replaces the default of `.gitignore` and `.gitattributes`.
+    #[arg(long = "allow-hidden", value_name = "NAME")]
+    pub allow_hidden: Vec<String>,
+
+    /// Only export this directory, e.g. `crates/core`. Only its subtree is listed, which
+    /// keeps the initial listing small on large monorepos.
+    #[arg(long, value_name = "DIR", value_parser = parse_subpath)]
+    pub subpath: Option<String>,
+
+    /// Only export files at most this many directories deep (0 = root files only)
+    #[arg(long, value_name = "DEPTH")]
+    pub max_depth: Option<usize>,
+
+    /// Skip files larger than this many bytes (default 1048576)
+    #[arg(long, value_name = "BYTES")]
+    pub max_file_size: Option<usize>,
+
+    /// Skip files with more than this many lines
+    #[arg(long, value_name = "LINES")]
+    pub max_lines: Option<usize>,
+
+    /// Skip files where more than this percentage of the characters are control
+    /// characters, as binary data that happens to decode as text (default 10; 100 keeps them)
+    #[arg(long, value_name = "PERCENT", value_parser = clap::value_parser!(u8).range(1..=100))]
+    pub binary_threshold: Option<u8>,
+
+    /// Stop adding files once the export would exceed this many bytes in total
+    #[arg(long, value_name = "BYTES")]
+    pub max_total_bytes: Option<usize>,
+
+    /// Only export the first N eligible files in path order
+    #[arg(long = "max-total-files", value_name = "N")]
+    pub max_files: Option<usize>,
+
+    /// Precede each file section with YAML front matter (path, language, bytes)
+    #[arg(long)]
+    pub front_matter: bool,
+
+    /// Soft-wrap lines longer than this many columns in Markdown output,
+    /// marking each continuation with `↪`
+    #[arg(long, value_name = "COLUMNS", value_parser = clap::value_parser!(u32).range(1..))]
+    pub wrap: Option<u32>,
+
+    /// Make Markdown output denser (repeatable): strip-blank-lines,
+    /// collapse-blank-lines, strip-comments or trim-whitespace. Noted in the export header.
+    #[arg(long = "filter", value_name = "FILTER")]
+    pub filters: Vec<ContentFilter>,
+
+    /// Convert Markdown file contents to Unicode NFC, so text that looks the same is
+    /// encoded the same way. Noted in the export header.
+    #[arg(long)]
+    pub normalize_unicode: bool,
+
+    /// Tag Markdown code fences with each file's language (e.g. ```` ```rust ````)
+    /// instead of `text`. Ambiguous extensions like `.h` follow the repository's
+    /// languages, which costs one request per repository.
+    #[arg(long)]
+    pub fence_languages: bool,
+
+    /// Use INFO as the full opening-fence info string for files with extension (or
+    /// name) EXT, e.g. `rs=rust,ignore` for mdBook (repeatable)
+    #[arg(long = "fence-info", value_name = "EXT=INFO", value_parser = parse_fence_info)]
+    pub fence_info: Vec<(String, String)>,
+
+    /// Layout for each file's section in Markdown output, using `{path}`, `{language}`,
+    /// `{bytes}` and `{content}` (the code block); `\n` starts a new line.
+    /// Default: `## {path}\n\n{content}`
+    #[arg(long, value_name = "TEMPLATE", value_parser = parse_section_template)]
+    pub section_template: Option<String>,
+
+    /// Write this line between file sections in Markdown and text output, e.g. `---`;
+    /// `{path}` names the next file and `\n` starts a new line
+    #[arg(long, value_name = "BANNER", value_parser = parse_separator)]
+    pub separator: Option<String>,
+
+    /// List this many of the largest files, with their sizes, at the end of the
+    /// export (0 for none)
+    #[arg(long, value_name = "N", default_value_t = DEFAULT_LARGEST_FILES)]
+    pub largest_files: usize,
+
+    /// Head Markdown file sections by file name instead of full path, adding the
+    /// parent directory where names collide
+    #[arg(long)]
+    pub flat: bool,
+
+    /// Shift every Markdown heading down N levels (the title becomes `#` × (1 + N),
+    /// file headings `#` × (2 + N)), to embed the export in a larger document.
+    /// Headings stop at level 6.
+    #[arg(long, value_name = "N", default_value_t = 0, value_parser = clap::value_parser!(u8).range(0..=5))]
+    pub heading_offset: u8,
+
+    /// Export renamed or transferred repositories under their new name, looked up with
+    /// one request per repository. Without this, a rename is only logged as a warning
+    /// and the export keeps the name it was given.
+    #[arg(long)]
+    pub follow_renames: bool,
+
+    /// Print roughly how many API requests each export would make, then exit
+    /// without exporting. Costs about one request per repository.
+    #[arg(long)]
+    pub estimate: bool,
+
+    /// List each repository's branches and tags, marking the default branch, then exit
+    /// without exporting; for picking a `--ref`
+    #[arg(long, conflicts_with_all = ["estimate", "index"])]
+    pub list_refs: bool,
+
+    /// Write a table of the eligible files (path, language and bytes) instead of their
+    /// contents, as markdown, text or json. Sizes come from the tree, so it costs about
+    /// one request per repository.
+    #[arg(long, conflicts_with_all = ["compare", "combined", "append"])]
+    pub index: bool,
+
+    /// Also count each file's lines in the `--index`, which fetches every file
+    #[arg(long, requires = "index")]
+    pub index_lines: bool,
+
+    /// Once the export is done, write the blob SHA of every eligible file to this
+    /// manifest, for a later `--incremental` export
+    #[arg(long, value_name = "FILE", conflicts_with_all = ["compare", "combined", "index"])]
+    pub manifest: Option<PathBuf>,
+
+    /// Only export the files that are new or changed since the export that wrote this
+    /// manifest; removed files are listed in the header. Pass the same path to
+    /// `--manifest` to update it.
+    #[arg(long, value_name = "MANIFEST", conflicts_with_all = ["compare", "combined", "index"])]
+    pub incremental: Option<PathBuf>,
+
+    /// Also export the repository's wiki pages, under `_wiki/`. Needs `git` installed.
+    #[arg(long)]
+    pub wiki: bool,
+
+    /// Ignore the defaults a repository sets in its `export.toml`
+    #[arg(long)]
+    pub ignore_repo_config: bool,
+
+    /// Print plain ASCII status messages instead of emoji (also `EXPORTER_NO_EMOJI`)
+    #[arg(long)]
+    pub no_emoji: bool,
+
+    /// Exit with a non-zero status if any file failed to fetch or decode
+    #[arg(long)]
+    pub fail_on_error: bool,
+
+    /// Time each file's fetch and list the slowest once each export is done
+    #[arg(long)]
+    pub timing: bool,
+
+    /// Run this shell command after each export is written, with the output path
+    /// appended as an argument and in `$EXPORT_FILE`. Exits with status 3 if it fails.
+    #[arg(long, value_name = "CMD")]
+    pub post_hook: Option<String>,
+}
+
+/// Requires `{content}`, and turns `\n` into real newlines since they're awkward to type in a shell argument
+fn parse_section_template(value: &str) -> Result<String, String> {
+    if !value.contains("{content}") {
+        return Err("the template needs a {content} placeholder, or file contents are left out".to_string());
+    }
+    Ok(value.replace("\\n", "\n"))
+}
+
+/// Turns `\n` into real newlines, as for `--section-template`
+fn parse_separator(value: &str) -> Result<String, String> {
+    if value.trim().is_empty() {
+        return Err("expected a separator such as --- or '===== {path} ====='".to_string());
+    }
+    Ok(value.replace("\\n", "\n"))
+}
+
+/// Drops leading and trailing slashes, so `/crates/core/` names the same directory
+fn parse_subpath(value: &str) -> Result<String, String> {
+    let subpath = value.trim().trim_matches('/');
+    if subpath.is_empty() {
+        return Err("expected a directory such as crates/core; omit --subpath to export the whole repository".to_string());
+    }
+    Ok(subpath.to_string())
+}
+
+/// Parses `BASE...HEAD`, as in GitHub's compare URLs
+fn parse_compare(value: &str) -> Result<(String, String), String> {
+    match value.split_once("...") {
+        Some((base, head)) if !base.trim().is_empty() && !head.trim().is_empty() => {
+            Ok((base.trim().to_string(), head.trim().to_string()))
+        }
+        _ => Err(format!("expected BASE...HEAD, got '{}'", value)),
+    }
+}
+
+/// Parses `EXT=INFO`, dropping a leading dot from the extension
+fn parse_fence_info(value: &str) -> Result<(String, String), String> {
+    let (key, info) = value
+        .split_once('=')
+        .ok_or_else(|| format!("expected EXT=INFO, got '{}'", value))?;
+    let key = key.trim().trim_start_matches('.');
+    if key.is_empty() || info.trim().is_empty() {
+        return Err(format!("expected EXT=INFO, got '{}'", value));
+    }
+    Ok((key.to_string(), info.trim().to_string()))
+}
@@ -0,0 +1,131 @@
+// ============= src/export/archive.rs =============
+use super::context::ExportContext;
+use super::options::ExportOptions;
+use super::text::write_text_header;
+use crate::github::{ExportedFile, FailedFile};
+use anyhow::Result;
+use std::fs::File;
+use std::io::Write;
+use std::path::Path;
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipWriter};
+
+/// Lists the files that are missing from the archive
+pub const SKIPPED_FILE: &str = "SKIPPED.txt";
+/// Holds the export header: the repository, ref, commit, license and change notes
+pub const EXPORT_INFO_FILE: &str = "EXPORT.txt";
+
+/// Writes repository files into a `.zip` that reproduces the repository's directory layout.
+///
+/// Symlinks are stored as symlinks. The header other formats open with (see
+/// `write_text_header`) goes in `EXPORT.txt`, and files that couldn't be fetched or
+/// decoded (typically binaries) are listed in `SKIPPED.txt`, both at the archive root
+/// unless the repository has a file of that name itself.
+pub(super) fn write_zip_export(
+    path: &Path,
+    context: &ExportContext,
+    files: &[ExportedFile],
+    failed: &[FailedFile],
+    options: &ExportOptions,
+) -> Result<()> {
+    let mut entries = Vec::with_capacity(files.len() + 1);
+    if let Some(info) = export_info(context, files, "", options)? {
+        entries.push(info);
+    }
+    entries.extend_from_slice(files);
+    write_zip_entries(path, &entries, failed)
+}
+
+/// `EXPORT.txt` under `prefix` for a repository's `files`, or `None` if they include
+/// one already
+pub(super) fn export_info(
+    context: &ExportContext,
+    files: &[ExportedFile],
+    prefix: &str,
+    options: &ExportOptions,
+) -> Result<Option<ExportedFile>> {
+    if files.iter().any(|file| file.path == EXPORT_INFO_FILE) {
+        return Ok(None);
+    }
+    let mut header = Vec::new();
+    write_text_header(&mut header, context, options)?;
+    Ok(Some(ExportedFile::new(format!("{}{}", prefix, EXPORT_INFO_FILE), String::from_utf8(header)?)))
+}
+
+/// Writes repository files into a `.zip`, listing `failed` in `SKIPPED.txt`, but without
+/// the `EXPORT.txt` header
+#[deprecated(note = "use `export::export` with `ExportFormat::Zip`, which also writes `EXPORT.txt`")]
+pub fn write_zip(path: &Path, files: &[ExportedFile], failed: &[FailedFile]) -> Result<()> {
+    write_zip_entries(path, files, failed)
+}
+
+/// Writes `files` as they are, plus `SKIPPED.txt` for `failed`
+pub(super) fn write_zip_entries(path: &Path, files: &[ExportedFile], failed: &[FailedFile]) -> Result<()> {
+    let mut zip = ZipWriter::new(File::create(path)?);
+    let options = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+
+    for file in files {
+        match &file.symlink_target {
+            Some(target) => zip.add_symlink(file.path.as_str(), target.as_str(), options)?,
+            None => {
+                zip.start_file(file.path.as_str(), options)?;
+                zip.write_all(file.content.as_bytes())?;
+            }
+        }
+    }
+
+    if !failed.is_empty() && !files.iter().any(|file| file.path == SKIPPED_FILE) {
+        zip.start_file(SKIPPED_FILE, options)?;
+        writeln!(zip, "Files left out of this export because they couldn't be fetched or decoded as text:\n")?;
+        for failure in failed {
+            writeln!(zip, "{}: {}", failure.path, failure.error)?;
+        }
+    }
+
+    zip.finish()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Read;
+    use zip::ZipArchive;
+
+    #[test]
+    fn test_write_zip() {
+        let files = vec![
+            ExportedFile::new("README.md", "# Demo\n"),
+            ExportedFile::new("src/main.rs", "fn main() {}\n"),
+            ExportedFile::symlink("docs/README.md", "../README.md"),
+        ];
+        let failed = vec![FailedFile {
+            path: "assets/logo.png".to_string(),
+            error: "File content is not valid UTF-8".to_string(),
+        }];
+
+        let mut context = ExportContext::new("owner", "repo", ExportOptions::default().export_time());
+        context.default_branch = Some("main".to_string());
+        context.commit_sha = Some("0123abcd".to_string());
+
+        let path = std::env::temp_dir().join(format!("repo_exporter_zip_{}.zip", std::process::id()));
+        write_zip_export(&path, &context, &files, &failed, &ExportOptions::default()).unwrap();
+        let mut archive = ZipArchive::new(File::open(&path).unwrap()).unwrap();
+
+        let mut info = String::new();
+        archive.by_name(EXPORT_INFO_FILE).unwrap().read_to_string(&mut info).unwrap();
+        assert!(info.starts_with("Repository Export: owner/repo\nBranch: main · Commit: 0123abcd\n"));
+
+        let mut main = String::new();
+        archive.by_name("src/main.rs").unwrap().read_to_string(&mut main).unwrap();
+        assert_eq!(main, "fn main() {}\n");
+
+        assert!(archive.by_name("docs/README.md").unwrap().is_symlink());
+
+        let mut skipped = String::new();
+        archive.by_name(SKIPPED_FILE).unwrap().read_to_string(&mut skipped).unwrap();
+        assert!(skipped.contains("assets/logo.png: File content is not valid UTF-8"));
+
+        std::fs::remove_file(&path).ok();
+    }
+}
@@ -0,0 +1,161 @@
+// ============= src/export/combined.rs =============
+use super::archive::{export_info, write_zip_entries};
+use super::context::ExportContext;
+use super::gzip::{gzip_file, output_path, with_compression};
+use super::json::{json_files, json_largest_files, write_document, JsonContext, JsonFile, JsonFileSize};
+use super::markdown::{heading, write_context_header, write_file_sections, write_header_notes, write_largest_files, write_license_text};
+use super::options::{ExportFormat, ExportOptions};
+use super::stats::{export_stats, ExportStats};
+use crate::github::{ExportedFile, FailedFile};
+use anyhow::{anyhow, Result};
+use serde::Serialize;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+/// One repository's files within a combined, multi-repository export
+pub struct RepoSection<'a> {
+    pub context: &'a ExportContext,
+    pub files: &'a [ExportedFile],
+    pub failed: &'a [FailedFile],
+}
+
+#[derive(Serialize)]
+struct CombinedJsonExport<'a> {
+    exported_at: String,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    interrupted: bool,
+    repositories: Vec<CombinedJsonRepo<'a>>,
+}
+
+#[derive(Serialize)]
+struct CombinedJsonRepo<'a> {
+    #[serde(flatten)]
+    context: JsonContext<'a>,
+    stats: ExportStats,
+    files: Vec<JsonFile<'a>>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    largest_files: Vec<JsonFileSize<'a>>,
+}
+
+/// Writes several repositories into a single export at `path`, one section per repository,
+/// and returns the path written, which has `.gz` added with `options.gzip`
+pub fn write_combined(
+    path: &Path,
+    format: ExportFormat,
+    sections: &[RepoSection],
+    options: &ExportOptions,
+) -> Result<PathBuf> {
+    match format {
+        ExportFormat::Zip => {
+            write_combined_zip(path, sections, options)?;
+            if options.gzip {
+                return gzip_file(path);
+            }
+            Ok(path.to_path_buf())
+        }
+        _ => {
+            let path = output_path(path, options.gzip);
+            write_combined_to(BufWriter::new(File::create(&path)?), format, sections, options)?;
+            Ok(path)
+        }
+    }
+}
+
+/// Like `write_combined`, but to any writer. Zip archives need a seekable file, so
+/// `ExportFormat::Zip` is rejected.
+pub fn write_combined_to<W: Write>(
+    out: W,
+    format: ExportFormat,
+    sections: &[RepoSection],
+    options: &ExportOptions,
+) -> Result<()> {
+    match format {
+        ExportFormat::Markdown => with_compression(out, options.gzip, |out| write_combined_markdown(out, sections, options)),
+        ExportFormat::Json => with_compression(out, options.gzip, |out| write_combined_json(out, sections, options)),
+        ExportFormat::Html | ExportFormat::Text => Err(anyhow!("Combined exports can't be written as {}; use markdown, json or zip", format.extension())),
+        ExportFormat::Zip => Err(anyhow!("Zip exports can only be written to a file")),
+    }
+}
+
+fn write_combined_markdown<W: Write>(mut file: W, sections: &[RepoSection], options: &ExportOptions) -> Result<()> {
+    let names: Vec<_> = sections.iter().map(|s| s.context.name()).collect();
+    writeln!(file, "{} Repository Export: {}\n", heading(1, options), names.join(", "))?;
+    write_header_notes(&mut file, options)?;
+
+    for section in sections {
+        writeln!(file, "{} {}\n", heading(2, options), section.context.name())?;
+        write_context_header(&mut file, section.context)?;
+        write_license_text(&mut file, section.context, options)?;
+        write_file_sections(&mut file, section.context, section.files, options, &heading(3, options))?;
+        write_largest_files(&mut file, section.files, options, &heading(3, options))?;
+    }
+
+    file.flush()?;
+    Ok(())
+}
+
+fn write_combined_json<W: Write>(out: W, sections: &[RepoSection], options: &ExportOptions) -> Result<()> {
+    let export = CombinedJsonExport {
+        exported_at: options.export_time().to_rfc3339(),
+        interrupted: options.interrupted,
+        repositories: sections
+            .iter()
+            .map(|section| CombinedJsonRepo {
+                context: section.context.into(),
+                stats: export_stats(section.files),
+                files: json_files(section.files),
+                largest_files: json_largest_files(section.files, options),
+            })
+            .collect(),
+    };
+
+    write_document(out, &export, options.json_style)
+}
+
+/// Each repository's files go under an `owner/repo/` directory, with its own `EXPORT.txt`
+fn write_combined_zip(path: &Path, sections: &[RepoSection], options: &ExportOptions) -> Result<()> {
+    let mut files = Vec::new();
+    let mut failed = Vec::new();
+
+    for section in sections {
+        let prefix = format!("{}/", section.context.name());
+        files.extend(export_info(section.context, section.files, &prefix, options)?);
+        files.extend(section.files.iter().map(|file| ExportedFile {
+            path: format!("{}{}", prefix, file.path),
+            ..file.clone()
+        }));
+        failed.extend(section.failed.iter().map(|failure| FailedFile {
+            path: format!("{}{}", prefix, failure.path),
+            error: failure.error.clone(),
+        }));
+    }
+
+    write_zip_entries(path, &files, &failed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_write_combined_markdown() {
+        let first = vec![ExportedFile::new("README.md", "# First")];
+        let second = vec![ExportedFile::new("src/lib.rs", "pub fn lib() {}")];
+        let now = ExportOptions::default().export_time();
+        let (first_context, second_context) = (ExportContext::new("owner", "first", now), ExportContext::new("owner", "second", now));
+        let sections = [
+            RepoSection { context: &first_context, files: &first, failed: &[] },
+            RepoSection { context: &second_context, files: &second, failed: &[] },
+        ];
+
+        let path = std::env::temp_dir().join(format!("repo_exporter_combined_{}.md", std::process::id()));
+        write_combined(&path, ExportFormat::Markdown, &sections, &ExportOptions::default()).unwrap();
+        let output = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).ok();
+
+        assert!(output.starts_with("# Repository Export: owner/first, owner/second\n"));
+        assert!(output.contains("## owner/first\n\n### README.md\n"));
+        assert!(output.contains("## owner/second\n\n### src/lib.rs\n"));
+    }
+}
@@ -0,0 +1,115 @@
+// ============= src/export/context.rs =============
+use crate::github::{ChangedFile, ExportedFile, License, Provenance};
+use chrono::{DateTime, FixedOffset};
+use serde::Serialize;
+
+/// Everything the writers know about an export besides its files: the repository,
+/// where the snapshot came from, and when it was taken. New header fields go here
+/// rather than into every writer's signature.
+#[derive(Debug, Clone)]
+pub struct ExportContext {
+    pub owner: String,
+    pub repo: String,
+    /// The branch, tag or commit requested; `None` means the default branch
+    pub git_ref: Option<String>,
+    /// `None` when it couldn't be looked up, like `commit_sha` and `description`
+    pub default_branch: Option<String>,
+    pub commit_sha: Option<String>,
+    /// The first line of the commit's message, shown with the commit
+    pub commit_subject: Option<String>,
+    pub description: Option<String>,
+    /// The repository is read-only; noted in the header
+    pub archived: bool,
+    /// Stated in the header, or that there's none once the repository is known
+    pub license: Option<License>,
+    /// The license file, shown in full near the top whatever the filters leave out
+    pub license_file: Option<ExportedFile>,
+    /// GitHub language names, most-used first. Picks the fence language for ambiguous
+    /// extensions like `.h`.
+    pub languages: Vec<String>,
+    /// Set for a diff export, which only has the files changed between two refs
+    pub changes: Option<RefChanges>,
+    pub timestamp: DateTime<FixedOffset>,
+}
+
+impl ExportContext {
+    /// A context with just the repository and time; the rest is filled in as it's fetched
+    pub fn new(owner: impl Into<String>, repo: impl Into<String>, timestamp: DateTime<FixedOffset>) -> Self {
+        Self {
+            owner: owner.into(),
+            repo: repo.into(),
+            git_ref: None,
+            default_branch: None,
+            commit_sha: None,
+            commit_subject: None,
+            description: None,
+            archived: false,
+            license: None,
+            license_file: None,
+            languages: Vec::new(),
+            changes: None,
+            timestamp,
+        }
+    }
+
+    pub fn with_provenance(mut self, provenance: Provenance) -> Self {
+        self.git_ref = provenance.git_ref;
+        self.default_branch = Some(provenance.default_branch);
+        self.commit_sha = Some(provenance.commit_sha);
+        self.commit_subject = provenance.commit_subject;
+        self.description = provenance.description;
+        self.archived = provenance.archived;
+        self.license = provenance.license;
+        self
+    }
+
+    /// A line naming the license, or saying none was detected; `None` when the
+    /// repository's metadata wasn't fetched, so it isn't known
+    pub fn license_banner(&self) -> Option<String> {
+        self.default_branch.as_ref()?;
+        Some(match &self.license {
+            Some(license) => match license.identified() {
+                Some(spdx_id) => format!("License: {} ({})", spdx_id, license.name),
+                None => format!("License: {}; GitHub couldn't identify it, so check the license file", license.name),
+            },
+            None => "No license detected; check the repository's terms before reusing this code".to_string(),
+        })
+    }
+
+    /// `owner/repo`
+    pub fn name(&self) -> String {
+        format!("{}/{}", self.owner, self.repo)
+    }
+}
+
+/// What a diff export leaves out: the files that moved or were deleted between `base`
+/// and `head`, which can't be shown by their content at `head`. Listed in the header.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RefChanges {
+    pub base: String,
+    pub head: String,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub renamed: Vec<RenamedFile>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub deleted: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RenamedFile {
+    pub from: String,
+    pub to: String,
+}
+
+impl RefChanges {
+    pub fn new(base: impl Into<String>, head: impl Into<String>, changed: &[ChangedFile]) -> Self {
+        let renamed = changed
+            .iter()
+            .filter_map(|file| {
+                let from = file.previous_filename.as_ref().filter(|_| file.status == "renamed")?;
+                Some(RenamedFile { from: from.clone(), to: file.filename.clone() })
+            })
+            .collect();
+        let deleted = changed.iter().filter(|file| file.is_removed()).map(|file| file.filename.clone()).collect();
+        Self { base: base.into(), head: head.into(), renamed, deleted }
+    }
+}
@@ -0,0 +1,63 @@
+// ============= src/export/dedupe.rs =============
+use crate::github::ExportedFile;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+
+/// For each file, the index of the first earlier file with byte-identical content.
+///
+/// The first occurrence of any content maps to `None`. Symlinks have no content of
+/// their own and are never treated as duplicates.
+pub fn find_duplicates(files: &[ExportedFile]) -> Vec<Option<usize>> {
+    let mut first_seen: HashMap<[u8; 32], usize> = HashMap::new();
+
+    files
+        .iter()
+        .enumerate()
+        .map(|(i, file)| {
+            if file.symlink_target.is_some() {
+                return None;
+            }
+
+            let digest: [u8; 32] = Sha256::digest(file.content.as_bytes()).into();
+            match first_seen.get(&digest) {
+                Some(&original) => Some(original),
+                None => {
+                    first_seen.insert(digest, i);
+                    None
+                }
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn file(path: &str, content: &str) -> ExportedFile {
+        ExportedFile::new(path, content)
+    }
+
+    #[test]
+    fn test_find_duplicates() {
+        let files = vec![
+            file("LICENSE", "MIT"),
+            file("src/main.rs", "fn main() {}"),
+            file("crates/a/LICENSE", "MIT"),
+            file("crates/b/LICENSE", "MIT"),
+            file("crates/b/lib.rs", "fn main() {} "),
+        ];
+
+        assert_eq!(find_duplicates(&files), vec![None, None, Some(0), Some(0), None]);
+    }
+
+    #[test]
+    fn test_symlinks_are_not_duplicates() {
+        let files = vec![
+            ExportedFile::symlink("a", "target"),
+            ExportedFile::symlink("b", "target"),
+        ];
+
+        assert_eq!(find_duplicates(&files), vec![None, None]);
+    }
+}
@@ -0,0 +1,198 @@
+// ============= src/export/dispatch.rs =============
+use super::archive::write_zip_export;
+use super::context::ExportContext;
+use super::gzip::{gzip_file, output_path, with_compression};
+use super::html::write_html_to;
+use super::json::write_json_to;
+use super::markdown::{append_markdown_to, write_markdown_to};
+use super::options::{ExportFormat, ExportOptions};
+use super::text::write_text_to;
+use crate::github::{ExportedFile, FailedFile};
+use anyhow::{anyhow, Result};
+use std::fs::{File, OpenOptions};
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+/// Writes `files` to `output` in `format`, headed by what `context` knows about the
+/// repository (in `EXPORT.txt` for zip archives), and returns the path written.
+/// `failed` lists the files that couldn't be fetched; only zip archives record them
+/// (in `SKIPPED.txt`).
+///
+/// With `options.append`, the export is added to the end of `output` instead of
+/// replacing it (see `append_to`). With `options.gzip`, it's written to `output.gz`.
+pub fn export(
+    format: ExportFormat,
+    context: &ExportContext,
+    files: &[ExportedFile],
+    failed: &[FailedFile],
+    options: &ExportOptions,
+    output: &Path,
+) -> Result<PathBuf> {
+    match format {
+        _ if options.append && options.gzip => return Err(anyhow!("Compressed exports can't be appended to")),
+        _ if options.append => append_to(format, output, context, files, options)?,
+        ExportFormat::Zip if options.gzip => {
+            write_zip_export(output, context, files, failed, options)?;
+            return gzip_file(output);
+        }
+        ExportFormat::Zip => write_zip_export(output, context, files, failed, options)?,
+        _ => {
+            let path = output_path(output, options.gzip);
+            export_to(format, BufWriter::new(File::create(&path)?), context, files, options)?;
+            return Ok(path);
+        }
+    }
+    Ok(output.to_path_buf())
+}
+
+/// Writes the same files in each of `formats`, so several formats cost one fetch. With
+/// one format this is `export`; with several, each is written next to `output` with its
+/// own extension (e.g. `snapshot.md` and `snapshot.json`). Returns the paths written, in
+/// `formats` order.
+pub fn export_all(
+    formats: &[ExportFormat],
+    context: &ExportContext,
+    files: &[ExportedFile],
+    failed: &[FailedFile],
+    options: &ExportOptions,
+    output: &Path,
+) -> Result<Vec<PathBuf>> {
+    if let [format] = formats {
+        return Ok(vec![export(*format, context, files, failed, options, output)?]);
+    }
+    formats
+        .iter()
+        .map(|format| export(*format, context, files, failed, options, &output.with_extension(format.extension())))
+        .collect()
+}
+
+/// Like `export`, but to any writer, e.g. stdout. Zip archives need a seekable file, so
+/// `ExportFormat::Zip` is rejected. `options.append` doesn't apply; with `options.gzip`,
+/// the writer gets the gzip stream.
+pub fn export_to<W: Write>(
+    format: ExportFormat,
+    out: W,
+    context: &ExportContext,
+    files: &[ExportedFile],
+    options: &ExportOptions,
+) -> Result<()> {
+    if format == ExportFormat::Zip {
+        return Err(anyhow!("Zip exports can only be written to a file"));
+    }
+    with_compression(out, options.gzip, |out| match format {
+        ExportFormat::Markdown => write_markdown_to(out, context, files, options),
+        ExportFormat::Json => write_json_to(out, context, files, options),
+        ExportFormat::Html => write_html_to(out, context, files, options),
+        ExportFormat::Text => write_text_to(out, context, files, options),
+        ExportFormat::Zip => unreachable!("rejected above"),
+    })
+}
+
+/// Adds the export to the end of `output`, creating it if needed. Markdown skips the
+/// top-level title when the file already has content and heads the files with the
+/// repository's name; text exports have no title, so their repository line separates
+/// them. JSON, HTML and zip files are single documents and can't be appended to.
+fn append_to(
+    format: ExportFormat,
+    output: &Path,
+    context: &ExportContext,
+    files: &[ExportedFile],
+    options: &ExportOptions,
+) -> Result<()> {
+    let file = OpenOptions::new().create(true).append(true).open(output)?;
+    let started = file.metadata()?.len() > 0;
+    let mut out = BufWriter::new(file);
+    match format {
+        ExportFormat::Markdown => append_markdown_to(out, context, files, options, started),
+        ExportFormat::Text => {
+            if started {
+                writeln!(out)?;
+            }
+            write_text_to(out, context, files, options)
+        }
+        _ => Err(anyhow!("Only markdown and text exports can be appended to, not {}", format.extension())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_export_writes_each_format() {
+        let files = vec![ExportedFile::new("src/main.rs", "fn main() {}")];
+        let context = ExportContext::new("owner", "repo", ExportOptions::default().export_time());
+
+        for format in [ExportFormat::Markdown, ExportFormat::Json, ExportFormat::Html, ExportFormat::Text, ExportFormat::Zip] {
+            let path = std::env::temp_dir().join(format!("repo_exporter_dispatch_{}.{}", std::process::id(), format.extension()));
+            let written = export(format, &context, &files, &[], &ExportOptions::default(), &path).unwrap();
+            let bytes = std::fs::read(&written).unwrap();
+            std::fs::remove_file(&written).ok();
+
+            assert_eq!(written, path);
+            assert!(!bytes.is_empty(), "{:?}", format);
+        }
+
+        assert!(export_to(ExportFormat::Zip, Vec::new(), &context, &files, &ExportOptions::default()).is_err());
+    }
+
+    #[test]
+    fn test_export_all_writes_each_format_beside_output() {
+        let files = vec![ExportedFile::new("src/main.rs", "fn main() {}")];
+        let context = ExportContext::new("owner", "repo", ExportOptions::default().export_time());
+        let output = std::env::temp_dir().join(format!("repo_exporter_export_all_{}.md", std::process::id()));
+
+        let written = export_all(&[ExportFormat::Markdown, ExportFormat::Json], &context, &files, &[], &ExportOptions::default(), &output).unwrap();
+        let json = std::fs::read_to_string(&written[1]).unwrap();
+        for path in &written {
+            std::fs::remove_file(path).ok();
+        }
+
+        assert_eq!(written, vec![output.clone(), output.with_extension("json")]);
+        assert!(json.contains("\"src/main.rs\""));
+    }
+
+    #[test]
+    fn test_gzip_writes_beside_output() {
+        let files = vec![ExportedFile::new("src/main.rs", "fn main() {}")];
+        let context = ExportContext::new("owner", "repo", ExportOptions::default().export_time());
+        let options = ExportOptions { gzip: true, ..ExportOptions::default() };
+
+        for format in [ExportFormat::Markdown, ExportFormat::Zip] {
+            let path = std::env::temp_dir().join(format!("repo_exporter_gzip_export_{}.{}", std::process::id(), format.extension()));
+            let written = export(format, &context, &files, &[], &options, &path).unwrap();
+            let sizes = crate::export::gzip_sizes(&written).unwrap();
+            std::fs::remove_file(&written).ok();
+
+            assert_eq!(written, crate::export::gzip_path(&path));
+            assert!(!path.exists(), "{:?}", format);
+            assert!(sizes.uncompressed > 0);
+        }
+
+        let append = ExportOptions { append: true, ..options };
+        assert!(export(ExportFormat::Markdown, &context, &files, &[], &append, Path::new("unused.md")).is_err());
+    }
+
+    #[test]
+    fn test_append_skips_title_once_started() {
+        let now = ExportOptions::default().export_time();
+        let options = ExportOptions { append: true, ..ExportOptions::default() };
+        let path = std::env::temp_dir().join(format!("repo_exporter_append_{}.md", std::process::id()));
+        std::fs::remove_file(&path).ok();
+
+        let first = vec![ExportedFile::new("README.md", "# First")];
+        export(ExportFormat::Markdown, &ExportContext::new("owner", "first", now), &first, &[], &options, &path).unwrap();
+        let second = vec![ExportedFile::new("src/lib.rs", "pub fn lib() {}")];
+        export(ExportFormat::Markdown, &ExportContext::new("owner", "second", now), &second, &[], &options, &path).unwrap();
+        let output = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).ok();
+
+        assert!(output.starts_with("# Repository Export\n\n## owner/first\n\n### README.md\n"));
+        assert!(output.contains("```\n\n## owner/second\n\n### src/lib.rs\n"));
+        assert_eq!(output.matches("# Repository Export").count(), 1);
+
+        let json = std::env::temp_dir().join(format!("repo_exporter_append_{}.json", std::process::id()));
+        assert!(export(ExportFormat::Json, &ExportContext::new("owner", "first", now), &first, &[], &options, &json).is_err());
+        std::fs::remove_file(&json).ok();
+    }
+}
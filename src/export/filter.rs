@@ -0,0 +1,127 @@
+// ============= src/export/filter.rs =============
+use super::language::{language_for_path, line_comment_prefixes};
+use anyhow::{anyhow, Result};
+use std::fmt;
+use std::str::FromStr;
+
+/// An opt-in pass that makes rendered content denser, at the cost of fidelity
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ContentFilter {
+    /// Drop every blank (or whitespace-only) line
+    StripBlankLines,
+    /// Collapse runs of blank lines into a single blank line
+    CollapseBlankLines,
+    /// Drop lines that consist only of a line comment in the file's language.
+    /// Trailing comments after code are kept, since telling them apart from
+    /// string contents would need a real parser.
+    StripComments,
+    /// Trim trailing whitespace from every line and drop blank lines at the end of the file
+    TrimWhitespace,
+}
+
+impl fmt::Display for ContentFilter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ContentFilter::StripBlankLines => write!(f, "blank lines stripped"),
+            ContentFilter::CollapseBlankLines => write!(f, "blank line runs collapsed"),
+            ContentFilter::StripComments => write!(f, "comment lines stripped"),
+            ContentFilter::TrimWhitespace => write!(f, "trailing whitespace trimmed"),
+        }
+    }
+}
+
+impl FromStr for ContentFilter {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim().to_lowercase().as_str() {
+            "strip-blank-lines" => Ok(ContentFilter::StripBlankLines),
+            "collapse-blank-lines" => Ok(ContentFilter::CollapseBlankLines),
+            "strip-comments" => Ok(ContentFilter::StripComments),
+            "trim-whitespace" => Ok(ContentFilter::TrimWhitespace),
+            other => Err(anyhow!(
+                "Unknown filter '{}'. Expected 'strip-blank-lines', 'collapse-blank-lines', 'strip-comments' or 'trim-whitespace'",
+                other
+            )),
+        }
+    }
+}
+
+/// Runs `filters` over a file's content, in order
+pub fn apply_filters(path: &str, content: &str, filters: &[ContentFilter]) -> String {
+    let mut lines: Vec<&str> = content.lines().collect();
+
+    for filter in filters {
+        lines = match filter {
+            ContentFilter::StripBlankLines => lines.into_iter().filter(|line| !line.trim().is_empty()).collect(),
+            ContentFilter::CollapseBlankLines => {
+                let mut collapsed: Vec<&str> = Vec::with_capacity(lines.len());
+                for line in lines {
+                    let blank = line.trim().is_empty();
+                    if !(blank && collapsed.last().is_some_and(|last| last.trim().is_empty())) {
+                        collapsed.push(line);
+                    }
+                }
+                collapsed
+            }
+            ContentFilter::StripComments => {
+                let prefixes = line_comment_prefixes(language_for_path(path));
+                lines.into_iter().filter(|line| !is_comment_line(line, prefixes)).collect()
+            }
+            ContentFilter::TrimWhitespace => {
+                let mut trimmed: Vec<&str> = lines.into_iter().map(str::trim_end).collect();
+                while trimmed.last().is_some_and(|line| line.is_empty()) {
+                    trimmed.pop();
+                }
+                trimmed
+            }
+        };
+    }
+
+    lines.join("\n")
+}
+
+fn is_comment_line(line: &str, prefixes: &[&str]) -> bool {
+    let trimmed = line.trim_start();
+    // Keep shebangs: they change how the file runs
+    !trimmed.starts_with("#!") && prefixes.iter().any(|prefix| trimmed.starts_with(prefix))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_strip_comments_by_language() {
+        let rust = "//! Crate docs\nfn main() {\n    // say hi\n    println!(\"// not a comment\");\n}";
+        assert_eq!(
+            apply_filters("src/main.rs", rust, &[ContentFilter::StripComments]),
+            "fn main() {\n    println!(\"// not a comment\");\n}"
+        );
+
+        let python = "#!/usr/bin/env python\n# comment\nprint('hi')  # trailing";
+        assert_eq!(
+            apply_filters("run.py", python, &[ContentFilter::StripComments]),
+            "#!/usr/bin/env python\nprint('hi')  # trailing"
+        );
+
+        // No known comment syntax: left alone
+        assert_eq!(apply_filters("notes.txt", "# heading", &[ContentFilter::StripComments]), "# heading");
+    }
+
+    #[test]
+    fn test_blank_line_filters() {
+        let content = "a\n\n\n  \nb\n\nc";
+        assert_eq!(apply_filters("f.txt", content, &[ContentFilter::StripBlankLines]), "a\nb\nc");
+        assert_eq!(apply_filters("f.txt", content, &[ContentFilter::CollapseBlankLines]), "a\n\nb\n\nc");
+    }
+
+    #[test]
+    fn test_trim_whitespace() {
+        let content = "fn main() {  \n\tlet x = 1;\t\n}\n\n  \n";
+        assert_eq!(
+            apply_filters("src/main.rs", content, &[ContentFilter::TrimWhitespace]),
+            "fn main() {\n\tlet x = 1;\n}"
+        );
+    }
+}
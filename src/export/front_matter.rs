@@ -0,0 +1,56 @@
+// ============= src/export/front_matter.rs =============
+use super::language::language_for_path;
+use crate::github::ExportedFile;
+
+/// Renders a YAML front matter block describing a file, for static site generators
+pub fn front_matter(file: &ExportedFile) -> String {
+    let mut block = String::from("---\n");
+    block.push_str(&format!("path: {}\n", yaml_quote(&file.path)));
+    block.push_str(&format!("language: {}\n", yaml_quote(language_for_path(&file.path))));
+    block.push_str(&format!("bytes: {}\n", file.content.len()));
+    if let Some(target) = &file.symlink_target {
+        block.push_str(&format!("symlink_target: {}\n", yaml_quote(target)));
+    }
+    block.push_str("---\n");
+    block
+}
+
+/// Quotes a value as a YAML double-quoted scalar, escaping anything YAML treats specially
+fn yaml_quote(value: &str) -> String {
+    let mut quoted = String::with_capacity(value.len() + 2);
+    quoted.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            '\t' => quoted.push_str("\\t"),
+            '\r' => quoted.push_str("\\r"),
+            c if c.is_control() => quoted.push_str(&format!("\\u{:04x}", c as u32)),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_front_matter() {
+        let file = ExportedFile::new("src/main.rs", "fn main() {}");
+        assert_eq!(
+            front_matter(&file),
+            "---\npath: \"src/main.rs\"\nlanguage: \"rust\"\nbytes: 12\n---\n"
+        );
+    }
+
+    #[test]
+    fn test_yaml_quote_escapes_special_characters() {
+        assert_eq!(yaml_quote(r#"docs/"quoted": #1.md"#), r#""docs/\"quoted\": #1.md""#);
+        assert_eq!(yaml_quote("a\\b\tc"), r#""a\\b\tc""#);
+        assert_eq!(yaml_quote("bell\u{7}"), r#""bell\u0007""#);
+    }
+}
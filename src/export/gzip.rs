@@ -0,0 +1,91 @@
+// ============= src/export/gzip.rs =============
+use anyhow::{anyhow, Result};
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+
+/// `path` with `.gz` added, e.g. `snapshot.md.gz`
+pub fn gzip_path(path: &Path) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(".gz");
+    PathBuf::from(name)
+}
+
+/// Where an export to `path` ends up: `path` itself, or `gzip_path(path)` when compressing
+pub(super) fn output_path(path: &Path, gzip: bool) -> PathBuf {
+    if gzip {
+        gzip_path(path)
+    } else {
+        path.to_path_buf()
+    }
+}
+
+/// Runs `write` against `out`, through a gzip encoder when `gzip` is set
+pub(super) fn with_compression<W: Write>(mut out: W, gzip: bool, write: impl FnOnce(&mut dyn Write) -> Result<()>) -> Result<()> {
+    if !gzip {
+        return write(&mut out);
+    }
+    let mut encoder = GzEncoder::new(out, Compression::default());
+    write(&mut encoder)?;
+    encoder.finish()?.flush()?;
+    Ok(())
+}
+
+/// Compresses the file at `path` into `gzip_path(path)` and removes the original, for
+/// formats like zip that have to be written to a seekable file first
+pub(super) fn gzip_file(path: &Path) -> Result<PathBuf> {
+    let gzipped = gzip_path(path);
+    with_compression(BufWriter::new(File::create(&gzipped)?), true, |out| {
+        io::copy(&mut BufReader::new(File::open(path)?), out)?;
+        Ok(())
+    })?;
+    std::fs::remove_file(path)?;
+    Ok(gzipped)
+}
+
+/// A gzip file's size on disk and the size of what it holds
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GzipSizes {
+    pub compressed: u64,
+    pub uncompressed: u64,
+}
+
+/// Reads the sizes of a single-member gzip file. As with `gzip -l`, the uncompressed
+/// size comes from the trailer, which only holds it modulo 4 GiB.
+pub fn gzip_sizes(path: &Path) -> Result<GzipSizes> {
+    let mut file = File::open(path)?;
+    let compressed = file.metadata()?.len();
+    if compressed < 18 {
+        return Err(anyhow!("{} is too short to be a gzip file", path.display()));
+    }
+    let mut trailer = [0; 4];
+    file.seek(SeekFrom::End(-4))?;
+    file.read_exact(&mut trailer)?;
+    Ok(GzipSizes { compressed, uncompressed: u64::from(u32::from_le_bytes(trailer)) })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flate2::read::GzDecoder;
+
+    #[test]
+    fn test_with_compression_round_trips_and_reports_sizes() {
+        let path = std::env::temp_dir().join(format!("repo_exporter_gzip_{}.md", std::process::id()));
+        let content = "## src/main.rs\n\nfn main() {}\n".repeat(100);
+        let gzipped = output_path(&path, true);
+        with_compression(File::create(&gzipped).unwrap(), true, |out| Ok(out.write_all(content.as_bytes())?)).unwrap();
+
+        let sizes = gzip_sizes(&gzipped).unwrap();
+        let mut decoded = String::new();
+        GzDecoder::new(File::open(&gzipped).unwrap()).read_to_string(&mut decoded).unwrap();
+        std::fs::remove_file(&gzipped).ok();
+
+        assert_eq!(gzipped.file_name().unwrap(), format!("repo_exporter_gzip_{}.md.gz", std::process::id()).as_str());
+        assert_eq!(decoded, content);
+        assert_eq!(sizes.uncompressed, content.len() as u64);
+        assert!(sizes.compressed < sizes.uncompressed);
+    }
+}
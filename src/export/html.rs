@@ -0,0 +1,158 @@
+// ============= src/export/html.rs =============
+use super::context::ExportContext;
+use super::language::fence_language;
+use super::options::ExportOptions;
+use crate::github::ExportedFile;
+use anyhow::Result;
+use std::collections::HashSet;
+use std::io::Write;
+
+/// Writes repository files as a standalone HTML page, one `<pre>` block per file.
+///
+/// File contents are written as fetched; the Markdown rendering `options` don't apply.
+pub(super) fn write_html_to<W: Write>(
+    mut out: W,
+    context: &ExportContext,
+    files: &[ExportedFile],
+    options: &ExportOptions,
+) -> Result<()> {
+    let title = escape_html(&format!("Repository Export: {}", context.name()));
+    writeln!(out, "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n<title>{}</title>\n</head>\n<body>", title)?;
+    writeln!(out, "<h1>{}</h1>", title)?;
+
+    if context.archived {
+        writeln!(out, "<p><strong>This repository is archived.</strong> It is read-only and may no longer be maintained.</p>")?;
+    }
+    if let Some(description) = &context.description {
+        writeln!(out, "<p>{}</p>", escape_html(description))?;
+    }
+    if let (Some(default_branch), Some(commit_sha)) = (&context.default_branch, &context.commit_sha) {
+        let label = match &context.git_ref {
+            Some(git_ref) => format!("Ref: <code>{}</code> · Commit", escape_html(git_ref)),
+            None => format!("Branch: <code>{}</code> · Commit", escape_html(default_branch)),
+        };
+        writeln!(out, "<p>{}: <code>{}</code></p>", label, escape_html(commit_sha))?;
+        if let Some(subject) = &context.commit_subject {
+            writeln!(out, "<p>Commit message: {}</p>", escape_html(subject))?;
+        }
+    }
+    if let Some(banner) = context.license_banner() {
+        writeln!(out, "<p><strong>{}</strong></p>", escape_html(&banner))?;
+    }
+    if let Some(changes) = &context.changes {
+        writeln!(
+            out,
+            "<p>Only the files changed between <code>{}</code> and <code>{}</code>, as of <code>{}</code>.</p>",
+            escape_html(&changes.base),
+            escape_html(&changes.head),
+            escape_html(&changes.head)
+        )?;
+        if !changes.renamed.is_empty() || !changes.deleted.is_empty() {
+            writeln!(out, "<ul>")?;
+            for file in &changes.renamed {
+                writeln!(out, "<li>Renamed: <code>{}</code> → <code>{}</code></li>", escape_html(&file.from), escape_html(&file.to))?;
+            }
+            for path in &changes.deleted {
+                writeln!(out, "<li>Deleted: <code>{}</code></li>", escape_html(path))?;
+            }
+            writeln!(out, "</ul>")?;
+        }
+    }
+    if options.interrupted {
+        writeln!(out, "<p>This export was interrupted; only the files fetched before then are included.</p>")?;
+    }
+    // Last in the header, as in the other formats
+    if let Some(license) = &context.license_file {
+        writeln!(
+            out,
+            "<details>\n<summary>{}</summary>\n<pre>{}</pre>\n</details>",
+            escape_html(&license.path),
+            escape_html(license.content.trim_end())
+        )?;
+    }
+
+    let mut ids = HashSet::new();
+    for file in files {
+        let path = escape_html(&file.path);
+        match &file.symlink_target {
+            Some(target) => writeln!(out, "<h2>{} -&gt; {} (symlink)</h2>", path, escape_html(target))?,
+            None => writeln!(
+                out,
+                "<h2 id=\"{}\">{}</h2>\n<pre><code class=\"language-{}\">{}</code></pre>",
+                anchor_id(&file.path, &mut ids),
+                path,
+                fence_language(&file.path, &context.languages),
+                escape_html(&file.content)
+            )?,
+        }
+    }
+
+    writeln!(out, "</body>\n</html>")?;
+    out.flush()?;
+    Ok(())
+}
+
+/// A fragment-safe `id` for a file's heading: `file-` and the path with everything but
+/// ASCII letters, digits, `-`, `_` and `.` turned into `-`. Paths that come out the same
+/// get `-2`, `-3` and so on, tracked in `used`.
+fn anchor_id(path: &str, used: &mut HashSet<String>) -> String {
+    let slug: String = path
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.') { c } else { '-' })
+        .collect();
+    let base = format!("file-{}", slug);
+    let mut id = base.clone();
+    let mut suffix = 2;
+    while !used.insert(id.clone()) {
+        id = format!("{}-{}", base, suffix);
+        suffix += 1;
+    }
+    id
+}
+
+fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_write_html_escapes_content() {
+        let files = vec![
+            ExportedFile::new("src/main.rs", "fn main() { if a < b && c > d {} }"),
+            ExportedFile::symlink("docs/README.md", "../README.md"),
+        ];
+        let context = ExportContext::new("owner", "repo", ExportOptions::default().export_time());
+        let mut out = Vec::new();
+        write_html_to(&mut out, &context, &files, &ExportOptions::default()).unwrap();
+
+        let output = String::from_utf8(out).unwrap();
+        assert!(output.starts_with("<!DOCTYPE html>\n"));
+        assert!(output.contains("<h1>Repository Export: owner/repo</h1>"));
+        assert!(output.contains(
+            "<h2 id=\"file-src-main.rs\">src/main.rs</h2>\n<pre><code class=\"language-rust\">fn main() { if a &lt; b &amp;&amp; c &gt; d {} }</code></pre>"
+        ));
+        assert!(output.contains("<h2>docs/README.md -&gt; ../README.md (symlink)</h2>"));
+        assert!(output.ends_with("</body>\n</html>\n"));
+    }
+
+    #[test]
+    fn test_anchor_ids_are_slugs_and_unique() {
+        let mut used = HashSet::new();
+        assert_eq!(anchor_id("docs/my file \"1\".md", &mut used), "file-docs-my-file--1-.md");
+        assert_eq!(anchor_id("docs/my-file--1-.md", &mut used), "file-docs-my-file--1-.md-2");
+        assert_eq!(anchor_id("docs/café.md", &mut used), "file-docs-caf-.md");
+    }
+}
@@ -0,0 +1,188 @@
+// ============= src/export/index.rs =============
+use super::context::ExportContext;
+use super::gzip::{output_path, with_compression};
+use super::json::{write_document, JsonContext};
+use super::language::language_for_path;
+use super::options::{ExportFormat, ExportOptions};
+use crate::github::ExportedFile;
+use anyhow::{anyhow, Result};
+use serde::Serialize;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+/// One row of a file index: a file's path, language and size, without its content
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct IndexEntry {
+    pub path: String,
+    pub language: &'static str,
+    /// `None` when the tree didn't give the file's size
+    pub bytes: Option<usize>,
+    /// Only known when the contents were fetched to count them
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub lines: Option<usize>,
+}
+
+impl IndexEntry {
+    /// An entry sized from the tree alone
+    pub fn new(path: impl Into<String>, bytes: Option<usize>) -> Self {
+        let path = path.into();
+        Self { language: language_for_path(&path), path, bytes, lines: None }
+    }
+
+    /// An entry sized and line-counted from a fetched file
+    pub fn from_file(file: &ExportedFile) -> Self {
+        Self { lines: Some(file.content.lines().count()), ..Self::new(file.path.clone(), Some(file.content.len())) }
+    }
+}
+
+#[derive(Serialize)]
+struct JsonIndex<'a> {
+    #[serde(flatten)]
+    context: JsonContext<'a>,
+    exported_at: String,
+    files: &'a [IndexEntry],
+}
+
+/// Writes the index to `output` in `format` (see `write_index_to`) and returns the path
+/// written, which has `.gz` added with `options.gzip`
+pub fn write_index(
+    format: ExportFormat,
+    context: &ExportContext,
+    entries: &[IndexEntry],
+    options: &ExportOptions,
+    output: &Path,
+) -> Result<PathBuf> {
+    check_format(format)?;
+    let path = output_path(output, options.gzip);
+    write_index_to(format, BufWriter::new(File::create(&path)?), context, entries, options)?;
+    Ok(path)
+}
+
+/// Writes a table of `entries` (path, language, bytes and, when counted, lines) headed
+/// by the repository's name and ref. Markdown writes a table, text aligned columns and
+/// JSON a `files` array; there's no HTML or zip index.
+pub fn write_index_to<W: Write>(
+    format: ExportFormat,
+    out: W,
+    context: &ExportContext,
+    entries: &[IndexEntry],
+    options: &ExportOptions,
+) -> Result<()> {
+    check_format(format)?;
+    with_compression(out, options.gzip, |out| write_table(format, out, context, entries, options))
+}
+
+fn write_table(
+    format: ExportFormat,
+    out: &mut dyn Write,
+    context: &ExportContext,
+    entries: &[IndexEntry],
+    options: &ExportOptions,
+) -> Result<()> {
+    let counts_lines = entries.iter().any(|entry| entry.lines.is_some());
+    let total_bytes: usize = entries.iter().filter_map(|entry| entry.bytes).sum();
+    let total_lines: usize = entries.iter().filter_map(|entry| entry.lines).sum();
+
+    match format {
+        ExportFormat::Markdown => {
+            writeln!(out, "# File Index: {}\n", context.name())?;
+            if let Some(git_ref) = &context.git_ref {
+                writeln!(out, "> Ref: `{}`\n", git_ref)?;
+            }
+            if counts_lines {
+                writeln!(out, "| Path | Language | Bytes | Lines |")?;
+                writeln!(out, "|------|----------|------:|------:|")?;
+            } else {
+                writeln!(out, "| Path | Language | Bytes |")?;
+                writeln!(out, "|------|----------|------:|")?;
+            }
+            for entry in entries {
+                let bytes = entry.bytes.map(|bytes| bytes.to_string()).unwrap_or_else(|| "?".to_string());
+                write!(out, "| `{}` | {} | {} |", entry.path.replace('|', "\\|"), entry.language, bytes)?;
+                match entry.lines {
+                    Some(lines) => writeln!(out, " {} |", lines)?,
+                    None if counts_lines => writeln!(out, " |")?,
+                    None => writeln!(out)?,
+                }
+            }
+            write!(out, "\n{} files, {} bytes", entries.len(), total_bytes)?;
+            if counts_lines {
+                write!(out, ", {} lines", total_lines)?;
+            }
+            writeln!(out)?;
+        }
+        ExportFormat::Text => {
+            writeln!(out, "File Index: {}", context.name())?;
+            if let Some(git_ref) = &context.git_ref {
+                writeln!(out, "Ref: {}", git_ref)?;
+            }
+            let width = entries.iter().map(|entry| entry.path.chars().count()).max().unwrap_or(0);
+            writeln!(out)?;
+            for entry in entries {
+                let bytes = entry.bytes.map(|bytes| bytes.to_string()).unwrap_or_else(|| "?".to_string());
+                write!(out, "{:<width$}  {:<12} {:>10}", entry.path, entry.language, bytes, width = width)?;
+                match entry.lines {
+                    Some(lines) => writeln!(out, " {:>8}", lines)?,
+                    None => writeln!(out)?,
+                }
+            }
+            write!(out, "\n{} files, {} bytes", entries.len(), total_bytes)?;
+            if counts_lines {
+                write!(out, ", {} lines", total_lines)?;
+            }
+            writeln!(out)?;
+        }
+        ExportFormat::Json => {
+            let index = JsonIndex {
+                context: context.into(),
+                exported_at: context.timestamp.to_rfc3339(),
+                files: entries,
+            };
+            return write_document(out, &index, options.json_style);
+        }
+        ExportFormat::Html | ExportFormat::Zip => unreachable!("rejected by check_format"),
+    }
+
+    out.flush()?;
+    Ok(())
+}
+
+fn check_format(format: ExportFormat) -> Result<()> {
+    match format {
+        ExportFormat::Markdown | ExportFormat::Text | ExportFormat::Json => Ok(()),
+        _ => Err(anyhow!("A file index can be written as markdown, text or json, not {}", format.extension())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_write_index_markdown_and_json() {
+        let context = ExportContext::new("owner", "repo", ExportOptions::default().export_time());
+        let entries = vec![
+            IndexEntry::from_file(&ExportedFile::new("README.md", "# Repo\n\nHello\n")),
+            IndexEntry::from_file(&ExportedFile::new("src/main.rs", "fn main() {}\n")),
+        ];
+
+        let mut markdown = Vec::new();
+        write_index_to(ExportFormat::Markdown, &mut markdown, &context, &entries, &ExportOptions::default()).unwrap();
+        let markdown = String::from_utf8(markdown).unwrap();
+        assert!(markdown.starts_with("# File Index: owner/repo\n"));
+        assert!(markdown.contains("| `README.md` | markdown | 14 | 3 |\n"));
+        assert!(markdown.contains("| `src/main.rs` | rust | 13 | 1 |\n"));
+        assert!(markdown.ends_with("2 files, 27 bytes, 4 lines\n"));
+
+        let mut json = Vec::new();
+        write_index_to(ExportFormat::Json, &mut json, &context, &[IndexEntry::new("src/main.rs", Some(13)), IndexEntry::new("src/lib.rs", None)], &ExportOptions::default()).unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&json).unwrap();
+        assert_eq!(json["files"], serde_json::json!([
+            { "path": "src/main.rs", "language": "rust", "bytes": 13 },
+            { "path": "src/lib.rs", "language": "rust", "bytes": null },
+        ]));
+
+        assert!(write_index_to(ExportFormat::Html, Vec::new(), &context, &entries, &ExportOptions::default()).is_err());
+    }
+}
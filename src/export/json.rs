@@ -0,0 +1,248 @@
+// ============= src/export/json.rs =============
+use super::context::{ExportContext, RefChanges};
+use super::options::{ExportOptions, JsonStyle};
+use super::output::default_output_path;
+use super::stats::{export_stats, largest_files, ExportStats};
+use crate::github::ExportedFile;
+use anyhow::Result;
+use serde::Serialize;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+#[derive(Serialize)]
+struct JsonExport<'a> {
+    #[serde(flatten)]
+    context: JsonContext<'a>,
+    exported_at: String,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    interrupted: bool,
+    stats: ExportStats,
+    files: Vec<JsonFile<'a>>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    largest_files: Vec<JsonFileSize<'a>>,
+}
+
+/// The repository fields of an `ExportContext`, as serialized in JSON exports
+#[derive(Serialize)]
+pub(super) struct JsonContext<'a> {
+    owner: &'a str,
+    repo: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    git_ref: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    default_branch: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    commit_sha: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    commit_subject: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    description: Option<&'a str>,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    archived: bool,
+    /// Left out when the repository's metadata wasn't fetched; `null` when it has no license
+    #[serde(skip_serializing_if = "Option::is_none")]
+    license: Option<Option<JsonLicense<'a>>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    changes: Option<&'a RefChanges>,
+}
+
+#[derive(Serialize)]
+struct JsonLicense<'a> {
+    spdx_id: Option<&'a str>,
+    name: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    path: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    text: Option<&'a str>,
+}
+
+impl<'a> From<&'a ExportContext> for JsonContext<'a> {
+    fn from(context: &'a ExportContext) -> Self {
+        Self {
+            owner: &context.owner,
+            repo: &context.repo,
+            git_ref: context.git_ref.as_deref(),
+            default_branch: context.default_branch.as_deref(),
+            commit_sha: context.commit_sha.as_deref(),
+            commit_subject: context.commit_subject.as_deref(),
+            description: context.description.as_deref(),
+            archived: context.archived,
+            license: context.default_branch.as_ref().map(|_| {
+                context.license.as_ref().map(|license| JsonLicense {
+                    spdx_id: license.spdx_id.as_deref(),
+                    name: &license.name,
+                    path: context.license_file.as_ref().map(|file| file.path.as_str()),
+                    text: context.license_file.as_ref().map(|file| file.content.as_str()),
+                })
+            }),
+            changes: context.changes.as_ref(),
+        }
+    }
+}
+
+#[derive(Serialize)]
+pub(super) struct JsonFile<'a> {
+    path: &'a str,
+    bytes: usize,
+    content: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    symlink_target: Option<&'a str>,
+}
+
+#[derive(Serialize)]
+pub(super) struct JsonFileSize<'a> {
+    path: &'a str,
+    bytes: usize,
+}
+
+/// Exports repository files to a timestamped JSON file, indented if `pretty` and on a
+/// single line otherwise
+#[deprecated(note = "use `export::export` with `ExportFormat::Json`")]
+pub fn export_to_json(owner: &str, repo: &str, files: &[ExportedFile], pretty: bool) -> Result<String> {
+    let options = ExportOptions {
+        json_style: if pretty { JsonStyle::Pretty } else { JsonStyle::Compact },
+        ..ExportOptions::default()
+    };
+    let context = ExportContext::new(owner, repo, options.export_time());
+    let output_file = default_output_path(repo, "json", context.timestamp);
+    write_json_to(BufWriter::new(File::create(&output_file)?), &context, files, &options)?;
+    Ok(output_file.display().to_string())
+}
+
+/// Writes repository files to `path` as a JSON document
+#[deprecated(note = "use `export::export` with `ExportFormat::Json`")]
+pub fn write_json(
+    path: &Path,
+    context: &ExportContext,
+    files: &[ExportedFile],
+    options: &ExportOptions,
+) -> Result<()> {
+    write_json_to(BufWriter::new(File::create(path)?), context, files, options)
+}
+
+/// Writes repository files as a JSON document to any writer. `export::export_to` does
+/// the same for any format.
+///
+/// File contents are always written as fetched; the Markdown rendering `options` don't
+/// apply.
+pub fn write_json_to<W: Write>(
+    out: W,
+    context: &ExportContext,
+    files: &[ExportedFile],
+    options: &ExportOptions,
+) -> Result<()> {
+    let export = JsonExport {
+        context: context.into(),
+        exported_at: context.timestamp.to_rfc3339(),
+        interrupted: options.interrupted,
+        stats: export_stats(files),
+        files: json_files(files),
+        largest_files: json_largest_files(files, options),
+    };
+
+    write_document(out, &export, options.json_style)
+}
+
+/// Serializes `value` in `style`, ending with a newline either way
+pub(super) fn write_document<W: Write, T: Serialize>(mut out: W, value: &T, style: JsonStyle) -> Result<()> {
+    match style {
+        JsonStyle::Pretty => serde_json::to_writer_pretty(&mut out, value)?,
+        JsonStyle::Compact => serde_json::to_writer(&mut out, value)?,
+    }
+    writeln!(out)?;
+    out.flush()?;
+    Ok(())
+}
+
+pub(super) fn json_files(files: &[ExportedFile]) -> Vec<JsonFile<'_>> {
+    files
+        .iter()
+        .map(|file| JsonFile {
+            path: &file.path,
+            bytes: file.content.len(),
+            content: &file.content,
+            symlink_target: file.symlink_target.as_deref(),
+        })
+        .collect()
+}
+
+/// The `options.largest_files` largest files, without their content
+pub(super) fn json_largest_files<'a>(files: &'a [ExportedFile], options: &ExportOptions) -> Vec<JsonFileSize<'a>> {
+    largest_files(files, options.largest_files)
+        .into_iter()
+        .map(|file| JsonFileSize { path: &file.path, bytes: file.content.len() })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::export::default_output_path;
+    use std::path::Path;
+
+    #[test]
+    fn test_write_json() {
+        let files = vec![
+            ExportedFile::new("src/main.rs", "fn main() {}"),
+        ];
+        let context = ExportContext::new("owner", "json_repo", ExportOptions::default().export_time());
+
+        let mut out = Vec::new();
+        write_json_to(&mut out, &context, &files, &ExportOptions::default()).unwrap();
+        let output = String::from_utf8(out).unwrap();
+
+        let value: serde_json::Value = serde_json::from_str(&output).unwrap();
+        assert_eq!(value["owner"], "owner");
+        assert_eq!(value["repo"], "json_repo");
+        assert_eq!(value["files"][0]["path"], "src/main.rs");
+        assert_eq!(value["files"][0]["bytes"], 12);
+        assert_eq!(value["files"][0]["content"], "fn main() {}");
+        assert_eq!(value["stats"]["files"], 1);
+        assert_eq!(value["stats"]["bytes"], 12);
+        assert_eq!(value["stats"]["lines"], 1);
+        assert_eq!(value["stats"]["languages"]["rs"]["files"], 1);
+    }
+
+    #[test]
+    fn test_write_json_compact() {
+        let files = vec![ExportedFile::new("src/main.rs", "fn main() {\n}\n")];
+        let context = ExportContext::new("owner", "repo", ExportOptions::default().export_time());
+        let render = |json_style| {
+            let mut out = Vec::new();
+            write_json_to(&mut out, &context, &files, &ExportOptions { json_style, ..ExportOptions::default() }).unwrap();
+            String::from_utf8(out).unwrap()
+        };
+
+        let (pretty, compact) = (render(JsonStyle::Pretty), render(JsonStyle::Compact));
+        assert_eq!(compact.lines().count(), 1);
+        assert!(compact.len() < pretty.len());
+        assert_eq!(
+            serde_json::from_str::<serde_json::Value>(&compact).unwrap(),
+            serde_json::from_str::<serde_json::Value>(&pretty).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_write_json_fixed_timestamp_is_reproducible() {
+        let files = vec![ExportedFile::new("README.md", "# Test")];
+        let options = ExportOptions {
+            timestamp: chrono::DateTime::from_timestamp(1_700_000_000, 0).map(|time| time.fixed_offset()),
+            ..ExportOptions::default()
+        };
+        let render = || {
+            let context = ExportContext::new("owner", "repo", options.export_time());
+            let mut out = Vec::new();
+            write_json_to(&mut out, &context, &files, &options).unwrap();
+            out
+        };
+
+        let first = render();
+        assert_eq!(first, render());
+        assert!(String::from_utf8(first).unwrap().contains("\"exported_at\": \"2023-11-14T22:13:20+00:00\""));
+        assert_eq!(
+            default_output_path("repo", "json", options.export_time()),
+            Path::new("repo_repo_export_20231114_221320.json")
+        );
+    }
+}
@@ -0,0 +1,209 @@
+// ============= src/export/language.rs =============
+use std::collections::HashMap;
+use std::path::Path;
+
+/// Well-known file names that don't have a telling extension
+const FILE_NAMES: [(&str, &str); 8] = [
+    ("Dockerfile", "dockerfile"),
+    ("Makefile", "makefile"),
+    ("CMakeLists.txt", "cmake"),
+    ("Cargo.lock", "toml"),
+    ("Gemfile", "ruby"),
+    ("Rakefile", "ruby"),
+    (".gitignore", "gitignore"),
+    (".env", "dotenv"),
+];
+
+const EXTENSIONS: [(&str, &str); 52] = [
+    ("rs", "rust"),
+    ("py", "python"),
+    ("js", "javascript"),
+    ("mjs", "javascript"),
+    ("cjs", "javascript"),
+    ("jsx", "jsx"),
+    ("ts", "typescript"),
+    ("tsx", "tsx"),
+    ("java", "java"),
+    ("kt", "kotlin"),
+    ("kts", "kotlin"),
+    ("scala", "scala"),
+    ("go", "go"),
+    ("c", "c"),
+    ("h", "c"),
+    ("cc", "cpp"),
+    ("cpp", "cpp"),
+    ("cxx", "cpp"),
+    ("hpp", "cpp"),
+    ("hh", "cpp"),
+    ("cs", "csharp"),
+    ("swift", "swift"),
+    ("m", "objectivec"),
+    ("rb", "ruby"),
+    ("php", "php"),
+    ("pl", "perl"),
+    ("lua", "lua"),
+    ("r", "r"),
+    ("dart", "dart"),
+    ("ex", "elixir"),
+    ("exs", "elixir"),
+    ("erl", "erlang"),
+    ("hs", "haskell"),
+    ("clj", "clojure"),
+    ("zig", "zig"),
+    ("sh", "bash"),
+    ("bash", "bash"),
+    ("zsh", "zsh"),
+    ("ps1", "powershell"),
+    ("sql", "sql"),
+    ("html", "html"),
+    ("css", "css"),
+    ("scss", "scss"),
+    ("vue", "vue"),
+    ("json", "json"),
+    ("toml", "toml"),
+    ("yaml", "yaml"),
+    ("yml", "yaml"),
+    ("xml", "xml"),
+    ("md", "markdown"),
+    ("proto", "protobuf"),
+    ("graphql", "graphql"),
+];
+
+/// Best-guess language identifier for a path, suitable for code-fence info strings.
+/// Unknown files map to `text`.
+pub fn language_for_path(path: &str) -> &'static str {
+    let path = Path::new(path);
+
+    if let Some(name) = path.file_name().and_then(|name| name.to_str()) {
+        if let Some((_, language)) = FILE_NAMES.iter().find(|(file_name, _)| *file_name == name) {
+            return language;
+        }
+    }
+
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .map(|ext| ext.to_lowercase())
+        .and_then(|ext| EXTENSIONS.iter().find(|(known, _)| *known == ext))
+        .map_or("text", |(_, language)| language)
+}
+
+/// Extensions several languages share, with the candidates in order of preference
+/// when the repository gives no hint
+const AMBIGUOUS_EXTENSIONS: [(&str, &[&str]); 2] = [
+    ("h", &["c", "cpp", "objectivec"]),
+    ("m", &["objectivec", "matlab"]),
+];
+
+/// Code-fence language for `path`. For extensions several languages share (like `.h`),
+/// whichever candidate the repository uses most wins. `repo_languages` are GitHub
+/// language names, most-used first, as returned by `GitHubClient::repo_languages`.
+pub fn fence_language(path: &str, repo_languages: &[String]) -> &'static str {
+    let extension = Path::new(path)
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .map(|ext| ext.to_lowercase());
+
+    if let Some((_, candidates)) = AMBIGUOUS_EXTENSIONS
+        .iter()
+        .find(|(ambiguous, _)| extension.as_deref() == Some(*ambiguous))
+    {
+        let preferred = repo_languages
+            .iter()
+            .map(|name| github_language_id(name))
+            .find_map(|id| candidates.iter().find(|candidate| **candidate == id));
+        return preferred.unwrap_or(&candidates[0]);
+    }
+
+    language_for_path(path)
+}
+
+/// Opening-fence info string for `path`. `overrides` maps an extension (matched
+/// case-insensitively) or an exact file name to a full info string (e.g. `rs` to
+/// `rust,ignore` for mdBook); other files get their bare `fence_language` when
+/// `tag_language` is set, and `text` otherwise.
+pub fn fence_info(
+    path: &str,
+    repo_languages: &[String],
+    overrides: &HashMap<String, String>,
+    tag_language: bool,
+) -> String {
+    let path_ref = Path::new(path);
+    let by_name = path_ref
+        .file_name()
+        .and_then(|name| name.to_str())
+        .and_then(|name| overrides.get(name));
+    let by_extension = || {
+        path_ref
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .and_then(|ext| overrides.iter().find(|(key, _)| key.eq_ignore_ascii_case(ext)))
+            .map(|(_, info)| info)
+    };
+
+    match by_name.or_else(by_extension) {
+        Some(info) => info.clone(),
+        None if tag_language => fence_language(path, repo_languages).to_string(),
+        None => "text".to_string(),
+    }
+}
+
+/// Maps a GitHub (linguist) language name to the identifiers used here
+fn github_language_id(name: &str) -> String {
+    match name {
+        "C++" => "cpp".to_string(),
+        "C#" => "csharp".to_string(),
+        "Objective-C" => "objectivec".to_string(),
+        "Shell" => "bash".to_string(),
+        other => other.to_lowercase(),
+    }
+}
+
+/// Prefixes that start a line comment in `language` (as returned by `language_for_path`);
+/// empty when the language has none or isn't known
+pub fn line_comment_prefixes(language: &str) -> &'static [&'static str] {
+    match language {
+        "rust" | "javascript" | "jsx" | "typescript" | "tsx" | "java" | "kotlin" | "scala" | "go" | "c"
+        | "cpp" | "csharp" | "swift" | "objectivec" | "dart" | "zig" | "protobuf" => &["//"],
+        "php" => &["//", "#"],
+        "python" | "ruby" | "perl" | "r" | "elixir" | "bash" | "zsh" | "powershell" | "yaml" | "toml"
+        | "dockerfile" | "makefile" | "cmake" | "gitignore" | "dotenv" | "graphql" => &["#"],
+        "sql" | "lua" | "haskell" => &["--"],
+        "erlang" => &["%"],
+        "clojure" => &[";"],
+        _ => &[],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_language_for_path() {
+        assert_eq!(language_for_path("src/main.rs"), "rust");
+        assert_eq!(language_for_path("web/App.TSX"), "tsx");
+        assert_eq!(language_for_path("docker/Dockerfile"), "dockerfile");
+        assert_eq!(language_for_path("LICENSE"), "text");
+        assert_eq!(language_for_path("notes.unknown"), "text");
+    }
+
+    #[test]
+    fn test_fence_language_biased_by_repo_languages() {
+        let cpp_repo = vec!["Python".to_string(), "C++".to_string(), "C".to_string()];
+        assert_eq!(fence_language("include/widget.h", &cpp_repo), "cpp");
+        assert_eq!(fence_language("include/widget.h", &[]), "c");
+        assert_eq!(fence_language("src/main.rs", &cpp_repo), "rust");
+    }
+
+    #[test]
+    fn test_fence_info_overrides() {
+        let overrides = HashMap::from([
+            ("rs".to_string(), "rust,ignore".to_string()),
+            ("Makefile".to_string(), "make".to_string()),
+        ]);
+        assert_eq!(fence_info("src/LIB.RS", &[], &overrides, false), "rust,ignore");
+        assert_eq!(fence_info("build/Makefile", &[], &overrides, false), "make");
+        assert_eq!(fence_info("main.py", &[], &overrides, true), "python");
+        assert_eq!(fence_info("main.py", &[], &overrides, false), "text");
+    }
+}
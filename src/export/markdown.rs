@@ -1,46 +1,541 @@
 // ============= src/export/markdown.rs =============
+use super::context::ExportContext;
+use super::dedupe::find_duplicates;
+use super::filter::apply_filters;
+use super::front_matter::front_matter;
+use super::language::{fence_info, fence_language};
+use super::options::{CodeBlockStyle, ExportOptions};
+use super::output::default_output_path;
+use super::render::{fill_template, flat_names, indent_block, soft_wrap, with_line_numbers};
+use super::stats::largest_files;
+use crate::github::ExportedFile;
+use crate::normalize::to_nfc;
 use anyhow::Result;
-use chrono::Local;
+use std::borrow::Cow;
 use std::fs::File;
-use std::io::Write;
+use std::io::{BufWriter, Write};
+use std::path::Path;
 
-/// Exports repository files to a Markdown file
+/// Exports repository files to a timestamped Markdown file
+#[deprecated(note = "use `export::export` with `ExportFormat::Markdown`")]
 pub fn export_to_markdown(
     owner: &str,
     repo: &str,
-    files: Vec<(String, String)>
+    files: &[ExportedFile],
+    options: &ExportOptions,
 ) -> Result<String> {
-    let timestamp = Local::now().format("%Y%m%d_%H%M%S").to_string();
-    let output_file = format!("{}_repo_export_{}.md", repo, timestamp);
-    let mut file = File::create(&output_file)?;
+    let context = ExportContext::new(owner, repo, options.export_time());
+    let output_file = default_output_path(repo, "md", context.timestamp);
+    let file = BufWriter::new(File::create(&output_file)?);
+    write_markdown_to(file, &context, files, options)?;
+    Ok(output_file.display().to_string())
+}
+
+/// Writes repository files to `path` as Markdown, headed by what `context` knows about
+/// the repository
+#[deprecated(note = "use `export::export` with `ExportFormat::Markdown`")]
+pub fn write_markdown(
+    path: &Path,
+    context: &ExportContext,
+    files: &[ExportedFile],
+    options: &ExportOptions,
+) -> Result<()> {
+    let file = BufWriter::new(File::create(path)?);
+    write_markdown_to(file, context, files, options)
+}
+
+/// Writes repository files as Markdown to any writer, headed by what `context` knows
+/// about the repository. `export::export_to` does the same for any format.
+pub fn write_markdown_to<W: Write>(
+    mut out: W,
+    context: &ExportContext,
+    files: &[ExportedFile],
+    options: &ExportOptions,
+) -> Result<()> {
+    writeln!(out, "{} Repository Export: {}\n", heading(1, options), context.name())?;
+    write_context_header(&mut out, context)?;
+    write_header_notes(&mut out, options)?;
+    write_license_text(&mut out, context, options)?;
+    write_file_sections(&mut out, context, files, options, &heading(2, options))?;
+    write_largest_files(&mut out, files, options, &heading(2, options))?;
+
+    out.flush()?;
+    Ok(())
+}
+
+/// Adds the files to an existing export as one repository's section, laid out as
+/// `--combined` lays out each repository. `started` says whether `out` already holds an
+/// export; if not, the top-level title is written first.
+pub(super) fn append_markdown_to<W: Write>(
+    mut out: W,
+    context: &ExportContext,
+    files: &[ExportedFile],
+    options: &ExportOptions,
+    started: bool,
+) -> Result<()> {
+    if started {
+        writeln!(out)?;
+    } else {
+        writeln!(out, "{} Repository Export\n", heading(1, options))?;
+    }
+    writeln!(out, "{} {}\n", heading(2, options), context.name())?;
+    write_context_header(&mut out, context)?;
+    write_header_notes(&mut out, options)?;
+    write_license_text(&mut out, context, options)?;
+    write_file_sections(&mut out, context, files, options, &heading(3, options))?;
+    write_largest_files(&mut out, files, options, &heading(3, options))?;
+
+    out.flush()?;
+    Ok(())
+}
+
+/// The Markdown heading marker for `level` (1 for `#`), shifted by
+/// `options.heading_offset` and capped at 6
+pub(super) fn heading(level: usize, options: &ExportOptions) -> String {
+    "#".repeat((level + options.heading_offset).min(6))
+}
+
+/// The repository description, then the branch, commit and commit message when they're
+/// known, then the license, then what a diff export compares. The license text follows
+/// the header notes (see `write_license_text`).
+pub(super) fn write_context_header<W: Write>(out: &mut W, context: &ExportContext) -> Result<()> {
+    if context.archived {
+        writeln!(out, "> 📦 **This repository is archived.** It is read-only and may no longer be maintained.\n")?;
+    }
+    if let Some(description) = &context.description {
+        writeln!(out, "{}\n", description)?;
+    }
+
+    if let (Some(default_branch), Some(commit_sha)) = (&context.default_branch, &context.commit_sha) {
+        match &context.git_ref {
+            Some(git_ref) => writeln!(
+                out,
+                "> Ref: `{}` · Commit: `{}` · Default branch: `{}`\n",
+                git_ref, commit_sha, default_branch
+            )?,
+            None => writeln!(out, "> Branch: `{}` · Commit: `{}`\n", default_branch, commit_sha)?,
+        }
+        if let Some(subject) = &context.commit_subject {
+            writeln!(out, "> Commit message: {}\n", subject)?;
+        }
+    }
+    if let Some(banner) = context.license_banner() {
+        writeln!(out, "> ⚖️ {}\n", banner)?;
+    }
+    if let Some(changes) = &context.changes {
+        writeln!(out, "> Only the files changed between `{}` and `{}`, as of `{}`.\n", changes.base, changes.head, changes.head)?;
+        if !changes.renamed.is_empty() {
+            let renamed: Vec<_> = changes.renamed.iter().map(|file| format!("`{}` → `{}`", file.from, file.to)).collect();
+            writeln!(out, "> Renamed: {}\n", renamed.join(", "))?;
+        }
+        if !changes.deleted.is_empty() {
+            let deleted: Vec<_> = changes.deleted.iter().map(|path| format!("`{}`", path)).collect();
+            writeln!(out, "> Deleted: {}\n", deleted.join(", "))?;
+        }
+    }
+    Ok(())
+}
+
+/// The repository's license text, if it was fetched, as the last part of the header like
+/// in the other formats. Set as a code block in `options.code_block_style`.
+pub(super) fn write_license_text<W: Write>(out: &mut W, context: &ExportContext, options: &ExportOptions) -> Result<()> {
+    if let Some(license) = &context.license_file {
+        let block = code_block(license.content.trim_end(), "text", options.code_block_style);
+        writeln!(out, "**{}**\n\n{}\n", license.path, block.trim_end())?;
+    }
+    Ok(())
+}
+
+/// `content` as a Markdown code block: fenced and tagged with `info`, or indented
+fn code_block(content: &str, info: &str, style: CodeBlockStyle) -> String {
+    match style {
+        CodeBlockStyle::Fenced => format!("```{}\n{}\n```", info, content),
+        CodeBlockStyle::Indented => format!("{}\n", indent_block(content)),
+    }
+}
+
+/// Notes under the title for any option that makes the rendered content differ from the files
+pub(super) fn write_header_notes<W: Write>(out: &mut W, options: &ExportOptions) -> Result<()> {
+    if options.interrupted {
+        writeln!(out, "> ⚠️ This export was interrupted; only the files fetched before then are included.\n")?;
+    }
+    if !options.filters.is_empty() {
+        let applied: Vec<_> = options.filters.iter().map(|filter| filter.to_string()).collect();
+        writeln!(out, "> Content has been altered for density: {}.\n", applied.join(", "))?;
+    }
+    if options.normalize_unicode {
+        writeln!(out, "> Text has been normalized to Unicode NFC.\n")?;
+    }
+    if options.line_numbers {
+        writeln!(out, "> Line numbers in code blocks are added for reference and are not part of the files.\n")?;
+    }
+    Ok(())
+}
+
+/// Writes one section per file, headed at the given Markdown `heading` level (e.g. `##`)
+pub(super) fn write_file_sections<W: Write>(
+    out: &mut W,
+    context: &ExportContext,
+    files: &[ExportedFile],
+    options: &ExportOptions,
+    heading: &str,
+) -> Result<()> {
+    let duplicates = if options.dedupe {
+        find_duplicates(files)
+    } else {
+        vec![None; files.len()]
+    };
+    // Headings only; front matter keeps the full path
+    let names: Vec<String> = if options.flat_headings {
+        flat_names(&files.iter().map(|file| file.path.as_str()).collect::<Vec<_>>())
+    } else {
+        files.iter().map(|file| file.path.clone()).collect()
+    };
+
+    for (index, ((exported, duplicate_of), name)) in files.iter().zip(duplicates).zip(&names).enumerate() {
+        if let Some(separator) = options.file_separator.as_ref().filter(|_| index > 0) {
+            // The blank line first keeps a `---` from turning the line above into a heading
+            writeln!(out, "\n{}\n", fill_template(separator, &[("path", &exported.path)]))?;
+        }
+        if options.front_matter {
+            writeln!(out, "{}", front_matter(exported))?;
+        }
+
+        if let Some(target) = &exported.symlink_target {
+            writeln!(out, "{} {} -> {} (symlink)\n", heading, name, target)?;
+            continue;
+        }
+
+        if let Some(original) = duplicate_of {
+            writeln!(out, "{} {} (identical to {})\n", heading, name, names[original])?;
+            continue;
+        }
+
+        let normalized = if options.normalize_unicode {
+            to_nfc(&exported.content)
+        } else {
+            Cow::Borrowed(exported.content.as_str())
+        };
+        let filtered = if options.filters.is_empty() {
+            normalized.into_owned()
+        } else {
+            apply_filters(&exported.path, &normalized, &options.filters)
+        };
+        let mut content = if options.line_numbers {
+            with_line_numbers(&filtered)
+        } else {
+            filtered
+        };
+        // Wrapped after numbering so continuation lines don't get numbers of their own
+        if let Some(width) = options.wrap_width {
+            content = soft_wrap(&content, width);
+        }
+
+        let info = fence_info(&exported.path, &context.languages, &options.fence_info, options.fence_languages);
+        let block = code_block(&content, &info, options.code_block_style);
+
+        match &options.section_template {
+            Some(template) => {
+                let bytes = exported.content.len().to_string();
+                let values = [
+                    ("path", name.as_str()),
+                    ("language", fence_language(&exported.path, &context.languages)),
+                    ("bytes", bytes.as_str()),
+                    ("content", block.as_str()),
+                ];
+                writeln!(out, "{}", fill_template(template, &values))?
+            }
+            None => writeln!(out, "{} {}\n\n{}", heading, name, block)?,
+        }
+    }
 
-    writeln!(file, "# Repository Export: {}/{}\n", owner, repo)?;
+    Ok(())
+}
 
-    for (path, content) in files {
-        writeln!(file, "## {}\n", path)?;
-        writeln!(file, "```text\n{}\n```", content)?;
+/// A table of the `options.largest_files` largest files and their sizes, if any
+pub(super) fn write_largest_files<W: Write>(
+    out: &mut W,
+    files: &[ExportedFile],
+    options: &ExportOptions,
+    heading: &str,
+) -> Result<()> {
+    let largest = largest_files(files, options.largest_files);
+    if largest.is_empty() {
+        return Ok(());
     }
 
-    Ok(output_file)
+    writeln!(out, "\n{} Largest Files\n", heading)?;
+    writeln!(out, "| File | Bytes |\n|------|------:|")?;
+    for file in largest {
+        writeln!(out, "| `{}` | {} |", file.path.replace('|', "\\|"), file.content.len())?;
+    }
+    Ok(())
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::export::ContentFilter;
+    use crate::github::{License, Provenance};
+
+    fn context() -> ExportContext {
+        ExportContext::new("owner", "repo", chrono::Local::now().fixed_offset())
+    }
+
+    fn render(files: &[ExportedFile], options: &ExportOptions) -> String {
+        let mut out = Vec::new();
+        write_markdown_to(&mut out, &context(), files, options).unwrap();
+        String::from_utf8(out).unwrap()
+    }
 
     #[test]
-    fn test_export_to_markdown() {
+    fn test_write_markdown_dedupe() {
         let files = vec![
-            ("README.md".to_string(), "# Test".to_string()),
-            ("src/main.rs".to_string(), "fn main() {}".to_string()),
+            ExportedFile::new("LICENSE", "MIT"),
+            ExportedFile::new("crates/a/LICENSE", "MIT"),
         ];
+        let options = ExportOptions {
+            dedupe: true,
+            ..ExportOptions::default()
+        };
 
-        let result = export_to_markdown("owner", "repo", files);
-        assert!(result.is_ok());
+        let output = render(&files, &options);
 
-        // Clean up test file
-        if let Ok(filename) = result {
-            std::fs::remove_file(filename).ok();
-        }
+        assert!(output.contains("## crates/a/LICENSE (identical to LICENSE)"));
+        assert_eq!(output.matches("MIT").count(), 1);
+    }
+
+    #[test]
+    fn test_write_markdown_provenance() {
+        let files = vec![ExportedFile::new("README.md", "# Test")];
+        let context = context().with_provenance(Provenance {
+            git_ref: None,
+            default_branch: "main".to_string(),
+            commit_sha: "0123abcd".to_string(),
+            commit_subject: Some("Fix the *parser*".to_string()),
+            description: Some("A test repository".to_string()),
+            archived: true,
+            license: None,
+            moved_to: None,
+        });
+
+        let mut out = Vec::new();
+        write_markdown_to(&mut out, &context, &files, &ExportOptions::default()).unwrap();
+        let output = String::from_utf8(out).unwrap();
+
+        assert!(output.starts_with(
+            "# Repository Export: owner/repo\n\n> 📦 **This repository is archived.** It is read-only and may no longer be maintained.\n\n\
+             A test repository\n\n> Branch: `main` · Commit: `0123abcd`\n\n> Commit message: Fix the *parser*\n"
+        ));
+    }
+
+    #[test]
+    fn test_write_markdown_license() {
+        let provenance = Provenance {
+            git_ref: None,
+            default_branch: "main".to_string(),
+            commit_sha: "0123abcd".to_string(),
+            commit_subject: None,
+            description: None,
+            archived: false,
+            license: Some(License { spdx_id: Some("MIT".to_string()), name: "MIT License".to_string() }),
+            moved_to: None,
+        };
+        let mut licensed = context().with_provenance(provenance.clone());
+        licensed.license_file = Some(ExportedFile::new("LICENSE", "MIT License\n\nCopyright (c) 2025\n"));
+        let mut out = Vec::new();
+        write_markdown_to(&mut out, &licensed, &[], &ExportOptions::default()).unwrap();
+        let output = String::from_utf8(out).unwrap();
+        assert!(output.contains("> ⚖️ License: MIT (MIT License)\n\n**LICENSE**\n\n```text\nMIT License\n\nCopyright (c) 2025\n```\n"));
+
+        // The license text follows the header notes and honors the code block style
+        let options = ExportOptions { interrupted: true, code_block_style: CodeBlockStyle::Indented, ..ExportOptions::default() };
+        let mut out = Vec::new();
+        write_markdown_to(&mut out, &licensed, &[], &options).unwrap();
+        let output = String::from_utf8(out).unwrap();
+        assert!(output.contains("interrupted; only the files fetched before then are included.\n\n**LICENSE**\n\n    MIT License\n    \n    Copyright (c) 2025\n\n"));
+
+        let unlicensed = context().with_provenance(Provenance { license: None, ..provenance });
+        let mut out = Vec::new();
+        write_markdown_to(&mut out, &unlicensed, &[], &ExportOptions::default()).unwrap();
+        assert!(String::from_utf8(out).unwrap().contains("> ⚖️ No license detected;"));
+
+        // Nothing is claimed when the repository's metadata is unknown
+        let mut out = Vec::new();
+        write_markdown_to(&mut out, &context(), &[], &ExportOptions::default()).unwrap();
+        assert!(!String::from_utf8(out).unwrap().contains("icense"));
+    }
+
+    #[test]
+    fn test_write_markdown_to_writer() {
+        let files = vec![ExportedFile::new("README.md", "# Test")];
+        let mut out = Vec::new();
+        write_markdown_to(&mut out, &context(), &files, &ExportOptions::default()).unwrap();
+
+        let output = String::from_utf8(out).unwrap();
+        assert!(output.starts_with("# Repository Export: owner/repo\n\n## README.md\n"));
+    }
+
+    #[test]
+    fn test_write_markdown_filters_are_noted() {
+        let files = vec![ExportedFile::new("src/main.rs", "// comment\nfn main() {}\n")];
+        let options = ExportOptions {
+            filters: vec![ContentFilter::StripComments],
+            ..ExportOptions::default()
+        };
+
+        let output = render(&files, &options);
+
+        assert!(output.contains("> Content has been altered for density: comment lines stripped."));
+        assert!(output.contains("```text\nfn main() {}\n```"));
+
+        let tagged = render(&files, &ExportOptions { fence_languages: true, ..options });
+        assert!(tagged.contains("```rust\nfn main() {}\n```"));
+    }
+
+    #[test]
+    fn test_write_markdown_normalize_unicode() {
+        let files = vec![ExportedFile::new("notes.txt", "cafe\u{301}")];
+        let options = ExportOptions {
+            normalize_unicode: true,
+            ..ExportOptions::default()
+        };
+
+        let output = render(&files, &options);
+
+        assert!(output.contains("> Text has been normalized to Unicode NFC."));
+        assert!(output.contains("caf\u{e9}") && !output.contains('\u{301}'));
+    }
+
+    #[test]
+    fn test_write_markdown_front_matter() {
+        let files = vec![ExportedFile::new("src/main.rs", "fn main() {}")];
+        let options = ExportOptions {
+            front_matter: true,
+            ..ExportOptions::default()
+        };
+
+        let output = render(&files, &options);
+
+        assert!(output.contains("---\npath: \"src/main.rs\"\nlanguage: \"rust\"\nbytes: 12\n---\n\n## src/main.rs"));
+    }
+
+    #[test]
+    fn test_write_markdown_largest_files() {
+        let files = vec![
+            ExportedFile::new("src/lib.rs", "pub fn lib() {}"),
+            ExportedFile::new("src/main.rs", "fn main() {}"),
+            ExportedFile::new("README.md", "# Readme"),
+        ];
+        let options = ExportOptions {
+            largest_files: 2,
+            ..ExportOptions::default()
+        };
+        let mut out = Vec::new();
+        write_markdown_to(&mut out, &context(), &files, &options).unwrap();
+
+        let output = String::from_utf8(out).unwrap();
+        assert!(output.ends_with("## Largest Files\n\n| File | Bytes |\n|------|------:|\n| `src/lib.rs` | 15 |\n| `src/main.rs` | 12 |\n"));
+    }
+
+    #[test]
+    fn test_write_markdown_heading_offset() {
+        let files = vec![ExportedFile::new("src/main.rs", "fn main() {}")];
+        let options = ExportOptions {
+            heading_offset: 2,
+            largest_files: 1,
+            ..ExportOptions::default()
+        };
+        let mut out = Vec::new();
+        write_markdown_to(&mut out, &context(), &files, &options).unwrap();
+
+        let output = String::from_utf8(out).unwrap();
+        assert!(output.starts_with("### Repository Export: owner/repo\n\n#### src/main.rs\n"));
+        assert!(output.contains("\n#### Largest Files\n"));
+
+        let options = ExportOptions { heading_offset: 5, ..ExportOptions::default() };
+        assert_eq!(heading(1, &options), "######");
+        assert_eq!(heading(3, &options), "######");
+    }
+
+    #[test]
+    fn test_write_markdown_file_separator() {
+        let files = vec![ExportedFile::new("README.md", "# Test"), ExportedFile::new("src/main.rs", "fn main() {}")];
+        let options = ExportOptions {
+            file_separator: Some("---".to_string()),
+            ..ExportOptions::default()
+        };
+        let mut out = Vec::new();
+        write_markdown_to(&mut out, &context(), &files, &options).unwrap();
+
+        let output = String::from_utf8(out).unwrap();
+        assert!(output.contains("```\n\n---\n\n## src/main.rs\n"));
+        assert_eq!(output.matches("---").count(), 1);
+    }
+
+    #[test]
+    fn test_write_markdown_flat_headings() {
+        let files = vec![
+            ExportedFile::new("src/a/mod.rs", "mod a;"),
+            ExportedFile::new("src/b/mod.rs", "mod b;"),
+            ExportedFile::new("src/main.rs", "fn main() {}"),
+        ];
+        let options = ExportOptions {
+            flat_headings: true,
+            ..ExportOptions::default()
+        };
+        let mut out = Vec::new();
+        write_markdown_to(&mut out, &context(), &files, &options).unwrap();
+
+        let output = String::from_utf8(out).unwrap();
+        assert!(output.contains("## mod.rs (a)\n"));
+        assert!(output.contains("## mod.rs (b)\n"));
+        assert!(output.contains("## main.rs\n"));
+        assert!(!output.contains("## src/"));
+    }
+
+    #[test]
+    fn test_write_markdown_section_template() {
+        let files = vec![
+            ExportedFile::new("src/main.rs", "fn main() {}"),
+            ExportedFile::new("src/lib.rs", "pub fn lib() {}"),
+        ];
+        let options = ExportOptions {
+            section_template: Some("### File: {path} ({language}, {bytes} bytes)\n\n{content}\n\n---\n".to_string()),
+            ..ExportOptions::default()
+        };
+        let mut out = Vec::new();
+        write_markdown_to(&mut out, &context(), &files, &options).unwrap();
+
+        let output = String::from_utf8(out).unwrap();
+        assert!(output.contains("### File: src/main.rs (rust, 12 bytes)\n\n```text\nfn main() {}\n```\n\n---\n"));
+        assert!(output.contains("### File: src/lib.rs (rust, 15 bytes)\n"));
+        assert!(!output.contains("## src/"));
+    }
+
+    #[test]
+    fn test_write_markdown_symlink() {
+        let files = vec![ExportedFile::symlink("docs/README.md", "../README.md")];
+
+        let output = render(&files, &ExportOptions::default());
+
+        assert!(output.contains("## docs/README.md -> ../README.md (symlink)\n"));
+        assert!(!output.contains("```"));
+    }
+
+    #[test]
+    fn test_write_markdown_indented() {
+        let files = vec![
+            ExportedFile::new("README.md", "```sh\nls\n```"),
+        ];
+        let options = ExportOptions {
+            code_block_style: CodeBlockStyle::Indented,
+            ..ExportOptions::default()
+        };
+
+        let output = render(&files, &options);
+
+        assert!(output.contains("## README.md\n\n    ```sh\n    ls\n    ```\n"));
+        assert!(!output.contains("```text"));
     }
 }
\ No newline at end of file
@@ -1,4 +1,35 @@
 // ============= src/export/mod.rs =============
+pub mod archive;
+pub mod combined;
+pub mod context;
+pub mod dedupe;
+pub mod dispatch;
+pub mod filter;
+pub mod front_matter;
+pub mod gzip;
+pub mod html;
+pub mod index;
+pub mod json;
+pub mod language;
 pub mod markdown;
+pub mod options;
+pub mod output;
+pub mod render;
+pub mod stats;
+pub mod text;
 
-pub use markdown::export_to_markdown;
\ No newline at end of file
+#[allow(deprecated)]
+pub use archive::write_zip;
+pub use combined::{write_combined, write_combined_to, RepoSection};
+pub use context::{ExportContext, RefChanges, RenamedFile};
+pub use dispatch::{export, export_all, export_to};
+pub use filter::ContentFilter;
+pub use gzip::{gzip_path, gzip_sizes, GzipSizes};
+pub use index::{write_index, write_index_to, IndexEntry};
+#[allow(deprecated)]
+pub use json::{export_to_json, write_json, write_json_to};
+#[allow(deprecated)]
+pub use markdown::{export_to_markdown, write_markdown, write_markdown_to};
+pub use options::{CodeBlockStyle, ExportFormat, ExportOptions, JsonStyle};
+pub use output::default_output_path;
+pub use stats::{export_stats, largest_files, ExportStats, FileCounts, DEFAULT_LARGEST_FILES};
@@ -0,0 +1,196 @@
+// ============= src/export/options.rs =============
+use super::filter::ContentFilter;
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, FixedOffset, Local};
+use std::collections::HashMap;
+use std::str::FromStr;
+
+/// Output file format
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ExportFormat {
+    #[default]
+    Markdown,
+    Json,
+    /// A standalone page with each file in a `<pre>` block, for reading in a browser
+    Html,
+    /// Plain text with a `==> path <==` line before each file, for tools that don't
+    /// understand Markdown
+    Text,
+    /// The files themselves, laid out as in the repository
+    Zip,
+}
+
+impl ExportFormat {
+    pub fn extension(self) -> &'static str {
+        match self {
+            ExportFormat::Markdown => "md",
+            ExportFormat::Json => "json",
+            ExportFormat::Html => "html",
+            ExportFormat::Text => "txt",
+            ExportFormat::Zip => "zip",
+        }
+    }
+
+    /// The format a file extension (without the dot, any case) stands for, e.g. `json`
+    /// or `htm`
+    pub fn from_extension(extension: &str) -> Option<Self> {
+        match extension.to_lowercase().as_str() {
+            "md" | "markdown" => Some(ExportFormat::Markdown),
+            "json" => Some(ExportFormat::Json),
+            "html" | "htm" => Some(ExportFormat::Html),
+            "txt" | "text" => Some(ExportFormat::Text),
+            "zip" => Some(ExportFormat::Zip),
+            _ => None,
+        }
+    }
+}
+
+impl FromStr for ExportFormat {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim().to_lowercase().as_str() {
+            "markdown" | "md" => Ok(ExportFormat::Markdown),
+            "json" => Ok(ExportFormat::Json),
+            "html" => Ok(ExportFormat::Html),
+            "text" | "txt" => Ok(ExportFormat::Text),
+            "zip" => Ok(ExportFormat::Zip),
+            other => Err(anyhow!("Unknown export format '{}'. Expected 'markdown', 'json', 'html', 'text' or 'zip'", other)),
+        }
+    }
+}
+
+/// How file contents are wrapped in the Markdown output
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum CodeBlockStyle {
+    /// ```` ``` ```` fenced blocks
+    #[default]
+    Fenced,
+    /// Every line indented by four spaces, for processors that mangle fences.
+    /// Also sidesteps files that themselves contain backtick fences.
+    Indented,
+}
+
+impl FromStr for CodeBlockStyle {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim().to_lowercase().as_str() {
+            "fenced" => Ok(CodeBlockStyle::Fenced),
+            "indented" => Ok(CodeBlockStyle::Indented),
+            other => Err(anyhow!("Unknown code block style '{}'. Expected 'fenced' or 'indented'", other)),
+        }
+    }
+}
+
+/// How JSON exports are laid out
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum JsonStyle {
+    /// Indented, one field per line, for reading and diffing
+    #[default]
+    Pretty,
+    /// A single line with no whitespace, for machine ingestion
+    Compact,
+}
+
+impl FromStr for JsonStyle {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim().to_lowercase().as_str() {
+            "pretty" => Ok(JsonStyle::Pretty),
+            "compact" => Ok(JsonStyle::Compact),
+            other => Err(anyhow!("Unknown JSON style '{}'. Expected 'pretty' or 'compact'", other)),
+        }
+    }
+}
+
+/// Settings that control how the export writers render files
+#[derive(Debug, Clone, Default)]
+pub struct ExportOptions {
+    /// Render files whose content is byte-identical to an earlier file as a
+    /// cross-reference instead of repeating the content
+    pub dedupe: bool,
+    /// Prefix each line inside code blocks with its line number. Off by default
+    /// because the gutter gets in the way of copy-pasting code.
+    pub line_numbers: bool,
+    pub code_block_style: CodeBlockStyle,
+    pub json_style: JsonStyle,
+    /// Precede each file section with YAML front matter (path, language, bytes)
+    /// so static site generators can index the sections
+    pub front_matter: bool,
+    /// Soft-wrap rendered lines longer than this many characters. Only affects the
+    /// Markdown output; JSON exports always carry the content unchanged.
+    pub wrap_width: Option<usize>,
+    /// Density passes run over each file before rendering, noted in the header.
+    /// Markdown only; JSON exports always carry the content unchanged.
+    pub filters: Vec<ContentFilter>,
+    /// Convert file contents to Unicode NFC before rendering, noted in the header, so
+    /// visually identical text is encoded one way. Markdown only; JSON exports always
+    /// carry the content unchanged.
+    pub normalize_unicode: bool,
+    /// Head each file by its name alone, with just enough of the parent directory to
+    /// tell apart files sharing a name. Markdown only; JSON and zip keep full paths.
+    pub flat_headings: bool,
+    /// Shift every Markdown heading down this many levels, e.g. `2` turns the title into
+    /// `###` and file headings into `####`, to embed the export in a larger document.
+    /// Headings stop at level 6; section templates are left as written.
+    pub heading_offset: usize,
+    /// Tag Markdown code fences with each file's language (biased towards the
+    /// repository's own languages for extensions like `.h`) rather than `text`
+    pub fence_languages: bool,
+    /// Full info strings for opening fences, keyed by extension (without the dot,
+    /// any case) or exact file name, e.g. `rs` → `rust,ignore`. Other files get the
+    /// language with `fence_languages`, or `text`.
+    pub fence_info: HashMap<String, String>,
+    /// Layout for each file's section in Markdown output, with `{path}`, `{language}`,
+    /// `{bytes}` and `{content}` (the rendered code block) placeholders. `None` is the
+    /// built-in `## {path}` heading followed by the code block. Symlinks and deduplicated
+    /// files keep their one-line headings.
+    pub section_template: Option<String>,
+    /// Written on lines of its own between file sections in Markdown and text output,
+    /// with `{path}` replaced by the next file's path, e.g. `---` or `===== {path} =====`.
+    /// `None` (the default) writes the sections back to back.
+    pub file_separator: Option<String>,
+    /// List this many of the largest files, with their sizes, at the end of the export
+    /// to help decide what to trim. `0` (the default) lists none; the CLI lists
+    /// `DEFAULT_LARGEST_FILES`.
+    pub largest_files: usize,
+    /// Add the export to the end of the output file instead of replacing it, e.g. to
+    /// build up a multi-repository export over several runs. Markdown and text only.
+    pub append: bool,
+    /// Compress the export with gzip. Files are written to `<output>.gz` (zip archives
+    /// are compressed once written); writers such as stdout get the gzip stream.
+    /// Can't be combined with `append`.
+    pub gzip: bool,
+    /// Time recorded in default output file names and JSON `exported_at`. `None` means
+    /// now; fix it (e.g. from `SOURCE_DATE_EPOCH`) for byte-reproducible exports.
+    pub timestamp: Option<DateTime<FixedOffset>>,
+    /// The export was stopped before every file was fetched; noted in the output.
+    /// Set by `Exporter`.
+    pub interrupted: bool,
+}
+
+impl ExportOptions {
+    /// `timestamp`, or the local time now
+    pub fn export_time(&self) -> DateTime<FixedOffset> {
+        self.timestamp.unwrap_or_else(|| Local::now().fixed_offset())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_from_extension() {
+        assert_eq!(ExportFormat::from_extension("json"), Some(ExportFormat::Json));
+        assert_eq!(ExportFormat::from_extension("HTM"), Some(ExportFormat::Html));
+        assert_eq!(ExportFormat::from_extension("md"), Some(ExportFormat::Markdown));
+        assert_eq!(ExportFormat::from_extension("pdf"), None);
+
+        for format in [ExportFormat::Markdown, ExportFormat::Json, ExportFormat::Html, ExportFormat::Text, ExportFormat::Zip] {
+            assert_eq!(ExportFormat::from_extension(format.extension()), Some(format));
+        }
+    }
+}
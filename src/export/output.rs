@@ -0,0 +1,9 @@
+// ============= src/export/output.rs =============
+use chrono::{DateTime, FixedOffset};
+use std::path::PathBuf;
+
+/// Default output file name: `{repo}_repo_export_{timestamp}.{extension}` in the working directory
+pub fn default_output_path(repo: &str, extension: &str, timestamp: DateTime<FixedOffset>) -> PathBuf {
+    let timestamp = timestamp.format("%Y%m%d_%H%M%S").to_string();
+    PathBuf::from(format!("{}_repo_export_{}.{}", repo, timestamp, extension))
+}
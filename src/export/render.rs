@@ -0,0 +1,169 @@
+// ============= src/export/render.rs =============
+/// Prefixes each line with its 1-based line number, right-aligned to the width of
+/// the largest number (e.g. `  9| ...` / ` 10| ...` for a file with 10+ lines)
+pub fn with_line_numbers(content: &str) -> String {
+    let line_count = content.lines().count();
+    let width = line_count.to_string().len();
+
+    content
+        .lines()
+        .enumerate()
+        .map(|(i, line)| format!("{:>width$}| {}", i + 1, line, width = width))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Starts each continuation line produced by `soft_wrap`, so wraps aren't mistaken for source
+pub const WRAP_MARKER: &str = "↪ ";
+
+/// Breaks lines longer than `width` characters, starting each continuation with `WRAP_MARKER`.
+/// The marker isn't counted towards `width`.
+pub fn soft_wrap(content: &str, width: usize) -> String {
+    let width = width.max(1);
+    let mut wrapped = Vec::new();
+
+    for line in content.lines() {
+        let chars: Vec<char> = line.chars().collect();
+        if chars.len() <= width {
+            wrapped.push(line.to_string());
+            continue;
+        }
+
+        for (i, chunk) in chars.chunks(width).enumerate() {
+            let chunk: String = chunk.iter().collect();
+            if i == 0 {
+                wrapped.push(chunk);
+            } else {
+                wrapped.push(format!("{}{}", WRAP_MARKER, chunk));
+            }
+        }
+    }
+
+    wrapped.join("\n")
+}
+
+/// Short display names for `paths`: the file name alone, or when several files share
+/// a name, the name followed by just enough of the parent directory to tell them apart
+/// (`mod.rs (a)`, `mod.rs (b)`). A root-level file keeps its bare name.
+pub fn flat_names(paths: &[&str]) -> Vec<String> {
+    let split = |path: &str| -> (String, Vec<String>) {
+        let mut parts: Vec<String> = path.split('/').map(String::from).collect();
+        let name = parts.pop().unwrap_or_default();
+        (name, parts)
+    };
+    let entries: Vec<_> = paths.iter().map(|path| split(path)).collect();
+
+    entries
+        .iter()
+        .map(|(name, parents)| {
+            let others: Vec<_> = entries
+                .iter()
+                .filter(|(other_name, other_parents)| other_name == name && other_parents != parents)
+                .collect();
+            if others.is_empty() {
+                return name.clone();
+            }
+
+            let suffix = |parents: &[String], depth: usize| parents[parents.len().saturating_sub(depth)..].join("/");
+            let depth = (1..=parents.len())
+                .find(|&depth| others.iter().all(|(_, other)| suffix(other, depth) != suffix(parents, depth)))
+                .unwrap_or(parents.len());
+
+            if depth == 0 {
+                name.clone()
+            } else {
+                format!("{} ({})", name, suffix(parents, depth))
+            }
+        })
+        .collect()
+}
+
+/// Indents every line by four spaces, producing a Markdown indented code block
+pub fn indent_block(content: &str) -> String {
+    content
+        .lines()
+        .map(|line| format!("    {}", line))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Replaces each `{name}` in `template` with its value from `values` in a single pass,
+/// so placeholders appearing inside the values are left alone. Unknown names are kept
+/// as written.
+pub fn fill_template(template: &str, values: &[(&str, &str)]) -> String {
+    let mut filled = String::with_capacity(template.len());
+    let mut rest = template;
+
+    while let Some(start) = rest.find('{') {
+        filled.push_str(&rest[..start]);
+        let after = &rest[start + 1..];
+        let value = after
+            .find('}')
+            .and_then(|end| values.iter().find(|(name, _)| *name == &after[..end]).map(|(_, value)| (end, value)));
+        match value {
+            Some((end, value)) => {
+                filled.push_str(value);
+                rest = &after[end + 1..];
+            }
+            None => {
+                filled.push('{');
+                rest = after;
+            }
+        }
+    }
+
+    filled.push_str(rest);
+    filled
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_with_line_numbers_pads_to_widest_number() {
+        let content = (1..=10).map(|i| format!("line {}", i)).collect::<Vec<_>>().join("\n");
+        let numbered = with_line_numbers(&content);
+        let lines: Vec<_> = numbered.lines().collect();
+
+        assert_eq!(lines[0], " 1| line 1");
+        assert_eq!(lines[9], "10| line 10");
+    }
+
+    #[test]
+    fn test_flat_names_disambiguates_collisions() {
+        let names = flat_names(&["README.md", "docs/README.md", "src/a/mod.rs", "lib/a/mod.rs", "src/b/mod.rs", "main.rs"]);
+        assert_eq!(names, vec![
+            "README.md",
+            "README.md (docs)",
+            "mod.rs (src/a)",
+            "mod.rs (lib/a)",
+            "mod.rs (b)",
+            "main.rs",
+        ]);
+    }
+
+    #[test]
+    fn test_fill_template() {
+        let values = [("path", "src/{bytes}.rs"), ("bytes", "12")];
+        assert_eq!(fill_template("### File: {path} ({bytes} bytes)", &values), "### File: src/{bytes}.rs (12 bytes)");
+        assert_eq!(fill_template("{unknown} {path", &values), "{unknown} {path");
+    }
+
+    #[test]
+    fn test_indent_block() {
+        assert_eq!(indent_block("fn main() {\n}\n"), "    fn main() {\n    }");
+    }
+
+    #[test]
+    fn test_soft_wrap() {
+        assert_eq!(soft_wrap("short\nabcdefgh", 3), "sho\n↪ rt\nabc\n↪ def\n↪ gh");
+        assert_eq!(soft_wrap("fits\n", 4), "fits");
+        assert_eq!(soft_wrap("ééé", 2), "éé\n↪ é");
+    }
+
+    #[test]
+    fn test_with_line_numbers_single_line() {
+        assert_eq!(with_line_numbers("fn main() {}"), "1| fn main() {}");
+    }
+}
@@ -0,0 +1,94 @@
+// ============= src/export/stats.rs =============
+use crate::github::ExportedFile;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::path::Path;
+
+/// How many of the largest files the CLI lists at the end of an export
+pub const DEFAULT_LARGEST_FILES: usize = 10;
+
+/// The `n` largest files by content size, largest first. Ties are broken by path so the
+/// list is stable; symlinks, which carry no content, are left out.
+pub fn largest_files(files: &[ExportedFile], n: usize) -> Vec<&ExportedFile> {
+    let mut largest: Vec<_> = files.iter().filter(|file| file.symlink_target.is_none()).collect();
+    largest.sort_by(|a, b| b.content.len().cmp(&a.content.len()).then_with(|| a.path.cmp(&b.path)));
+    largest.truncate(n);
+    largest
+}
+
+/// How many files, bytes and lines
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct FileCounts {
+    pub files: usize,
+    pub bytes: usize,
+    pub lines: usize,
+}
+
+/// Totals over an export's files, and the same per extension. Symlinks carry no content
+/// and aren't counted.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct ExportStats {
+    #[serde(flatten)]
+    pub total: FileCounts,
+    /// Keyed by lowercase extension without the dot; files without one are under `""`
+    pub languages: BTreeMap<String, FileCounts>,
+}
+
+impl FileCounts {
+    fn add(&mut self, file: &ExportedFile) {
+        self.files += 1;
+        self.bytes += file.content.len();
+        self.lines += file.content.lines().count();
+    }
+}
+
+/// Counts `files` in total and by extension
+pub fn export_stats(files: &[ExportedFile]) -> ExportStats {
+    let mut stats = ExportStats::default();
+    for file in files.iter().filter(|file| file.symlink_target.is_none()) {
+        let extension = Path::new(&file.path)
+            .extension()
+            .map(|extension| extension.to_string_lossy().to_lowercase())
+            .unwrap_or_default();
+        stats.total.add(file);
+        stats.languages.entry(extension).or_default().add(file);
+    }
+    stats
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_largest_files() {
+        let files = vec![
+            ExportedFile::new("b.rs", "12345"),
+            ExportedFile::new("a.rs", "12345"),
+            ExportedFile::new("big.rs", "1234567890"),
+            ExportedFile::new("small.rs", "1"),
+            ExportedFile::symlink("link", "big.rs"),
+        ];
+
+        let largest: Vec<_> = largest_files(&files, 3).iter().map(|file| file.path.as_str()).collect();
+        assert_eq!(largest, vec!["big.rs", "a.rs", "b.rs"]);
+        assert_eq!(largest_files(&files, 10).len(), 4);
+        assert!(largest_files(&files, 0).is_empty());
+    }
+
+    #[test]
+    fn test_export_stats() {
+        let files = vec![
+            ExportedFile::new("src/main.rs", "fn main() {\n}\n"),
+            ExportedFile::new("src/Lib.RS", "pub fn lib() {}"),
+            ExportedFile::new("Makefile", "all:\n\tcargo build\n"),
+            ExportedFile::symlink("link.rs", "src/main.rs"),
+        ];
+
+        let stats = export_stats(&files);
+        assert_eq!(stats.total, FileCounts { files: 3, bytes: 47, lines: 5 });
+        assert_eq!(stats.languages["rs"], FileCounts { files: 2, bytes: 29, lines: 3 });
+        assert_eq!(stats.languages[""], FileCounts { files: 1, bytes: 18, lines: 2 });
+        assert_eq!(stats.languages.len(), 2);
+    }
+}
@@ -0,0 +1,111 @@
+// ============= src/export/text.rs =============
+use super::context::ExportContext;
+use super::options::ExportOptions;
+use super::render::fill_template;
+use crate::github::ExportedFile;
+use anyhow::Result;
+use std::io::Write;
+
+/// Writes repository files as plain text, each preceded by a `==> path <==` line (as
+/// `head` does for several files).
+///
+/// File contents are written as fetched; the Markdown rendering `options` don't apply.
+pub(super) fn write_text_to<W: Write>(
+    mut out: W,
+    context: &ExportContext,
+    files: &[ExportedFile],
+    options: &ExportOptions,
+) -> Result<()> {
+    write_text_header(&mut out, context, options)?;
+
+    for (index, file) in files.iter().enumerate() {
+        if let Some(separator) = options.file_separator.as_ref().filter(|_| index > 0) {
+            writeln!(out, "\n{}", fill_template(separator, &[("path", &file.path)]))?;
+        }
+        match &file.symlink_target {
+            Some(target) => writeln!(out, "\n==> {} -> {} (symlink) <==", file.path, target)?,
+            None => {
+                writeln!(out, "\n==> {} <==", file.path)?;
+                write!(out, "{}", file.content)?;
+                if !file.content.is_empty() && !file.content.ends_with('\n') {
+                    writeln!(out)?;
+                }
+            }
+        }
+    }
+
+    out.flush()?;
+    Ok(())
+}
+
+/// The title line and what `context` knows about the repository, as the text export
+/// opens with; zip archives carry it in `EXPORT.txt`
+pub(super) fn write_text_header<W: Write>(out: &mut W, context: &ExportContext, options: &ExportOptions) -> Result<()> {
+    writeln!(out, "Repository Export: {}", context.name())?;
+    if context.archived {
+        writeln!(out, "This repository is archived. It is read-only and may no longer be maintained.")?;
+    }
+    if let Some(description) = &context.description {
+        writeln!(out, "{}", description)?;
+    }
+    if let (Some(default_branch), Some(commit_sha)) = (&context.default_branch, &context.commit_sha) {
+        match &context.git_ref {
+            Some(git_ref) => writeln!(out, "Ref: {} · Commit: {} · Default branch: {}", git_ref, commit_sha, default_branch)?,
+            None => writeln!(out, "Branch: {} · Commit: {}", default_branch, commit_sha)?,
+        }
+        if let Some(subject) = &context.commit_subject {
+            writeln!(out, "Commit message: {}", subject)?;
+        }
+    }
+    if let Some(banner) = context.license_banner() {
+        writeln!(out, "{}", banner)?;
+    }
+    if let Some(changes) = &context.changes {
+        writeln!(out, "Only the files changed between {} and {}, as of {}.", changes.base, changes.head, changes.head)?;
+        for file in &changes.renamed {
+            writeln!(out, "Renamed: {} -> {}", file.from, file.to)?;
+        }
+        for path in &changes.deleted {
+            writeln!(out, "Deleted: {}", path)?;
+        }
+    }
+    if options.interrupted {
+        writeln!(out, "This export was interrupted; only the files fetched before then are included.")?;
+    }
+    if let Some(license) = &context.license_file {
+        writeln!(out, "\n==> {} (license) <==\n{}", license.path, license.content.trim_end())?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_write_text() {
+        let files = vec![
+            ExportedFile::new("README.md", "# Test\n"),
+            ExportedFile::new("src/main.rs", "fn main() {}"),
+        ];
+        let context = ExportContext::new("owner", "repo", ExportOptions::default().export_time());
+        let mut out = Vec::new();
+        write_text_to(&mut out, &context, &files, &ExportOptions::default()).unwrap();
+
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "Repository Export: owner/repo\n\n==> README.md <==\n# Test\n\n==> src/main.rs <==\nfn main() {}\n"
+        );
+
+        let options = ExportOptions {
+            file_separator: Some("===== next: {path} =====".to_string()),
+            ..ExportOptions::default()
+        };
+        let mut out = Vec::new();
+        write_text_to(&mut out, &context, &files, &options).unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "Repository Export: owner/repo\n\n==> README.md <==\n# Test\n\n===== next: src/main.rs =====\n\n==> src/main.rs <==\nfn main() {}\n"
+        );
+    }
+}
@@ -0,0 +1,1547 @@
+// ============= src/exporter.rs =============
+use crate::bitbucket::BitbucketClient;
+use crate::checkpoint::Checkpoint;
+use crate::export::{
+    default_output_path, export, export_to, write_combined, write_combined_to, write_index, write_index_to, ExportContext,
+    ExportFormat, ExportOptions, IndexEntry, RefChanges, RepoSection,
+};
+use crate::github::{
+    AppCredentials, AuthScheme, EmptyRepositoryError, ExportedFile, FailedFile, FetchItem, FetchOptions, Gist, GitHubClient, GitTreeEntry, HttpSettings, RepoFiles, RepositoryDisabledError, WIKI_DIR,
+};
+use crate::github::options::{DEFAULT_REF, LATEST_RELEASE_REF};
+use crate::manifest::Manifest;
+use crate::progress::ProgressReporter;
+use crate::redact::{RedactionRule, Redactor};
+use crate::repo_config::{RepoConfig, REPO_CONFIG_FILE};
+use anyhow::{anyhow, Result};
+use futures::{pin_mut, StreamExt};
+use std::borrow::Cow;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tokio_util::sync::CancellationToken;
+
+/// `output` path that sends the export to stdout instead of a file
+pub const STDOUT_OUTPUT: &str = "-";
+
+/// Requests an export makes besides fetching files: the repository and commit for
+/// provenance and the license file. Looking for `export.toml` and fetching the
+/// language breakdown (see `Exporter::wants_languages`) are one more each.
+const METADATA_REQUESTS: usize = 3;
+
+/// Rewrites a file's content given its path and content; see `ExporterBuilder::content_transform`
+pub type ContentTransform = Box<dyn Fn(&str, &str) -> String + Send + Sync>;
+
+/// High-level entry point that fetches a repository and writes the export in one call.
+///
+/// ```no_run
+/// # async fn run() -> anyhow::Result<()> {
+/// use repo_exporter::export::ExportFormat;
+/// use repo_exporter::exporter::Exporter;
+///
+/// let exporter = Exporter::builder("ghp_...")
+///     .format(ExportFormat::Json)
+///     .output("snapshot.json")
+///     .build()?;
+/// let path = exporter.export("tidynest", "repo_exporter").await?;
+/// # Ok(())
+/// # }
+/// ```
+pub struct Exporter {
+    client: GitHubClient,
+    bitbucket: BitbucketClient,
+    /// Empty until set, so a repository's `export.toml` can pick one
+    formats: Vec<ExportFormat>,
+    output: Option<PathBuf>,
+    fetch_options: FetchOptions,
+    export_options: ExportOptions,
+    redactor: Option<Redactor>,
+    content_transform: Option<ContentTransform>,
+    checkpoint_dir: Option<PathBuf>,
+    interrupt: InterruptHandle,
+    honor_repo_config: bool,
+    include_wiki: bool,
+    manifest_output: Option<PathBuf>,
+    previous_manifest: Option<Manifest>,
+}
+
+/// Stops a running export early, e.g. from a Ctrl-C handler. Files fetched so far are
+/// still written, with a note that the export was interrupted.
+///
+/// A thin wrapper over the `CancellationToken` the exporter puts in
+/// `FetchOptions::cancel`, so interrupting and cancelling are the same thing.
+#[derive(Clone, Default)]
+pub struct InterruptHandle(CancellationToken);
+
+impl InterruptHandle {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn interrupt(&self) {
+        self.0.cancel();
+    }
+
+    pub fn is_interrupted(&self) -> bool {
+        self.0.is_cancelled()
+    }
+
+    /// The token behind this handle, cancelled by `interrupt`
+    pub fn token(&self) -> CancellationToken {
+        self.0.clone()
+    }
+}
+
+impl From<CancellationToken> for InterruptHandle {
+    fn from(token: CancellationToken) -> Self {
+        Self(token)
+    }
+}
+
+/// What a completed `Exporter::run` produced
+#[derive(Debug)]
+pub struct ExportSummary {
+    /// `None` when no files were eligible, in which case nothing is written
+    pub output: Option<PathBuf>,
+    /// The other files written when exporting to several formats; `output` is the first
+    pub extra_outputs: Vec<PathBuf>,
+    pub files_exported: usize,
+    /// Files carried over from an interrupted run's checkpoint rather than fetched again
+    pub resumed_files: usize,
+    pub redactions: usize,
+    /// Files that couldn't be fetched or decoded and are missing from the output
+    pub failed: Vec<FailedFile>,
+    /// Files left out because their content didn't match `FetchOptions::content_pattern`
+    pub unmatched_files: usize,
+    /// The export was stopped early; the checkpoint is kept so running it again resumes
+    pub interrupted: bool,
+    /// Where the manifest was written, with `ExporterBuilder::manifest`
+    pub manifest: Option<PathBuf>,
+}
+
+/// How one repository fared in `Exporter::run_combined`
+#[derive(Debug)]
+pub struct RepoOutcome {
+    pub owner: String,
+    pub repo: String,
+    pub result: Result<ExportSummary>,
+}
+
+/// A repository's files, fetched and redacted but not yet written
+struct Collected {
+    files: Vec<ExportedFile>,
+    failed: Vec<FailedFile>,
+    unmatched: usize,
+    resumed_files: usize,
+    redactions: usize,
+    /// The branch, commit and languages are left out if they couldn't be looked up;
+    /// the export goes ahead without them
+    context: ExportContext,
+    checkpoint: Option<Checkpoint>,
+    /// Fetching stopped early; `files` holds what was fetched up to then
+    interrupted: bool,
+    /// The repository's `export.toml`, or the default if it has none or it isn't honored
+    repo_config: RepoConfig,
+    /// The SHAs of the eligible files, when a manifest is written or compared against
+    manifest: Option<Manifest>,
+}
+
+impl Collected {
+    /// Discards the checkpoint now that the export has been written (or there was nothing
+    /// to write), unless it was interrupted and a later run should resume
+    fn finish(self, mut outputs: Vec<PathBuf>) -> Result<ExportSummary> {
+        if let Some(checkpoint) = self.checkpoint.filter(|_| !self.interrupted) {
+            checkpoint.remove()?;
+        }
+
+        let output = (!outputs.is_empty()).then(|| outputs.remove(0));
+        Ok(ExportSummary {
+            output,
+            extra_outputs: outputs,
+            files_exported: self.files.len(),
+            resumed_files: self.resumed_files,
+            redactions: self.redactions,
+            failed: self.failed,
+            unmatched_files: self.unmatched,
+            interrupted: self.interrupted,
+            manifest: None,
+        })
+    }
+}
+
+pub struct ExporterBuilder {
+    token: String,
+    base_url: Option<String>,
+    auth_scheme: AuthScheme,
+    app_credentials: Option<AppCredentials>,
+    user_agent: Option<String>,
+    http: Option<HttpSettings>,
+    progress: Option<Arc<dyn ProgressReporter>>,
+    bitbucket: Option<BitbucketClient>,
+    formats: Vec<ExportFormat>,
+    output: Option<PathBuf>,
+    fetch_options: FetchOptions,
+    export_options: ExportOptions,
+    redactor: Option<Redactor>,
+    content_transform: Option<ContentTransform>,
+    checkpoint_dir: Option<PathBuf>,
+    interrupt: Option<InterruptHandle>,
+    honor_repo_config: bool,
+    include_wiki: bool,
+    manifest_output: Option<PathBuf>,
+    previous_manifest: Option<Manifest>,
+}
+
+impl ExporterBuilder {
+    /// Points the client at a different API root, e.g. GitHub Enterprise
+    pub fn base_url(mut self, base_url: &str) -> Self {
+        self.base_url = Some(base_url.to_string());
+        self
+    }
+
+    pub fn auth_scheme(mut self, auth_scheme: AuthScheme) -> Self {
+        self.auth_scheme = auth_scheme;
+        self
+    }
+
+    /// Authenticates as a GitHub App installation instead of with the token
+    pub fn app_credentials(mut self, credentials: AppCredentials) -> Self {
+        self.app_credentials = Some(credentials);
+        self
+    }
+
+    pub fn user_agent(mut self, user_agent: &str) -> Self {
+        self.user_agent = Some(user_agent.to_string());
+        self
+    }
+
+    /// Timeouts and proxy for API requests (a 30 second timeout by default)
+    pub fn http_settings(mut self, settings: HttpSettings) -> Self {
+        self.http = Some(settings);
+        self
+    }
+
+    pub fn progress(mut self, progress: Arc<dyn ProgressReporter>) -> Self {
+        self.progress = Some(progress);
+        self
+    }
+
+    /// Markdown unless set here or, for exports to a default path, by the repository's
+    /// `export.toml`
+    pub fn format(mut self, format: ExportFormat) -> Self {
+        self.formats = vec![format];
+        self
+    }
+
+    /// Writes the export in each of these formats from a single fetch. With an `output`
+    /// path, each format is written next to it with its own extension.
+    pub fn formats(mut self, formats: impl IntoIterator<Item = ExportFormat>) -> Self {
+        self.formats = formats.into_iter().collect();
+        self
+    }
+
+    /// Writes to this path instead of a timestamped file in the working directory.
+    /// `-` (`STDOUT_OUTPUT`) writes to stdout, which zip exports don't support.
+    pub fn output(mut self, path: impl Into<PathBuf>) -> Self {
+        self.output = Some(path.into());
+        self
+    }
+
+    pub fn fetch_options(mut self, options: FetchOptions) -> Self {
+        self.fetch_options = options;
+        self
+    }
+
+    pub fn export_options(mut self, options: ExportOptions) -> Self {
+        self.export_options = options;
+        self
+    }
+
+    /// Scrubs secrets from file contents before they're written
+    pub fn redactor(mut self, redactor: Redactor) -> Self {
+        self.redactor = Some(redactor);
+        self
+    }
+
+    /// Fetches Bitbucket repositories with `client`, e.g. one with credentials for
+    /// private repositories. Without one, only public repositories can be exported. The
+    /// user agent and progress reporter given to this builder apply to it too.
+    pub fn bitbucket(mut self, client: BitbucketClient) -> Self {
+        self.bitbucket = Some(client);
+        self
+    }
+
+    /// Rewrites each file's content before it's written, e.g. to strip a license header.
+    /// Called with the path and decoded content of every file (symlinks excepted), after
+    /// fetching and before redaction, so secrets are still scrubbed from what it returns.
+    /// Checkpoints keep the content as fetched.
+    pub fn content_transform(mut self, transform: impl Fn(&str, &str) -> String + Send + Sync + 'static) -> Self {
+        self.content_transform = Some(Box::new(transform));
+        self
+    }
+
+    /// Records progress in a checkpoint in `dir` so an interrupted export can resume
+    pub fn checkpoint_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.checkpoint_dir = Some(dir.into());
+        self
+    }
+
+    /// Stops the export early when `handle` is interrupted. Without one, a token in
+    /// `FetchOptions::cancel` serves as the handle, and `Exporter::interrupt_handle`
+    /// returns it on the built exporter. Either way it replaces `FetchOptions::cancel`.
+    pub fn interrupt_handle(mut self, handle: InterruptHandle) -> Self {
+        self.interrupt = Some(handle);
+        self
+    }
+
+    /// Whether to apply the defaults a repository sets in its `export.toml` (on by
+    /// default). Settings given to this builder always take precedence over them.
+    pub fn repo_config(mut self, honor: bool) -> Self {
+        self.honor_repo_config = honor;
+        self
+    }
+
+    /// Also export the repository's wiki pages, under `_wiki/` (off by default). Needs
+    /// the `git` command-line tool; see `GitHubClient::fetch_wiki`.
+    pub fn wiki(mut self, include: bool) -> Self {
+        self.include_wiki = include;
+        self
+    }
+
+    /// Writes a `Manifest` of the blob SHA of every eligible file to `path` once the
+    /// export is done (not when it's interrupted). Files that failed to fetch are left
+    /// out, so an incremental export retries them. Costs the tree request again, plus one
+    /// per root ignore file.
+    pub fn manifest(mut self, path: impl Into<PathBuf>) -> Self {
+        self.manifest_output = Some(path.into());
+        self
+    }
+
+    /// Only exports the files that are new or changed since `previous` was written, as a
+    /// diff export listing the files removed since in its header. Applies to `run` and
+    /// `run_pull_request`, for the repository the manifest was written for.
+    pub fn incremental(mut self, previous: Manifest) -> Self {
+        self.previous_manifest = Some(previous);
+        self
+    }
+
+    /// Fails only if the HTTP client can't be set up with the given `http` settings
+    pub fn build(self) -> Result<Exporter> {
+        let mut client = GitHubClient::new(self.token).with_auth_scheme(self.auth_scheme);
+        if let Some(base_url) = &self.base_url {
+            client = client.with_base_url(base_url);
+        }
+        if let Some(credentials) = self.app_credentials {
+            client = client.with_app_credentials(credentials);
+        }
+        if let Some(user_agent) = &self.user_agent {
+            client = client.with_user_agent(user_agent);
+        }
+        if let Some(http) = &self.http {
+            client = client.with_http_settings(http)?;
+        }
+        let mut bitbucket = match (self.bitbucket, &self.http) {
+            (Some(bitbucket), _) => bitbucket,
+            (None, Some(http)) => BitbucketClient::new(None).with_http_settings(http)?,
+            (None, None) => BitbucketClient::new(None),
+        };
+        if let Some(user_agent) = &self.user_agent {
+            bitbucket = bitbucket.with_user_agent(user_agent);
+        }
+        if let Some(progress) = self.progress {
+            client = client.with_progress(progress.clone());
+            bitbucket = bitbucket.with_progress(progress);
+        }
+        let mut fetch_options = self.fetch_options;
+        let interrupt = match (self.interrupt, fetch_options.cancel.take()) {
+            (Some(handle), _) => handle,
+            (None, Some(token)) => token.into(),
+            (None, None) => InterruptHandle::new(),
+        };
+        fetch_options.cancel = Some(interrupt.token());
+
+        Ok(Exporter {
+            client,
+            bitbucket,
+            formats: self.formats,
+            output: self.output,
+            fetch_options,
+            export_options: self.export_options,
+            redactor: self.redactor,
+            content_transform: self.content_transform,
+            checkpoint_dir: self.checkpoint_dir,
+            interrupt,
+            honor_repo_config: self.honor_repo_config,
+            include_wiki: self.include_wiki,
+            manifest_output: self.manifest_output,
+            previous_manifest: self.previous_manifest,
+        })
+    }
+}
+
+impl Exporter {
+    pub fn builder(token: impl Into<String>) -> ExporterBuilder {
+        ExporterBuilder {
+            token: token.into(),
+            base_url: None,
+            auth_scheme: AuthScheme::default(),
+            app_credentials: None,
+            user_agent: None,
+            http: None,
+            progress: None,
+            bitbucket: None,
+            formats: Vec::new(),
+            output: None,
+            fetch_options: FetchOptions::default(),
+            export_options: ExportOptions::default(),
+            redactor: None,
+            content_transform: None,
+            checkpoint_dir: None,
+            interrupt: None,
+            honor_repo_config: true,
+            include_wiki: false,
+            manifest_output: None,
+            previous_manifest: None,
+        }
+    }
+
+    pub fn client(&self) -> &GitHubClient {
+        &self.client
+    }
+
+    /// A handle that stops this exporter's runs early when interrupted
+    pub fn interrupt_handle(&self) -> InterruptHandle {
+        self.interrupt.clone()
+    }
+
+    /// Whether the interrupt handle has stopped this exporter
+    fn stopped(&self) -> bool {
+        self.interrupt.is_interrupted()
+    }
+
+    /// Exports the repository and returns the path written
+    pub async fn export(&self, owner: &str, repo: &str) -> Result<PathBuf> {
+        self.run(owner, repo)
+            .await?
+            .output
+            .ok_or_else(|| anyhow!("No files found in the repository or all files were skipped"))
+    }
+
+    /// Exports the repository, returning details about what was written
+    #[tracing::instrument(skip(self))]
+    pub async fn run(&self, owner: &str, repo: &str) -> Result<ExportSummary> {
+        let collected = self.collect(owner, repo).await?;
+        self.write_collected(collected)
+    }
+
+    /// Exports the head commit of a pull request, as its author last pushed it. The export
+    /// is headed by `pull/<number>/head` and the commit.
+    #[tracing::instrument(skip(self))]
+    pub async fn run_pull_request(&self, owner: &str, repo: &str, number: u64) -> Result<ExportSummary> {
+        self.check_previous_manifest(owner, repo)?;
+        let head = self.client.pull_request_head(owner, repo, number).await?;
+        tracing::info!(number, head = %head.label, "exporting pull request");
+
+        let fetch_options = FetchOptions {
+            git_ref: Some(head.sha),
+            ..self.fetch_options.clone()
+        };
+        let mut collected = self.collect_at(owner, repo, fetch_options, None).await?;
+        collected.context.git_ref = Some(format!("pull/{}/head", number));
+        self.write_collected(collected)
+    }
+
+    /// Exports only the files changed between `base` and `head`, as they are at `head`,
+    /// e.g. for release notes. Renamed and deleted files are listed in the header; the
+    /// fetch filters still apply to the changed files.
+    #[tracing::instrument(skip(self))]
+    pub async fn run_compare(&self, owner: &str, repo: &str, base: &str, head: &str) -> Result<ExportSummary> {
+        let changed = self.client.compare(owner, repo, base, head).await?;
+        tracing::info!(changed = changed.len(), base, head, "compared refs");
+
+        let fetch_options = FetchOptions {
+            git_ref: Some(head.to_string()),
+            only_paths: Some(changed.iter().filter(|file| !file.is_removed()).map(|file| file.filename.clone()).collect()),
+            ..self.fetch_options.clone()
+        };
+        let changes = RefChanges::new(base, head, &changed);
+        let collected = self.collect_at(owner, repo, fetch_options, Some(changes)).await?;
+        self.write_collected(collected)
+    }
+
+    /// Writes a table of the repository's eligible files (path, language and bytes)
+    /// instead of their contents. Sizes come from the tree, so this costs a request or
+    /// two however large the repository is. With `count_lines`, every file is fetched to
+    /// count its lines too, and the content-based filters apply as in `run`.
+    ///
+    /// There's no checkpoint or `export.toml`, and nothing is redacted since no content
+    /// is written.
+    #[tracing::instrument(skip(self))]
+    pub async fn run_index(&self, owner: &str, repo: &str, count_lines: bool) -> Result<ExportSummary> {
+        let fetch_options = self.resolve_ref(owner, repo).await?;
+        let (entries, failed, unmatched) = if count_lines {
+            let fetched = self.client.fetch_repo_files(owner, repo, &fetch_options).await?;
+            (fetched.files.iter().map(IndexEntry::from_file).collect(), fetched.failed, fetched.unmatched)
+        } else {
+            let listed = self.client.list_files(owner, repo, &fetch_options).await?;
+            let entries: Vec<_> = listed.into_iter().map(|entry| IndexEntry::new(entry.path, entry.size)).collect();
+            (entries, Vec::new(), 0)
+        };
+
+        let mut context = ExportContext::new(owner, repo, self.export_options.export_time());
+        context.git_ref = fetch_options.git_ref.clone();
+        let formats = if self.formats.is_empty() { vec![ExportFormat::default()] } else { self.formats.clone() };
+        let mut outputs = Vec::new();
+        if !entries.is_empty() {
+            let output = self.output.clone().unwrap_or_else(|| {
+                default_output_path(&format!("{}_file_index", repo), formats[0].extension(), context.timestamp)
+            });
+            outputs = write_outputs(
+                &output,
+                &formats,
+                |format, out| write_index_to(format, out, &context, &entries, &self.export_options),
+                |format, path| write_index(format, &context, &entries, &self.export_options, path),
+            )?;
+            for output in &outputs {
+                tracing::info!(output = %output.display(), files = entries.len(), "file index written");
+            }
+        }
+
+        let output = (!outputs.is_empty()).then(|| outputs.remove(0));
+        Ok(ExportSummary {
+            output,
+            extra_outputs: outputs,
+            files_exported: entries.len(),
+            resumed_files: 0,
+            redactions: 0,
+            failed,
+            unmatched_files: unmatched,
+            interrupted: false,
+            manifest: None,
+        })
+    }
+
+    /// Exports a gist, headed by its owner and id like a repository. The outcome is
+    /// named the same way, or `gist/{id}` if the gist couldn't be fetched. Gists have no
+    /// checkpoint or provenance, and only the name-based fetch filters apply to them.
+    #[tracing::instrument(skip(self))]
+    pub async fn run_gist(&self, id: &str) -> RepoOutcome {
+        match self.client.fetch_gist(id, &self.fetch_options).await {
+            Ok(gist) => RepoOutcome {
+                owner: gist.owner.clone(),
+                repo: gist.id.clone(),
+                result: self.write_gist(gist),
+            },
+            Err(e) => RepoOutcome { owner: "gist".to_string(), repo: id.to_string(), result: Err(e) },
+        }
+    }
+
+    fn write_gist(&self, gist: Gist) -> Result<ExportSummary> {
+        let RepoFiles { mut files, failed, unmatched, .. } = gist.files;
+        self.transform(&mut files);
+        let redactions = self.redact(&mut files, &[]);
+
+        let collected = Collected {
+            files,
+            failed,
+            unmatched,
+            resumed_files: 0,
+            redactions,
+            context: ExportContext::new(gist.owner, gist.id, self.export_options.export_time()),
+            checkpoint: None,
+            interrupted: false,
+            repo_config: RepoConfig::default(),
+            manifest: None,
+        };
+        self.write_collected(collected)
+    }
+
+    /// Exports a Bitbucket Cloud repository at `FetchOptions::git_ref`, or its main branch.
+    /// The fetch options apply as for GitHub (see `BitbucketClient`), but there's no
+    /// provenance, checkpoint or `export.toml` as for GitHub repositories.
+    #[tracing::instrument(skip(self))]
+    pub async fn run_bitbucket(&self, workspace: &str, repo: &str) -> Result<ExportSummary> {
+        let git_ref = match &self.fetch_options.git_ref {
+            Some(git_ref) => git_ref.clone(),
+            None => self.bitbucket.main_branch(workspace, repo).await?,
+        };
+        let fetched = self.bitbucket.fetch_repo_files(workspace, repo, &git_ref, &self.fetch_options).await?;
+        let RepoFiles { mut files, failed, unmatched, cancelled } = fetched;
+        if cancelled {
+            tracing::warn!("export interrupted; writing the files fetched so far");
+        }
+        self.transform(&mut files);
+        let redactions = self.redact(&mut files, &[]);
+
+        let mut context = ExportContext::new(workspace, repo, self.export_options.export_time());
+        context.git_ref = Some(git_ref);
+        let collected = Collected {
+            files,
+            failed,
+            unmatched,
+            resumed_files: 0,
+            redactions,
+            context,
+            checkpoint: None,
+            interrupted: cancelled,
+            repo_config: RepoConfig::default(),
+            manifest: None,
+        };
+        self.write_collected(collected)
+    }
+
+    /// Writes what was collected unless it's empty, then finishes up
+    fn write_collected(&self, mut collected: Collected) -> Result<ExportSummary> {
+        // A diff export that only removes files is still worth writing, for its header
+        let has_removals = collected.context.changes.as_ref().is_some_and(|changes| !changes.deleted.is_empty());
+        let outputs = if collected.files.is_empty() && !has_removals {
+            Vec::new()
+        } else {
+            let formats = self.formats_for(&collected);
+            let output = self
+                .output
+                .clone()
+                .unwrap_or_else(|| default_output_path(&collected.context.repo, formats[0].extension(), collected.context.timestamp));
+            let outputs = self.write(&output, &collected, &formats)?;
+            for output in &outputs {
+                tracing::info!(output = %output.display(), files = collected.files.len(), "export written");
+            }
+            outputs
+        };
+
+        let manifest = match (&self.manifest_output, collected.manifest.take()) {
+            (Some(path), Some(mut manifest)) if !collected.interrupted => {
+                for failure in &collected.failed {
+                    manifest.files.remove(&failure.path);
+                }
+                manifest.save(path)?;
+                tracing::info!(manifest = %path.display(), files = manifest.files.len(), "manifest written");
+                Some(path.clone())
+            }
+            _ => None,
+        };
+        Ok(ExportSummary { manifest, ..collected.finish(outputs)? })
+    }
+
+    /// Exports several repositories into one file with a section per repository.
+    ///
+    /// A repository that fails to fetch doesn't stop the others; its error is returned
+    /// in its `RepoOutcome`. Without an explicit `output`, writes `combined_repo_export_*`.
+    #[tracing::instrument(skip_all, fields(repos = repos.len()))]
+    pub async fn run_combined(&self, repos: &[(String, String)]) -> Result<Vec<RepoOutcome>> {
+        let mut results = Vec::new();
+        for (owner, repo) in repos {
+            if self.stopped() {
+                results.push(Err(anyhow!("Export interrupted before {}/{} was fetched", owner, repo)));
+                continue;
+            }
+            results.push(self.collect(owner, repo).await);
+        }
+        let options = ExportOptions {
+            interrupted: self.stopped(),
+            ..self.export_options.clone()
+        };
+
+        let sections: Vec<_> = results
+            .iter()
+            .filter_map(|result| match result {
+                Ok(collected) if !collected.files.is_empty() => Some(RepoSection {
+                    context: &collected.context,
+                    files: &collected.files,
+                    failed: &collected.failed,
+                }),
+                _ => None,
+            })
+            .collect();
+
+        // Repositories' own format and filter preferences don't apply to a shared file
+        let formats = if self.formats.is_empty() { vec![ExportFormat::default()] } else { self.formats.clone() };
+        let mut outputs = Vec::new();
+        if !sections.is_empty() {
+            let output = self
+                .output
+                .clone()
+                .unwrap_or_else(|| default_output_path("combined", formats[0].extension(), self.export_options.export_time()));
+            outputs = write_outputs(
+                &output,
+                &formats,
+                |format, out| write_combined_to(out, format, &sections, &options),
+                |format, path| write_combined(path, format, &sections, &options),
+            )?;
+            for output in &outputs {
+                tracing::info!(output = %output.display(), repos = sections.len(), "combined export written");
+            }
+        }
+
+        repos
+            .iter()
+            .zip(results)
+            .map(|((owner, repo), result)| {
+                let result = match result {
+                    Ok(collected) => {
+                        let outputs = if collected.files.is_empty() { Vec::new() } else { outputs.clone() };
+                        collected.finish(outputs)
+                    }
+                    Err(e) => Err(e),
+                };
+                Ok(RepoOutcome {
+                    owner: owner.clone(),
+                    repo: repo.clone(),
+                    result,
+                })
+            })
+            .collect()
+    }
+
+    /// Roughly how many API requests `run` would make for the repository, without
+    /// fetching any file contents (see `GitHubClient::estimate_requests`)
+    pub async fn estimate_requests(&self, owner: &str, repo: &str) -> Result<usize> {
+        let resolves_latest = self.fetch_options.git_ref.as_deref() == Some(LATEST_RELEASE_REF);
+        let resolves_at = self.fetch_options.at.is_some();
+        let fetch_options = self.resolve_ref(owner, repo).await?;
+        let fetches = self.client.estimate_requests(owner, repo, &fetch_options).await?;
+
+        Ok(usize::from(resolves_latest)
+            + usize::from(resolves_at)
+            + METADATA_REQUESTS
+            + usize::from(self.honor_repo_config)
+            + usize::from(self.wants_languages())
+            + fetches)
+    }
+
+    /// Whether anything uses the repository's languages: tagged Markdown fences or the
+    /// HTML page's highlighting classes
+    fn wants_languages(&self) -> bool {
+        self.export_options.fence_languages || self.formats.contains(&ExportFormat::Html)
+    }
+
+    /// Fetches and redacts a repository's files, resuming from its checkpoint if there is one
+    #[tracing::instrument(skip(self))]
+    async fn collect(&self, owner: &str, repo: &str) -> Result<Collected> {
+        self.check_previous_manifest(owner, repo)?;
+        let fetch_options = self.resolve_ref(owner, repo).await?;
+        self.collect_at(owner, repo, fetch_options, None).await
+    }
+
+    /// Like `collect`, with the ref already resolved. `changes` is set for a diff export.
+    async fn collect_at(
+        &self,
+        owner: &str,
+        repo: &str,
+        mut fetch_options: FetchOptions,
+        changes: Option<RefChanges>,
+    ) -> Result<Collected> {
+        // Resolved before the tree is fetched, so it names the commit the files came from
+        // unless something is pushed in between
+        let mut context = ExportContext::new(owner, repo, self.export_options.export_time());
+        context.git_ref = fetch_options.git_ref.clone();
+        context.changes = changes;
+        match self.client.fetch_provenance(owner, repo, fetch_options.git_ref.as_deref()).await {
+            Ok(provenance) => {
+                if let Some(moved_to) = &provenance.moved_to {
+                    tracing::warn!(owner, repo, %moved_to, "repository has been renamed or transferred; exporting it under the name given");
+                }
+                context = context.with_provenance(provenance);
+            }
+            Err(e) if e.is::<RepositoryDisabledError>() => return Err(e),
+            Err(e) => tracing::warn!(error = %e, "couldn't resolve branch and commit; exporting without them"),
+        }
+        if context.archived {
+            tracing::warn!(owner, repo, "repository is archived (read-only); exporting it anyway");
+        }
+        // Name the default branch rather than relying on the `HEAD` alias, which is only
+        // the fallback when the repository metadata couldn't be fetched
+        if fetch_options.git_ref.is_none() {
+            fetch_options.git_ref = context.default_branch.clone();
+        }
+        // Shown whatever the filters leave out, so the terms travel with the code
+        if context.license.is_some() {
+            match self.client.license_file(owner, repo, fetch_options.git_ref.as_deref()).await {
+                Ok(file) => context.license_file = file,
+                Err(e) => tracing::debug!(error = %e, "couldn't fetch the license file"),
+            }
+        }
+
+        if self.wants_languages() {
+            match self.client.repo_languages(owner, repo).await {
+                Ok(languages) => context.languages = languages,
+                Err(e) => tracing::debug!(error = %e, "couldn't fetch repository languages"),
+            }
+        }
+
+        let repo_config = self.repo_config(owner, repo, fetch_options.git_ref.as_deref()).await;
+        fetch_options.exclude.extend(repo_config.exclude.iter().cloned());
+
+        let wants_manifest = self.manifest_output.is_some() || self.previous_manifest.is_some();
+        let (manifest, tree) = if wants_manifest && context.changes.is_none() {
+            // Listed once, for both the manifest and the fetch
+            let tree = self.client.tree_for(owner, repo, &fetch_options).await?;
+            let manifest = self.current_manifest(owner, repo, &context, &tree, &fetch_options).await?;
+            if let Some(previous) = &self.previous_manifest {
+                let changed = previous.changed_in(&manifest);
+                let deleted = previous.removed_in(&manifest);
+                tracing::info!(changed = changed.len(), removed = deleted.len(), "compared with the previous manifest");
+                fetch_options.only_paths = Some(changed);
+                context.changes = Some(RefChanges {
+                    base: previous.commit_sha.clone().unwrap_or_else(|| "previous export".to_string()),
+                    head: context.commit_sha.clone().unwrap_or_else(|| fetch_options.git_ref().to_string()),
+                    renamed: Vec::new(),
+                    deleted,
+                });
+            }
+            (Some(manifest), Some(tree))
+        } else {
+            (None, None)
+        };
+
+        // A diff export has only some of the files at its ref, so it gets a checkpoint of
+        // its own rather than sharing one with a full export of that ref
+        let checkpoint_ref = match &context.changes {
+            Some(changes) => format!("{}...{}", changes.base, changes.head),
+            None => fetch_options.git_ref().to_string(),
+        };
+        let mut checkpoint = match &self.checkpoint_dir {
+            Some(dir) => {
+                if self.fetch_options.git_ref.is_none() && context.changes.is_none() {
+                    Checkpoint::migrate(dir, owner, repo, DEFAULT_REF, &checkpoint_ref)?;
+                }
+                Some(Checkpoint::open(dir, owner, repo, &checkpoint_ref, &fetch_options.filter_digest())?)
+            }
+            None => None,
+        };
+        let resumed_files = checkpoint.as_ref().map_or(0, |c| c.files().len());
+        if resumed_files > 0 {
+            tracing::info!(resumed_files, "resuming from checkpoint");
+        }
+
+        let (RepoFiles { mut files, failed, unmatched, .. }, interrupted) = match self.fetch(owner, repo, &fetch_options, tree, checkpoint.as_mut()).await {
+            Ok(fetched) => fetched,
+            Err(e) => {
+                if e.is::<EmptyRepositoryError>() {
+                    if let Some(checkpoint) = checkpoint {
+                        checkpoint.remove()?;
+                    }
+                }
+                return Err(e);
+            }
+        };
+
+        // The wiki isn't part of the comparison
+        if self.include_wiki && !interrupted && context.changes.is_none() {
+            files.extend(self.wiki_pages(owner, repo, &fetch_options).await);
+        }
+        self.transform(&mut files);
+        let redactions = self.redact(&mut files, &repo_config.redact);
+
+        Ok(Collected {
+            files,
+            failed,
+            unmatched,
+            resumed_files,
+            redactions,
+            context,
+            checkpoint,
+            interrupted,
+            repo_config,
+            manifest,
+        })
+    }
+
+    /// Errors before any request if the previous manifest is for another repository
+    fn check_previous_manifest(&self, owner: &str, repo: &str) -> Result<()> {
+        self.previous_manifest.as_ref().map_or(Ok(()), |previous| previous.check_repo(owner, repo))
+    }
+
+    /// The blob SHAs of the files in `tree` that `fetch_options` makes eligible, whatever
+    /// their size
+    async fn current_manifest(
+        &self,
+        owner: &str,
+        repo: &str,
+        context: &ExportContext,
+        tree: &[GitTreeEntry],
+        fetch_options: &FetchOptions,
+    ) -> Result<Manifest> {
+        let list_options = FetchOptions { max_file_size: None, max_files: None, ..fetch_options.clone() };
+        let entries = self.client.list_tree_files(owner, repo, tree, &list_options).await?;
+        Ok(Manifest {
+            commit_sha: context.commit_sha.clone(),
+            files: entries.into_iter().map(|entry| (entry.path, entry.sha)).collect(),
+            ..Manifest::new(owner, repo)
+        })
+    }
+
+    /// The repository's wiki pages, placed under `WIKI_DIR`. A wiki that can't be
+    /// cloned is logged and left out rather than failing the export.
+    async fn wiki_pages(&self, owner: &str, repo: &str, fetch_options: &FetchOptions) -> Vec<ExportedFile> {
+        match self.client.fetch_wiki(owner, repo, fetch_options).await {
+            Ok(Some(pages)) => {
+                tracing::info!(pages = pages.len(), "including wiki pages");
+                pages
+                    .into_iter()
+                    .map(|page| ExportedFile { path: format!("{}/{}", WIKI_DIR, page.path), ..page })
+                    .collect()
+            }
+            Ok(None) => {
+                tracing::info!(owner, repo, "repository has no wiki to include");
+                Vec::new()
+            }
+            Err(e) => {
+                tracing::warn!(error = %e, "couldn't fetch the wiki; exporting without it");
+                Vec::new()
+            }
+        }
+    }
+
+    /// The repository's `export.toml` if it has one and it's honored. One that can't be
+    /// fetched or parsed is logged and ignored rather than failing the export.
+    async fn repo_config(&self, owner: &str, repo: &str, git_ref: Option<&str>) -> RepoConfig {
+        if !self.honor_repo_config {
+            return RepoConfig::default();
+        }
+        match self.client.fetch_repo_config(owner, repo, git_ref).await {
+            Ok(Some(config)) => {
+                tracing::info!(owner, repo, file = REPO_CONFIG_FILE, "applying repository export defaults");
+                config
+            }
+            Ok(None) => RepoConfig::default(),
+            Err(e) => {
+                tracing::warn!(owner, repo, file = REPO_CONFIG_FILE, error = %e, "ignoring repository export defaults");
+                RepoConfig::default()
+            }
+        }
+    }
+
+    /// Applies the content transform, if one is set, to every file but symlinks
+    fn transform(&self, files: &mut [ExportedFile]) {
+        let Some(transform) = &self.content_transform else {
+            return;
+        };
+        for file in files.iter_mut().filter(|file| file.symlink_target.is_none()) {
+            file.content = transform(&file.path, &file.content);
+        }
+    }
+
+    /// Scrubs secrets from `files` if a redactor is set, returning how many were redacted.
+    /// `extra_rules` (from the repository's `export.toml`) are applied on top.
+    fn redact(&self, files: &mut [ExportedFile], extra_rules: &[RedactionRule]) -> usize {
+        let Some(redactor) = &self.redactor else {
+            return 0;
+        };
+        let redactor = if extra_rules.is_empty() {
+            Cow::Borrowed(redactor)
+        } else {
+            let mut redactor = redactor.clone();
+            for rule in extra_rules {
+                redactor.add_rule(rule.clone());
+            }
+            Cow::Owned(redactor)
+        };
+
+        let mut redactions = 0;
+        for file in files.iter_mut() {
+            let (redacted, count) = redactor.redact(&file.content);
+            file.content = redacted;
+            redactions += count;
+        }
+        redactions
+    }
+
+    /// The fetch options for one repository, with `latest` replaced by its latest release tag,
+    /// then with `at` replaced by the SHA of the ref's last commit at that time.
+    /// Repositories without releases fall back to the default branch.
+    async fn resolve_ref(&self, owner: &str, repo: &str) -> Result<FetchOptions> {
+        let mut fetch_options = self.fetch_options.clone();
+        if fetch_options.git_ref.as_deref() == Some(LATEST_RELEASE_REF) {
+            fetch_options.git_ref = self.client.latest_release_tag(owner, repo).await?;
+            match &fetch_options.git_ref {
+                Some(tag) => tracing::info!(%tag, "exporting the latest release"),
+                None => tracing::warn!(owner, repo, "no releases; exporting the default branch instead"),
+            }
+        }
+
+        if let Some(at) = fetch_options.at.take() {
+            let sha = self.client.commit_at(owner, repo, fetch_options.git_ref.as_deref(), at).await?;
+            tracing::info!(%sha, %at, "exporting the commit current at the given time");
+            fetch_options.git_ref = Some(sha);
+        }
+        Ok(fetch_options)
+    }
+
+    /// Streams the repository's files into `checkpoint` (when there is one) until done or
+    /// interrupted; the flag says whether it was interrupted
+    async fn fetch(
+        &self,
+        owner: &str,
+        repo: &str,
+        fetch_options: &FetchOptions,
+        tree: Option<Vec<GitTreeEntry>>,
+        mut checkpoint: Option<&mut Checkpoint>,
+    ) -> Result<(RepoFiles, bool)> {
+        let mut fetch_options = fetch_options.clone();
+        if let Some(checkpoint) = &checkpoint {
+            fetch_options.already_fetched = checkpoint.paths();
+            // Resumed files count towards the total size cap
+            let resumed_bytes: usize = checkpoint.files().iter().map(|f| f.content.len()).sum();
+            fetch_options.max_total_bytes = fetch_options.max_total_bytes.map(|limit| limit.saturating_sub(resumed_bytes));
+            // And towards the file count cap
+            fetch_options.max_files = fetch_options.max_files.map(|limit| limit.saturating_sub(checkpoint.files().len()));
+        }
+
+        let stream = match tree {
+            Some(tree) => self.client.stream_tree_files(owner, repo, tree, &fetch_options).left_stream(),
+            None => self.client.stream_repo_files(owner, repo, &fetch_options).right_stream(),
+        };
+        pin_mut!(stream);
+        let mut fetched = RepoFiles::default();
+        // Interrupting cancels the token in `fetch_options`, which ends the stream early
+        while let Some(item) = stream.next().await {
+            match (item?, checkpoint.as_deref_mut()) {
+                (FetchItem::File(file), Some(checkpoint)) => checkpoint.record(&file)?,
+                (FetchItem::File(file), None) => fetched.files.push(file),
+                // Failures aren't checkpointed, so a resumed run retries them
+                (FetchItem::Failed(failure), _) => fetched.failed.push(failure),
+                (FetchItem::Unmatched(_), _) => fetched.unmatched += 1,
+            }
+        }
+
+        if let Some(checkpoint) = checkpoint {
+            fetched.files = checkpoint.sorted_files();
+        }
+        let interrupted = self.interrupt.is_interrupted();
+        if interrupted {
+            tracing::warn!("export interrupted; writing the files fetched so far");
+        }
+        Ok((fetched, interrupted))
+    }
+
+    /// The formats set on the builder, else the repository's preference for exports
+    /// written to a default path, else Markdown. Never empty.
+    fn formats_for(&self, collected: &Collected) -> Vec<ExportFormat> {
+        if !self.formats.is_empty() {
+            return self.formats.clone();
+        }
+        vec![collected.repo_config.format.filter(|_| self.output.is_none()).unwrap_or_default()]
+    }
+
+    /// Writes `collected` in each of `formats` from the same files, returning the paths written
+    fn write(&self, path: &Path, collected: &Collected, formats: &[ExportFormat]) -> Result<Vec<PathBuf>> {
+        let (context, files) = (&collected.context, &collected.files);
+        let filters = if self.export_options.filters.is_empty() {
+            collected.repo_config.filters.clone()
+        } else {
+            self.export_options.filters.clone()
+        };
+        let options = ExportOptions {
+            interrupted: collected.interrupted,
+            filters,
+            ..self.export_options.clone()
+        };
+        write_outputs(
+            path,
+            formats,
+            |format, out| export_to(format, out, context, files, &options),
+            |format, path| export(format, context, files, &collected.failed, &options, path),
+        )
+    }
+}
+
+fn is_stdout(path: &Path) -> bool {
+    path == Path::new(STDOUT_OUTPUT)
+}
+
+/// Writes `output` in each of `formats` and returns the paths written. With several
+/// formats, each file gets its own extension next to `output`, as `export_all` does.
+/// When `output` is `-`, the one format allowed goes to stdout through `to_stdout`.
+fn write_outputs(
+    output: &Path,
+    formats: &[ExportFormat],
+    to_stdout: impl FnOnce(ExportFormat, io::StdoutLock<'static>) -> Result<()>,
+    mut to_file: impl FnMut(ExportFormat, &Path) -> Result<PathBuf>,
+) -> Result<Vec<PathBuf>> {
+    if is_stdout(output) {
+        let [format] = formats[..] else {
+            return Err(anyhow!("Only one format can be written to stdout"));
+        };
+        to_stdout(format, io::stdout().lock())?;
+        return Ok(vec![output.to_path_buf()]);
+    }
+    if let [format] = formats {
+        return Ok(vec![to_file(*format, output)?]);
+    }
+    formats.iter().map(|&format| to_file(format, &output.with_extension(format.extension()))).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use base64::{engine::general_purpose, Engine as _};
+    use chrono::{DateTime, Utc};
+    use wiremock::matchers::{method, path, path_regex, query_param};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    /// A recursive tree listing with a blob for each of `paths`
+    fn tree_response(paths: &[&str]) -> ResponseTemplate {
+        let entries: Vec<_> = paths.iter().map(|p| serde_json::json!({ "path": p, "type": "blob" })).collect();
+        ResponseTemplate::new(200).set_body_json(serde_json::json!({ "tree": entries }))
+    }
+
+    /// `content` base64-encoded, as the contents API serves a file
+    fn file_response(content: &str) -> ResponseTemplate {
+        ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "content": general_purpose::STANDARD.encode(content),
+            "encoding": "base64"
+        }))
+    }
+
+    /// Serves `owner/{repo}`'s tree at `git_ref` with a blob for each of `paths`
+    async fn mount_tree(server: &MockServer, repo: &str, git_ref: &str, paths: &[&str]) {
+        Mock::given(method("GET"))
+            .and(path(format!("/repos/owner/{}/git/trees/{}", repo, git_ref)))
+            .respond_with(tree_response(paths))
+            .mount(server)
+            .await;
+    }
+
+    /// Serves `file_path` in `owner/{repo}` at any ref
+    async fn mount_file(server: &MockServer, repo: &str, file_path: &str, content: &str) {
+        Mock::given(method("GET"))
+            .and(path(format!("/repos/owner/{}/contents/{}", repo, file_path)))
+            .respond_with(file_response(content))
+            .mount(server)
+            .await;
+    }
+
+    #[tokio::test]
+    async fn test_several_formats_share_one_fetch() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/repos/owner/repo/git/trees/HEAD"))
+            .respond_with(tree_response(&["src/lib.rs"]))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/repos/owner/repo/contents/src/lib.rs"))
+            .respond_with(file_response("pub fn lib() {}\n"))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let output = std::env::temp_dir().join(format!("repo_exporter_formats_{}.md", std::process::id()));
+        let exporter = Exporter::builder("token")
+            .base_url(&server.uri())
+            .formats([ExportFormat::Markdown, ExportFormat::Json])
+            .output(&output)
+            .build()
+            .unwrap();
+
+        let summary = exporter.run("owner", "repo").await.unwrap();
+        let markdown = std::fs::read_to_string(&output).unwrap();
+        let json = std::fs::read_to_string(output.with_extension("json")).unwrap();
+        std::fs::remove_file(&output).ok();
+        std::fs::remove_file(output.with_extension("json")).ok();
+
+        assert_eq!(summary.output.as_deref(), Some(output.as_path()));
+        assert_eq!(summary.extra_outputs, vec![output.with_extension("json")]);
+        assert!(markdown.contains("## src/lib.rs"));
+        assert!(json.contains("\"src/lib.rs\""));
+    }
+
+    #[tokio::test]
+    async fn test_exporter_writes_requested_format_and_path() {
+        let server = MockServer::start().await;
+        mount_tree(&server, "repo", "HEAD", &["config.env"]).await;
+        mount_file(&server, "repo", "config.env", "password=hunter2\n").await;
+
+        let output = std::env::temp_dir().join(format!("repo_exporter_facade_{}.json", std::process::id()));
+        let exporter = Exporter::builder("token")
+            .base_url(&server.uri())
+            .format(ExportFormat::Json)
+            .output(&output)
+            .redactor(Redactor::default())
+            .build()
+            .unwrap();
+
+        let summary = exporter.run("owner", "repo").await.unwrap();
+        let written = std::fs::read_to_string(&output).unwrap();
+        std::fs::remove_file(&output).ok();
+
+        assert_eq!(summary.output.as_deref(), Some(output.as_path()));
+        assert_eq!(summary.files_exported, 1);
+        assert_eq!(summary.redactions, 1);
+
+        let value: serde_json::Value = serde_json::from_str(&written).unwrap();
+        assert_eq!(value["files"][0]["content"], "password=***REDACTED***\n");
+    }
+
+    #[tokio::test]
+    async fn test_incremental_export_skips_unchanged_files_and_lists_removed() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/repos/owner/repo/git/trees/HEAD"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({ "tree": [
+                { "path": "README.md", "type": "blob", "sha": "a1" },
+                { "path": "src/lib.rs", "type": "blob", "sha": "b9" },
+                { "path": "src/new.rs", "type": "blob", "sha": "d4" }
+            ] })))
+            // Listed once for both the manifest and the fetch
+            .expect(1)
+            .mount(&server)
+            .await;
+        mount_file(&server, "repo", "src/lib.rs", "pub fn lib() {}\n").await;
+        mount_file(&server, "repo", "src/new.rs", "pub fn new() {}\n").await;
+        Mock::given(method("GET"))
+            .and(path("/repos/owner/repo/contents/README.md"))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(0)
+            .mount(&server)
+            .await;
+
+        let previous = Manifest {
+            commit_sha: Some("0ld5ha".to_string()),
+            files: [("README.md", "a1"), ("src/lib.rs", "b2"), ("src/old.rs", "c3")]
+                .into_iter()
+                .map(|(path, sha)| (path.to_string(), sha.to_string()))
+                .collect(),
+            ..Manifest::new("owner", "repo")
+        };
+        let output = std::env::temp_dir().join(format!("repo_exporter_incremental_{}.json", std::process::id()));
+        let manifest_path = output.with_extension("manifest.json");
+        let exporter = Exporter::builder("token")
+            .base_url(&server.uri())
+            .format(ExportFormat::Json)
+            .output(&output)
+            .manifest(&manifest_path)
+            .incremental(previous)
+            .build()
+            .unwrap();
+
+        let summary = exporter.run("owner", "repo").await.unwrap();
+        let written = std::fs::read_to_string(&output).unwrap();
+        let manifest = Manifest::load(&manifest_path);
+        std::fs::remove_file(&output).ok();
+        std::fs::remove_file(&manifest_path).ok();
+
+        assert_eq!(summary.files_exported, 2);
+        assert_eq!(summary.manifest.as_deref(), Some(manifest_path.as_path()));
+        let value: serde_json::Value = serde_json::from_str(&written).unwrap();
+        let paths: Vec<_> = value["files"].as_array().unwrap().iter().map(|file| file["path"].as_str().unwrap()).collect();
+        assert_eq!(paths, vec!["src/lib.rs", "src/new.rs"]);
+        assert_eq!(value["changes"]["base"], "0ld5ha");
+        assert_eq!(value["changes"]["deleted"], serde_json::json!(["src/old.rs"]));
+        let manifest = manifest.unwrap();
+        assert_eq!(manifest.files.keys().collect::<Vec<_>>(), vec!["README.md", "src/lib.rs", "src/new.rs"]);
+        assert_eq!(manifest.files["src/lib.rs"], "b9");
+
+        // A manifest for another repository is refused before anything is requested
+        let other = MockServer::start().await;
+        let exporter = Exporter::builder("token")
+            .base_url(&other.uri())
+            .incremental(Manifest::new("owner", "other"))
+            .build()
+            .unwrap();
+        let error = exporter.run("owner", "repo").await.unwrap_err();
+        assert_eq!(error.to_string(), "The manifest is for owner/other, not owner/repo");
+        assert!(other.received_requests().await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_content_transform_runs_before_redaction() {
+        let server = MockServer::start().await;
+        mount_tree(&server, "repo", "HEAD", &["src/main.rs"]).await;
+        mount_file(&server, "repo", "src/main.rs", "// Copyright Example Corp\nfn main() {}\n").await;
+
+        let output = std::env::temp_dir().join(format!("repo_exporter_transform_{}.json", std::process::id()));
+        let exporter = Exporter::builder("token")
+            .base_url(&server.uri())
+            .format(ExportFormat::Json)
+            .output(&output)
+            .redactor(Redactor::default())
+            .content_transform(|path, content| {
+                let content = content.strip_prefix("// Copyright Example Corp\n").unwrap_or(content);
+                format!("// {}\npassword=hunter2\n{}", path, content)
+            })
+            .build()
+            .unwrap();
+
+        exporter.run("owner", "repo").await.unwrap();
+        let written = std::fs::read_to_string(&output).unwrap();
+        std::fs::remove_file(&output).ok();
+
+        let value: serde_json::Value = serde_json::from_str(&written).unwrap();
+        assert_eq!(value["files"][0]["content"], "// src/main.rs\npassword=***REDACTED***\nfn main() {}\n");
+    }
+
+    #[tokio::test]
+    async fn test_repo_config_sets_defaults_below_the_callers() {
+        let server = MockServer::start().await;
+        let config = "format = \"json\"\nexclude = [\"fixtures/\"]\nfilters = [\"strip-comments\"]\n\n[redact]\nhost = 'internal\\.example\\.com'\n";
+        mount_file(&server, "repo", "export.toml", config).await;
+        mount_tree(&server, "repo", "HEAD", &["fixtures/data.rs", "src/main.rs"]).await;
+        mount_file(&server, "repo", "src/main.rs", "// calls internal.example.com\nfn main() { connect(\"internal.example.com\") }\n").await;
+
+        // An explicit output path keeps the default Markdown format
+        let output = std::env::temp_dir().join(format!("repo_exporter_repo_config_{}.md", std::process::id()));
+        let exporter = Exporter::builder("token")
+            .base_url(&server.uri())
+            .output(&output)
+            .redactor(Redactor::new(Vec::new()))
+            .build()
+            .unwrap();
+
+        let summary = exporter.run("owner", "repo").await.unwrap();
+        let written = std::fs::read_to_string(&output).unwrap();
+        std::fs::remove_file(&output).ok();
+
+        assert_eq!(summary.files_exported, 1);
+        assert_eq!(summary.redactions, 2);
+        assert!(written.starts_with("# Repository Export: owner/repo\n"));
+        assert!(written.contains("comment lines stripped"));
+        assert!(written.contains("fn main() { connect(\"***REDACTED***\") }"));
+        assert!(!written.contains("fixtures/"));
+    }
+
+    #[tokio::test]
+    async fn test_default_branch_is_fetched_by_name() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/repos/owner/repo"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({ "default_branch": "trunk" })))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/repos/owner/repo/commits/trunk"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "sha": "0123abcd",
+                "commit": { "message": "Initial commit" }
+            })))
+            .mount(&server)
+            .await;
+        mount_tree(&server, "repo", "trunk", &["README.md"]).await;
+        Mock::given(method("GET"))
+            .and(path("/repos/owner/repo/contents/README.md"))
+            .and(query_param("ref", "trunk"))
+            .respond_with(file_response("# Trunk\n"))
+            .mount(&server)
+            .await;
+
+        let output = std::env::temp_dir().join(format!("repo_exporter_default_branch_{}.md", std::process::id()));
+        let exporter = Exporter::builder("token").base_url(&server.uri()).output(&output).build().unwrap();
+
+        let summary = exporter.run("owner", "repo").await.unwrap();
+        let written = std::fs::read_to_string(&output).unwrap();
+        std::fs::remove_file(&output).ok();
+
+        assert_eq!(summary.files_exported, 1);
+        assert!(written.contains("> Branch: `trunk` · Commit: `0123abcd`"));
+        assert!(written.contains("# Trunk"));
+    }
+
+    #[tokio::test]
+    async fn test_run_combined_reports_each_repository() {
+        let server = MockServer::start().await;
+        mount_tree(&server, "good", "HEAD", &["README.md"]).await;
+        mount_file(&server, "good", "README.md", "# Good\n").await;
+
+        let output = std::env::temp_dir().join(format!("repo_exporter_batch_{}.md", std::process::id()));
+        let exporter = Exporter::builder("token").base_url(&server.uri()).output(&output).build().unwrap();
+
+        let repos = vec![
+            ("owner".to_string(), "good".to_string()),
+            ("owner".to_string(), "missing".to_string()),
+        ];
+        let outcomes = exporter.run_combined(&repos).await.unwrap();
+        let written = std::fs::read_to_string(&output).unwrap();
+        std::fs::remove_file(&output).ok();
+
+        assert_eq!(outcomes[0].result.as_ref().unwrap().files_exported, 1);
+        assert!(outcomes[1].result.is_err());
+        assert!(written.contains("## owner/good\n\n### README.md"));
+        assert!(!written.contains("owner/missing"));
+    }
+
+    #[tokio::test]
+    async fn test_run_compare_exports_changed_files_at_head() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/repos/owner/repo/compare/v1.0...v1.1"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "files": [
+                    { "filename": "src/lib.rs", "status": "modified" },
+                    { "filename": "docs/guide.md", "status": "renamed", "previous_filename": "GUIDE.md" },
+                    { "filename": "old.rs", "status": "removed" }
+                ]
+            })))
+            .mount(&server)
+            .await;
+        mount_tree(&server, "repo", "v1.1", &["README.md", "docs/guide.md", "src/lib.rs"]).await;
+        for (file, content) in [("docs/guide.md", "# Guide\n"), ("src/lib.rs", "pub fn lib() {}\n")] {
+            Mock::given(method("GET"))
+                .and(path(format!("/repos/owner/repo/contents/{}", file)))
+                .and(query_param("ref", "v1.1"))
+                .respond_with(file_response(content))
+                .mount(&server)
+                .await;
+        }
+
+        let output = std::env::temp_dir().join(format!("repo_exporter_compare_{}.md", std::process::id()));
+        let exporter = Exporter::builder("token").base_url(&server.uri()).output(&output).build().unwrap();
+
+        let summary = exporter.run_compare("owner", "repo", "v1.0", "v1.1").await.unwrap();
+        let written = std::fs::read_to_string(&output).unwrap();
+        std::fs::remove_file(&output).ok();
+
+        assert_eq!(summary.files_exported, 2);
+        assert!(written.contains("> Only the files changed between `v1.0` and `v1.1`, as of `v1.1`."));
+        assert!(written.contains("> Renamed: `GUIDE.md` → `docs/guide.md`"));
+        assert!(written.contains("> Deleted: `old.rs`"));
+        assert!(written.contains("## src/lib.rs"));
+        assert!(!written.contains("## README.md"));
+    }
+
+    #[tokio::test]
+    async fn test_latest_ref_resolves_to_release_tag() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/repos/owner/released/releases/latest"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({ "tag_name": "v1.2.0" })))
+            .mount(&server)
+            .await;
+        for (repo, git_ref) in [("released", "v1.2.0"), ("unreleased", "HEAD")] {
+            mount_tree(&server, repo, git_ref, &["README.md"]).await;
+            mount_file(&server, repo, "README.md", git_ref).await;
+        }
+
+        let exporter = Exporter::builder("token")
+            .base_url(&server.uri())
+            .fetch_options(FetchOptions {
+                git_ref: Some("latest".to_string()),
+                ..FetchOptions::default()
+            })
+            .build()
+            .unwrap();
+
+        let released = exporter.collect("owner", "released").await.unwrap();
+        assert_eq!(released.files[0].content, "v1.2.0");
+
+        // No releases: falls back to the default branch rather than failing
+        let unreleased = exporter.collect("owner", "unreleased").await.unwrap();
+        assert_eq!(unreleased.files[0].content, "HEAD");
+    }
+
+    #[tokio::test]
+    async fn test_at_exports_last_commit_before_the_date() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/repos/owner/repo/commits"))
+            .and(query_param("sha", "main"))
+            .and(query_param("until", "2025-01-01T23:59:59Z"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!([{
+                "sha": "abc123",
+                "commit": { "message": "Last of the year", "committer": { "date": "2024-12-31T18:00:00Z" } }
+            }])))
+            .mount(&server)
+            .await;
+        mount_tree(&server, "repo", "abc123", &["README.md"]).await;
+        Mock::given(method("GET"))
+            .and(path("/repos/owner/repo/contents/README.md"))
+            .and(query_param("ref", "abc123"))
+            .respond_with(file_response("# Old"))
+            .mount(&server)
+            .await;
+
+        let exporter = Exporter::builder("token")
+            .base_url(&server.uri())
+            .fetch_options(FetchOptions {
+                git_ref: Some("main".to_string()),
+                at: Some(DateTime::parse_from_rfc3339("2025-01-01T23:59:59Z").unwrap().with_timezone(&Utc)),
+                ..FetchOptions::default()
+            })
+            .build()
+            .unwrap();
+
+        let collected = exporter.collect("owner", "repo").await.unwrap();
+        assert_eq!(collected.context.git_ref.as_deref(), Some("abc123"));
+        assert_eq!(collected.files[0].content, "# Old");
+    }
+
+    #[tokio::test]
+    async fn test_index_lists_files_from_the_tree_alone() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/repos/owner/repo/git/trees/HEAD"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "tree": [
+                    { "path": "src", "type": "tree" },
+                    { "path": "src/main.rs", "type": "blob", "size": 120 },
+                    { "path": "README.md", "type": "blob", "size": 40 },
+                    { "path": "node_modules/dep/index.js", "type": "blob", "size": 9 }
+                ]
+            })))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path_regex("/contents/"))
+            .respond_with(ResponseTemplate::new(500))
+            .expect(0)
+            .mount(&server)
+            .await;
+
+        let output = std::env::temp_dir().join(format!("repo_exporter_index_{}.md", std::process::id()));
+        let exporter = Exporter::builder("token").base_url(&server.uri()).output(&output).build().unwrap();
+        let summary = exporter.run_index("owner", "repo", false).await.unwrap();
+        let written = std::fs::read_to_string(&output).unwrap();
+        std::fs::remove_file(&output).ok();
+
+        assert_eq!(summary.files_exported, 2);
+        assert!(written.contains("| `README.md` | markdown | 40 |\n| `src/main.rs` | rust | 120 |\n"));
+        assert!(written.contains("2 files, 160 bytes"));
+    }
+
+    /// Interrupts the export as soon as the first file has been fetched
+    struct InterruptAfterFirstFile(InterruptHandle);
+
+    impl ProgressReporter for InterruptAfterFirstFile {
+        fn on_file_start(&self, _path: &str) {}
+        fn on_file_done(&self, _path: &str, _bytes: usize) {
+            self.0.interrupt();
+        }
+        fn on_file_skipped(&self, _path: &str, _reason: &crate::progress::SkipReason) {}
+    }
+
+    #[tokio::test]
+    async fn test_interrupted_export_writes_partial_output_and_keeps_checkpoint() {
+        let server = MockServer::start().await;
+        let names = ["a.txt", "b.txt", "c.txt"];
+        mount_tree(&server, "interrupted", "HEAD", &names).await;
+        for name in names {
+            mount_file(&server, "interrupted", name, name).await;
+        }
+
+        let dir = std::env::temp_dir().join(format!("repo_exporter_interrupt_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let output = dir.join("export.md");
+        let interrupt = InterruptHandle::new();
+        let exporter = Exporter::builder("token")
+            .base_url(&server.uri())
+            .progress(Arc::new(InterruptAfterFirstFile(interrupt.clone())))
+            .interrupt_handle(interrupt)
+            .output(&output)
+            .checkpoint_dir(&dir)
+            .build()
+            .unwrap();
+
+        let summary = exporter.run("owner", "interrupted").await.unwrap();
+        let written = std::fs::read_to_string(&output).unwrap();
+        let checkpoint_kept = Checkpoint::exists_for_repo(&dir, "owner", "interrupted");
+        std::fs::remove_dir_all(&dir).ok();
+
+        assert!(summary.interrupted);
+        assert_eq!(summary.files_exported, 1);
+        assert!(written.contains("> ⚠️ This export was interrupted"));
+        assert!(written.contains("## a.txt\n\n```text\na.txt\n```"));
+        assert!(!written.contains("b.txt"));
+        assert!(checkpoint_kept);
+    }
+}
@@ -0,0 +1,60 @@
+// ============= src/github/auth.rs =============
+use anyhow::{anyhow, Result};
+use std::fmt;
+use std::str::FromStr;
+
+/// Prefix used in the `Authorization` header.
+///
+/// PATs, fine-grained tokens and app installation tokens all accept `Bearer`;
+/// some OAuth device-flow tokens are only accepted with the older `token` prefix.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum AuthScheme {
+    #[default]
+    Bearer,
+    Token,
+}
+
+impl AuthScheme {
+    pub fn header_value(&self, token: &str) -> String {
+        match self {
+            AuthScheme::Bearer => format!("Bearer {}", token),
+            AuthScheme::Token => format!("token {}", token),
+        }
+    }
+}
+
+impl fmt::Display for AuthScheme {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AuthScheme::Bearer => write!(f, "bearer"),
+            AuthScheme::Token => write!(f, "token"),
+        }
+    }
+}
+
+impl FromStr for AuthScheme {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim().to_lowercase().as_str() {
+            "bearer" => Ok(AuthScheme::Bearer),
+            "token" => Ok(AuthScheme::Token),
+            other => Err(anyhow!("Unknown GITHUB_AUTH_SCHEME '{}'. Expected 'bearer' or 'token'", other)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_auth_scheme() {
+        assert_eq!("Bearer".parse::<AuthScheme>().unwrap(), AuthScheme::Bearer);
+        assert_eq!(" token ".parse::<AuthScheme>().unwrap(), AuthScheme::Token);
+        assert!("basic".parse::<AuthScheme>().is_err());
+
+        assert_eq!(AuthScheme::Bearer.header_value("abc"), "Bearer abc");
+        assert_eq!(AuthScheme::Token.header_value("abc"), "token abc");
+    }
+}
@@ -1,98 +1,2871 @@
+use super::app::{AppAuth, AppCredentials, InstallationToken};
+use super::auth::AuthScheme;
+use super::error::{EmptyRepositoryError, FileTooLargeError, IsDirectoryError, RepositoryDisabledError};
+use super::http::{HttpSettings, RetrySettings};
+use super::options::{FetchOptions, DEFAULT_REF};
 use super::types::*;
-use crate::utils::should_skip_path;
-use anyhow::{Result, anyhow};
+use super::wiki::{clone_wiki, read_pages};
+use crate::ignore::{parse_export_ignore, parse_ignore_file, PathPatterns, EXPORTER_IGNORE_FILE};
+use crate::normalize::{normalize_line_endings, strip_bom};
+use crate::progress::{ProgressReporter, SilentProgress, SkipReason};
+use crate::repo_config::{RepoConfig, REPO_CONFIG_FILE};
+use crate::utils::{encode_path, encode_segment, is_generated_path, is_under, looks_binary, looks_minified, path_depth, should_skip_path};
+use anyhow::{Context, Result, anyhow};
+use async_stream::try_stream;
 use base64::{engine::general_purpose, Engine as _};
+use chrono::{DateTime, SecondsFormat, Utc};
+use futures::{pin_mut, Stream, TryStreamExt};
+use reqwest::header::{AUTHORIZATION, USER_AGENT};
 use reqwest::Client;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+const DEFAULT_BASE_URL: &str = "https://api.github.com";
+const DEFAULT_RAW_BASE_URL: &str = "https://raw.githubusercontent.com";
+const DEFAULT_GIT_BASE_URL: &str = "https://github.com";
+pub const DEFAULT_USER_AGENT: &str = "Rust-GitHubClient";
+/// The contents API leaves the content out of its response for larger files
+const INLINE_CONTENT_LIMIT: usize = 1024 * 1024;
+/// GitHub won't serve larger files through the API at all
+const API_CONTENT_LIMIT: usize = 100 * 1024 * 1024;
+/// The compare API lists no more changed files than this
+const COMPARE_FILE_LIMIT: usize = 300;
 
 pub struct GitHubClient {
     client: Client,
     token: String,
+    auth_scheme: AuthScheme,
+    /// When set, requests use installation tokens minted for this app instead of `token`
+    app: Option<AppAuth>,
+    user_agent: String,
+    base_url: String,
+    raw_base_url: String,
+    /// Where repositories are cloned over git, for wikis
+    git_base_url: String,
+    retry: RetrySettings,
+    progress: Arc<dyn ProgressReporter>,
+    /// Last-commit time per "owner/repo/path", so repeated `since` filtering is free
+    last_modified_cache: Mutex<HashMap<String, Option<DateTime<Utc>>>>,
+    /// Language breakdown per "owner/repo", fetched at most once per client
+    languages_cache: Mutex<HashMap<String, Vec<String>>>,
+}
+
+/// Configures a `GitHubClient`; see `GitHubClient::builder`. Anything left unset keeps
+/// the default `GitHubClient::new` uses.
+#[derive(Default)]
+pub struct GitHubClientBuilder {
+    token: Option<String>,
+    base_url: Option<String>,
+    raw_base_url: Option<String>,
+    git_base_url: Option<String>,
+    http: HttpSettings,
+    auth_scheme: AuthScheme,
+    user_agent: Option<String>,
+    progress: Option<Arc<dyn ProgressReporter>>,
+    app_credentials: Option<AppCredentials>,
+}
+
+impl GitHubClientBuilder {
+    /// The personal access token to authenticate with; needed unless `app_credentials` is set
+    pub fn token(mut self, token: impl Into<String>) -> Self {
+        self.token = Some(token.into());
+        self
+    }
+
+    /// Points the client at a different API root; see `GitHubClient::with_base_url`
+    pub fn base_url(mut self, base_url: &str) -> Self {
+        self.base_url = Some(base_url.to_string());
+        self
+    }
+
+    /// Sets the host used by the raw-content fallback (see `FetchOptions::raw_fallback`)
+    pub fn raw_base_url(mut self, raw_base_url: &str) -> Self {
+        self.raw_base_url = Some(raw_base_url.to_string());
+        self
+    }
+
+    /// Sets where wikis are cloned from (`https://github.com` by default)
+    pub fn git_base_url(mut self, git_base_url: &str) -> Self {
+        self.git_base_url = Some(git_base_url.to_string());
+        self
+    }
+
+    /// Replaces all the HTTP settings: timeouts, proxy and retries
+    pub fn http_settings(mut self, settings: HttpSettings) -> Self {
+        self.http = settings;
+        self
+    }
+
+    /// Limit for each wait on GitHub (30 seconds by default); see `HttpSettings::timeout`
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.http.timeout = timeout;
+        self
+    }
+
+    /// How often failed requests are retried, and how long to wait in between
+    pub fn retry(mut self, retry: RetrySettings) -> Self {
+        self.http.retry = retry;
+        self
+    }
+
+    /// Sets the `Authorization` header prefix (`Bearer` by default)
+    pub fn auth_scheme(mut self, auth_scheme: AuthScheme) -> Self {
+        self.auth_scheme = auth_scheme;
+        self
+    }
+
+    /// Sets the `User-Agent`; a blank value keeps the default
+    pub fn user_agent(mut self, user_agent: &str) -> Self {
+        self.user_agent = Some(user_agent.to_string());
+        self
+    }
+
+    /// Sets the reporter that receives per-file progress events (silent by default)
+    pub fn progress(mut self, progress: Arc<dyn ProgressReporter>) -> Self {
+        self.progress = Some(progress);
+        self
+    }
+
+    /// Authenticates as a GitHub App installation instead of with a token
+    pub fn app_credentials(mut self, credentials: AppCredentials) -> Self {
+        self.app_credentials = Some(credentials);
+        self
+    }
+
+    /// Fails if neither a token nor app credentials were given, since every request
+    /// would then be rejected
+    pub fn build(self) -> Result<GitHubClient> {
+        if self.token.as_deref().is_none_or(|token| token.trim().is_empty()) && self.app_credentials.is_none() {
+            return Err(anyhow!("A GitHub client needs a token or GitHub App credentials"));
+        }
+
+        let mut client = GitHubClient::new(self.token.unwrap_or_default())
+            .with_http_settings(&self.http)?
+            .with_auth_scheme(self.auth_scheme);
+        if let Some(base_url) = &self.base_url {
+            client = client.with_base_url(base_url);
+        }
+        if let Some(raw_base_url) = &self.raw_base_url {
+            client = client.with_raw_base_url(raw_base_url);
+        }
+        if let Some(git_base_url) = &self.git_base_url {
+            client = client.with_git_base_url(git_base_url);
+        }
+        if let Some(user_agent) = &self.user_agent {
+            client = client.with_user_agent(user_agent);
+        }
+        if let Some(progress) = self.progress {
+            client = client.with_progress(progress);
+        }
+        if let Some(credentials) = self.app_credentials {
+            client = client.with_app_credentials(credentials);
+        }
+        Ok(client)
+    }
 }
 
 impl GitHubClient {
+    /// Configures every setting at once, e.g.
+    /// `GitHubClient::builder().token(token).timeout(Duration::from_secs(60)).build()?`
+    pub fn builder() -> GitHubClientBuilder {
+        GitHubClientBuilder::default()
+    }
+
+    /// A client authenticating with `token` and otherwise using the defaults; the
+    /// `with_*` methods or `builder` change the rest
     pub fn new(token: String) -> Self {
         Self {
-            client: Client::new(),
+            // The defaults only fail where the TLS backend can't initialize, and then
+            // `Client::default` panics just as `Client::new` would
+            client: HttpSettings::default().client().unwrap_or_default(),
             token,
+            auth_scheme: AuthScheme::default(),
+            app: None,
+            user_agent: DEFAULT_USER_AGENT.to_string(),
+            base_url: DEFAULT_BASE_URL.to_string(),
+            raw_base_url: DEFAULT_RAW_BASE_URL.to_string(),
+            git_base_url: DEFAULT_GIT_BASE_URL.to_string(),
+            retry: RetrySettings::default(),
+            progress: Arc::new(SilentProgress),
+            last_modified_cache: Mutex::new(HashMap::new()),
+            languages_cache: Mutex::new(HashMap::new()),
         }
     }
 
-    pub async fn fetch_repo_files(&self, owner: &str, repo: &str) -> Result<Vec<(String, String)>> {
-        let url = format!(
-            "https://api.github.com/repos/{}/{}/git/trees/HEAD?recursive=1",
-            owner, repo
-        );
+    /// Points the client at a different API root, e.g. GitHub Enterprise or a test server.
+    /// A GitHub Enterprise root (`https://host/api/v3`) also points wiki clones at `host`.
+    pub fn with_base_url(mut self, base_url: &str) -> Self {
+        self.base_url = base_url.trim_end_matches('/').to_string();
+        if let Some(host) = self.base_url.strip_suffix("/api/v3") {
+            self.git_base_url = host.to_string();
+        }
+        self
+    }
 
-        println!("🔍 Checking repository existence...");
+    /// Sets the host used by the raw-content fallback (see `FetchOptions::raw_fallback`)
+    pub fn with_raw_base_url(mut self, raw_base_url: &str) -> Self {
+        self.raw_base_url = raw_base_url.trim_end_matches('/').to_string();
+        self
+    }
 
-        let response = self.client
-            .get(&url)
-            .bearer_auth(&self.token)
-            .header("User-Agent", "Rust-GitHubClient")
-            .send()
+    /// Sets where wikis are cloned from (`https://github.com` by default; see `fetch_wiki`)
+    pub fn with_git_base_url(mut self, git_base_url: &str) -> Self {
+        self.git_base_url = git_base_url.trim_end_matches('/').to_string();
+        self
+    }
+
+    /// Replaces the default 30 second timeout, and optionally sets a connect timeout
+    /// and proxy
+    pub fn with_http_settings(mut self, settings: &HttpSettings) -> Result<Self> {
+        self.client = settings.client()?;
+        self.retry = settings.retry.clone();
+        Ok(self)
+    }
+
+    /// Sets the `Authorization` header prefix (`Bearer` by default)
+    pub fn with_auth_scheme(mut self, auth_scheme: AuthScheme) -> Self {
+        self.auth_scheme = auth_scheme;
+        self
+    }
+
+    /// Sets the `User-Agent` sent with every request. GitHub rejects requests without one,
+    /// so a blank value keeps the default.
+    pub fn with_user_agent(mut self, user_agent: &str) -> Self {
+        if !user_agent.trim().is_empty() {
+            self.user_agent = user_agent.trim().to_string();
+        }
+        self
+    }
+
+    /// Sets the reporter that receives per-file progress events (silent by default)
+    pub fn with_progress(mut self, progress: Arc<dyn ProgressReporter>) -> Self {
+        self.progress = progress;
+        self
+    }
+
+    /// Authenticates as a GitHub App installation, minting installation tokens as
+    /// needed; the token passed to `new` is then unused
+    pub fn with_app_credentials(mut self, credentials: AppCredentials) -> Self {
+        self.app = Some(AppAuth::new(credentials));
+        self
+    }
+
+    /// Confirms the token authenticates and reports what it can access.
+    ///
+    /// Classic tokens advertise their scopes in the `X-OAuth-Scopes` header; fine-grained
+    /// and app tokens don't, in which case private-repo access is reported as unknown.
+    pub async fn verify_token(&self) -> Result<TokenInfo> {
+        // Installation tokens can't read /user; minting one proves the app credentials work
+        if let Some(app) = &self.app {
+            self.authorization().await?;
+            return Ok(TokenInfo {
+                login: format!(
+                    "GitHub App {} (installation {})",
+                    app.credentials.app_id, app.credentials.installation_id
+                ),
+                scopes: Vec::new(),
+                private_repo_access: None,
+            });
+        }
+
+        let response = self.send_get(&format!("{}/user", self.base_url)).await?;
+
+        let status = response.status();
+        if status == reqwest::StatusCode::UNAUTHORIZED {
+            return Err(anyhow!(
+                "GITHUB_TOKEN was rejected by GitHub (401) using the '{}' auth scheme: the token is invalid or expired, \
+                 or needs a different GITHUB_AUTH_SCHEME ('bearer' or 'token')",
+                self.auth_scheme
+            ));
+        }
+        if !status.is_success() {
+            return Err(api_error(response).await);
+        }
+
+        let scopes: Option<Vec<String>> = response
+            .headers()
+            .get("x-oauth-scopes")
+            .and_then(|value| value.to_str().ok())
+            .map(|value| {
+                value.split(',')
+                    .map(|scope| scope.trim().to_string())
+                    .filter(|scope| !scope.is_empty())
+                    .collect()
+            });
+
+        let user: AuthenticatedUser = response.json().await?;
+        let private_repo_access = scopes.as_ref().map(|scopes| scopes.iter().any(|s| s == "repo"));
+
+        Ok(TokenInfo {
+            login: user.login,
+            scopes: scopes.unwrap_or_default(),
+            private_repo_access,
+        })
+    }
+
+    /// Fetches every eligible file in the repository.
+    ///
+    /// Collects `stream_repo_files`; files that fail to fetch or decode are returned
+    /// separately in `RepoFiles::failed`.
+    #[tracing::instrument(skip(self, options))]
+    pub async fn fetch_repo_files(
+        &self,
+        owner: &str,
+        repo: &str,
+        options: &FetchOptions,
+    ) -> Result<RepoFiles> {
+        let stream = self.stream_repo_files(owner, repo, options);
+        pin_mut!(stream);
+
+        let mut fetched = RepoFiles::default();
+        while let Some(item) = stream.try_next().await? {
+            match item {
+                FetchItem::File(file) => fetched.files.push(file),
+                FetchItem::Failed(failed) => fetched.failed.push(failed),
+                FetchItem::Unmatched(_) => fetched.unmatched += 1,
+            }
+        }
+        fetched.cancelled = options.is_cancelled();
+        Ok(fetched)
+    }
+
+    /// Streams eligible files one at a time as their contents arrive.
+    ///
+    /// Errors fetching the tree end the stream with an `Err`. Per-file fetch or decode
+    /// failures are yielded as `FetchItem::Failed` and reported to the progress reporter;
+    /// files skipped by the size limits are only reported. Files not matching
+    /// `content_pattern` are reported and yielded as `FetchItem::Unmatched`. Cancelling
+    /// `options.cancel` ends the stream early, dropping the file request in flight.
+    pub fn stream_repo_files<'a>(
+        &'a self,
+        owner: &'a str,
+        repo: &'a str,
+        options: &'a FetchOptions,
+    ) -> impl Stream<Item = Result<FetchItem>> + 'a {
+        self.stream_files(owner, repo, None, options)
+    }
+
+    /// Like `stream_repo_files`, but from a `tree` already listed by `tree_for` with the
+    /// same options, so it isn't listed again
+    pub fn stream_tree_files<'a>(
+        &'a self,
+        owner: &'a str,
+        repo: &'a str,
+        tree: Vec<GitTreeEntry>,
+        options: &'a FetchOptions,
+    ) -> impl Stream<Item = Result<FetchItem>> + 'a {
+        self.stream_files(owner, repo, Some(tree), options)
+    }
+
+    fn stream_files<'a>(
+        &'a self,
+        owner: &'a str,
+        repo: &'a str,
+        tree: Option<Vec<GitTreeEntry>>,
+        options: &'a FetchOptions,
+    ) -> impl Stream<Item = Result<FetchItem>> + 'a {
+        try_stream! {
+            let tree = match tree {
+                Some(tree) => tree,
+                None => self.tree_for(owner, repo, options).await?,
+            };
+            tracing::debug!(entries = tree.len(), "fetched repository tree");
+            let ignores = self.root_ignores(owner, repo, &tree, options).await?;
+            let (mut eligible_files, generated) = split_generated(filter_entries(&tree, options, &ignores)?, options);
+            for entry in generated {
+                self.progress.on_file_skipped(&entry.path, &SkipReason::Generated);
+            }
+
+            if let Some(since) = options.since {
+                let mut changed = Vec::new();
+                for entry in eligible_files {
+                    let modified = self.last_modified(owner, repo, options.git_ref(), &entry.path).await?;
+                    if modified.is_some_and(|modified| modified >= since) {
+                        changed.push(entry);
+                    }
+                }
+                eligible_files = changed;
+            }
+
+            eligible_files.sort_by(|a, b| a.path.cmp(&b.path));
+            if let Some(max_files) = options.max_files.filter(|max_files| eligible_files.len() > *max_files) {
+                self.progress.on_file_limit_reached(eligible_files.len() - max_files);
+                eligible_files.truncate(max_files);
+            }
+            let mut total_files = eligible_files.len();
+            self.progress.on_start(total_files);
+            // What's left of `max_files` for the files of paths that turn out to be directories
+            let mut spare_files = options.max_files.map(|max_files| max_files - total_files);
+
+            let mut total_bytes = 0;
+            let mut pending: VecDeque<GitTreeEntry> = eligible_files.into_iter().cloned().collect();
+            while let Some(entry) = pending.pop_front() {
+                if options.is_cancelled() {
+                    break;
+                }
+                self.progress.on_file_start(&entry.path);
+                // Oversized files the tree gives a size for are skipped without a request;
+                // the rest are checked once fetched
+                if let Some(reason) = tree_size_exceeded(&entry, options) {
+                    self.progress.on_file_skipped(&entry.path, &reason);
+                    continue;
+                }
+
+                let started = Instant::now();
+                if entry.is_symlink() {
+                    let Some(target) = unless_cancelled(options, self.fetch_blob_text(owner, repo, &entry.sha)).await else {
+                        break;
+                    };
+                    self.progress.on_file_fetched(&entry.path, started.elapsed());
+                    match target {
+                        Ok(target) => {
+                            self.progress.on_file_done(&entry.path, 0);
+                            yield FetchItem::File(ExportedFile::symlink(entry.path.clone(), target));
+                        }
+                        Err(e) => {
+                            self.progress.on_file_skipped(&entry.path, &SkipReason::FetchFailed(e.to_string()));
+                            yield FetchItem::Failed(FailedFile { path: entry.path.clone(), error: e.to_string() });
+                        }
+                    }
+                } else {
+                    let Some(content) = unless_cancelled(options, self.fetch_file_content(owner, repo, &entry.path, options)).await else {
+                        break;
+                    };
+                    self.progress.on_file_fetched(&entry.path, started.elapsed());
+                    match content {
+                        Ok(content) => match content_skip_reason(&content, options) {
+                            Some(reason) => self.progress.on_file_skipped(&entry.path, &reason),
+                            None if options.content_pattern.as_ref().is_some_and(|pattern| !pattern.is_match(&content)) => {
+                                self.progress.on_file_skipped(&entry.path, &SkipReason::NoContentMatch);
+                                yield FetchItem::Unmatched(entry.path.clone());
+                            }
+                            None if options.max_total_bytes.is_some_and(|limit| total_bytes + content.len() > limit) => {
+                                self.progress.on_total_limit_reached(pending.len() + 1);
+                                break;
+                            }
+                            None => {
+                                total_bytes += content.len();
+                                self.progress.on_file_done(&entry.path, content.len());
+                                yield FetchItem::File(ExportedFile::new(entry.path.clone(), content));
+                            }
+                        },
+                        Err(e) if e.is::<IsDirectoryError>() => {
+                            // The path turned out to be a directory (e.g. the tree was out of
+                            // date), so fetch the files inside it in its place, filtered and
+                            // capped like the tree's
+                            let listed = self.list_directories(owner, repo, options.git_ref(), &entry.path).await?;
+                            let (mut files, generated) = split_generated(filter_entries(&listed, options, &ignores)?, options);
+                            for file in generated {
+                                self.progress.on_file_skipped(&file.path, &SkipReason::Generated);
+                            }
+                            files.sort_by(|a, b| a.path.cmp(&b.path));
+                            if let Some(spare) = spare_files.as_mut() {
+                                // The directory's own place under the cap goes to its files
+                                let allowed = *spare + 1;
+                                if files.len() > allowed {
+                                    self.progress.on_file_limit_reached(files.len() - allowed);
+                                    files.truncate(allowed);
+                                }
+                                *spare = allowed - files.len();
+                            }
+                            tracing::debug!(path = %entry.path, files = files.len(), "path is a directory; fetching its files");
+                            total_files += files.len();
+                            self.progress.on_start(total_files);
+                            for file in files.into_iter().rev() {
+                                pending.push_front(file.clone());
+                            }
+                        }
+                        Err(e) => match e.downcast_ref::<FileTooLargeError>() {
+                            Some(too_large) => {
+                                let bytes = entry.size.unwrap_or(too_large.limit);
+                                self.progress.on_file_skipped(&entry.path, &SkipReason::TooLarge { bytes, limit: too_large.limit });
+                            }
+                            None => {
+                                self.progress.on_file_skipped(&entry.path, &SkipReason::FetchFailed(e.to_string()));
+                                yield FetchItem::Failed(FailedFile { path: entry.path.clone(), error: e.to_string() });
+                            }
+                        },
+                    }
+                }
+
+                unless_cancelled(options, tokio::time::sleep(tokio::time::Duration::from_millis(100))).await;
+            }
+
+            if options.is_cancelled() {
+                tracing::debug!("fetch cancelled; ending the stream early");
+            }
+        }
+    }
+
+    /// Roughly how many API requests fetching the repository's files with `options` will
+    /// take: the tree listings, any root ignore files, and one per eligible file (up to
+    /// `max_files`) not already over `max_file_size` by its tree size. With `since`, each
+    /// file also needs a commit lookup, so the count is an upper bound.
+    ///
+    /// The tree is one listing, or with `subpath` one per directory on the way down plus
+    /// the subtree (see `fetch_subtree`). A tree too large to list at once is walked one
+    /// directory at a time, which this doesn't count.
+    ///
+    /// Costs the tree listings plus one request per root ignore file, which are needed to
+    /// know what's eligible.
+    pub async fn estimate_requests(&self, owner: &str, repo: &str, options: &FetchOptions) -> Result<usize> {
+        let tree = self.tree_for(owner, repo, options).await?;
+        let mut entries = split_generated(self.eligible_entries(owner, repo, &tree, options).await?, options).0;
+        entries.sort_by(|a, b| a.path.cmp(&b.path));
+        let eligible = entries.len();
+        let ignore_files = [
+            (options.skip.honor_export_ignore, ".gitattributes"),
+            (options.skip.honor_exporter_ignore, EXPORTER_IGNORE_FILE),
+        ]
+        .into_iter()
+        .filter(|(honored, name)| *honored && tree.iter().any(|entry| entry.kind == "blob" && entry.path == *name))
+        .count();
+        let lookups = if options.since.is_some() { eligible } else { 0 };
+        let fetches = entries
+            .iter()
+            .take(options.max_files.unwrap_or(usize::MAX))
+            .filter(|entry| tree_size_exceeded(entry, options).is_none())
+            .count();
+
+        let listings = options.subpath.as_ref().map_or(1, |subpath| subpath.split('/').count() + 1);
+
+        Ok(listings + ignore_files + lookups + fetches)
+    }
+
+    /// The eligible files' tree entries, sorted by path, without fetching any contents:
+    /// the path rules, ignore files, `max_file_size` (by the size the tree reports) and
+    /// `max_files` apply, but not `since` or the rules that need a file's content.
+    ///
+    /// Costs the tree request plus one per root ignore file, like `estimate_requests`.
+    pub async fn list_files(&self, owner: &str, repo: &str, options: &FetchOptions) -> Result<Vec<GitTreeEntry>> {
+        let tree = self.tree_for(owner, repo, options).await?;
+        self.list_tree_files(owner, repo, &tree, options).await
+    }
+
+    /// Like `list_files`, but from a `tree` already listed by `tree_for`. Costs one
+    /// request per root ignore file.
+    pub async fn list_tree_files(&self, owner: &str, repo: &str, tree: &[GitTreeEntry], options: &FetchOptions) -> Result<Vec<GitTreeEntry>> {
+        let mut eligible: Vec<GitTreeEntry> = split_generated(self.eligible_entries(owner, repo, tree, options).await?, options)
+            .0
+            .into_iter()
+            .filter(|entry| tree_size_exceeded(entry, options).is_none())
+            .cloned()
+            .collect();
+
+        eligible.sort_by(|a, b| a.path.cmp(&b.path));
+        if let Some(max_files) = options.max_files {
+            eligible.truncate(max_files);
+        }
+        Ok(eligible)
+    }
+
+    /// Looks up the repository's default branch, description and archived flag, and the
+    /// commit `git_ref` resolves to (`None` meaning the default branch).
+    ///
+    /// Fails with `RepositoryDisabledError` if GitHub has disabled the repository.
+    pub async fn fetch_provenance(&self, owner: &str, repo: &str, git_ref: Option<&str>) -> Result<Provenance> {
+        let repo_info: RepoInfo = self.get_json(&format!("{}/repos/{}/{}", self.base_url, owner, repo)).await?;
+        if repo_info.disabled {
+            return Err(RepositoryDisabledError { owner: owner.to_string(), repo: repo.to_string() }.into());
+        }
+        let commit: CommitInfo = self
+            .get_json(&format!(
+                "{}/repos/{}/{}/commits/{}",
+                self.base_url, owner, repo, encode_segment(git_ref.unwrap_or(&repo_info.default_branch))
+            ))
+            .await?;
+
+        let moved_to = is_moved(&repo_info, owner, repo).then(|| repo_info.full_name.clone());
+        Ok(Provenance {
+            git_ref: git_ref.map(String::from),
+            default_branch: repo_info.default_branch,
+            commit_sha: commit.sha,
+            commit_subject: commit.commit.message.lines().next().map(str::trim).filter(|subject| !subject.is_empty()).map(String::from),
+            description: repo_info.description.filter(|description| !description.trim().is_empty()),
+            archived: repo_info.archived,
+            license: repo_info.license,
+            moved_to,
+        })
+    }
+
+    /// Where `owner/repo` lives now if it has been renamed or transferred, or `None` if
+    /// it's still there. GitHub answers requests for the old name with a `301` to the
+    /// repository's id-based URL; that's followed and the new name read from what it
+    /// returns. Costs one request; `fetch_provenance` notices the same without it.
+    pub async fn moved_to(&self, owner: &str, repo: &str) -> Result<Option<(String, String)>> {
+        let repo_info: RepoInfo = self.get_json(&format!("{}/repos/{}/{}", self.base_url, owner, repo)).await?;
+        if !is_moved(&repo_info, owner, repo) {
+            return Ok(None);
+        }
+        Ok(repo_info
+            .full_name
+            .split_once('/')
+            .map(|(new_owner, new_repo)| (new_owner.to_string(), new_repo.to_string())))
+    }
+
+    /// The repository's license file at `git_ref` (the default branch if `None`), as
+    /// GitHub detected it, or `None` if it has none
+    pub async fn license_file(&self, owner: &str, repo: &str, git_ref: Option<&str>) -> Result<Option<ExportedFile>> {
+        let mut url = reqwest::Url::parse(&format!("{}/repos/{}/{}/license", self.base_url, owner, repo))?;
+        if let Some(git_ref) = git_ref {
+            url.query_pairs_mut().append_pair("ref", git_ref);
+        }
+        let Some(file) = self
+            .get_json_optional::<LicenseFile>(url.as_str())
+            .await
+            .context("Failed to fetch the license")?
+        else {
+            return Ok(None);
+        };
+        let decoded = general_purpose::STANDARD.decode(file.content.replace('\n', ""))?;
+        let text = String::from_utf8(decoded).map_err(|_| anyhow!("{} is not valid UTF-8", file.path))?;
+        Ok(Some(ExportedFile::new(file.path, strip_bom(&text))))
+    }
+
+    /// The repository's languages as GitHub names them, by bytes of code, most-used first
+    pub async fn repo_languages(&self, owner: &str, repo: &str) -> Result<Vec<String>> {
+        let cache_key = format!("{}/{}", owner, repo);
+        if let Some(cached) = self.languages_cache.lock().unwrap().get(&cache_key) {
+            return Ok(cached.clone());
+        }
+
+        let bytes: HashMap<String, u64> = self
+            .get_json(&format!("{}/repos/{}/{}/languages", self.base_url, owner, repo))
             .await?;
+        let mut languages: Vec<_> = bytes.into_iter().collect();
+        languages.sort_by(|(a_name, a_bytes), (b_name, b_bytes)| b_bytes.cmp(a_bytes).then(a_name.cmp(b_name)));
+        let languages: Vec<String> = languages.into_iter().map(|(name, _)| name).collect();
+
+        self.languages_cache.lock().unwrap().insert(cache_key, languages.clone());
+        Ok(languages)
+    }
 
+    /// Fetches a gist and its files. The Gists API returns content inline, so this is a
+    /// single request, plus one per file too large to be included (over 1 MB). Gists
+    /// are flat, so of the fetch filters only the skip rules and the include/exclude
+    /// patterns apply, matched against the file names.
+    #[tracing::instrument(skip(self, options))]
+    pub async fn fetch_gist(&self, id: &str, options: &FetchOptions) -> Result<Gist> {
+        let response: GistResponse = self.get_json(&format!("{}/gists/{}", self.base_url, id)).await?;
+
+        let entries: Vec<GitTreeEntry> = response.files
+            .keys()
+            .map(|name| GitTreeEntry {
+                path: name.clone(),
+                mode: "100644".to_string(),
+                kind: "blob".to_string(),
+                sha: String::new(),
+                size: None,
+            })
+            .collect();
+        let name_options = FetchOptions { subpath: None, only_paths: None, ..options.clone() };
+        let eligible: HashSet<&str> = filter_entries(&entries, &name_options, &[])?
+            .into_iter()
+            .map(|entry| entry.path.as_str())
+            .collect();
+
+        let mut files = Vec::new();
+        let mut failed = Vec::new();
+        // `files` is keyed by name, so it's already sorted
+        for (name, file) in response.files {
+            if !eligible.contains(name.as_str()) {
+                continue;
+            }
+            let content = match file.content {
+                Some(content) if !file.truncated => Ok(content),
+                _ => self.fetch_gist_raw(&file.raw_url).await,
+            };
+            match content {
+                Ok(content) => files.push(ExportedFile::new(name, normalized(&content, options))),
+                Err(e) => failed.push(FailedFile { path: name, error: e.to_string() }),
+            }
+        }
+
+        Ok(Gist {
+            id: response.id,
+            owner: response.owner.map_or_else(|| "anonymous".to_string(), |owner| owner.login),
+            files: RepoFiles { files, failed, ..RepoFiles::default() },
+        })
+    }
+
+    async fn fetch_gist_raw(&self, raw_url: &str) -> Result<String> {
+        let url = reqwest::Url::parse(raw_url)?;
+        let mut request = self.client.get(url.clone()).header(USER_AGENT, &self.user_agent);
+        // The URL comes from the API response, so the token only goes along to GitHub
+        if self.is_github_host(&url) {
+            request = request.header(AUTHORIZATION, self.authorization().await?);
+        }
+        let response = request.send().await?;
         if !response.status().is_success() {
-            let status = response.status();
-            return if let Ok(error) = response.json::<GitHubError>().await {
-                Err(anyhow!("GitHub API error ({}): {}", status, error.message))
-            } else {
-                Err(anyhow!("GitHub API returned status: {}", status))
+            return Err(anyhow!("GitHub returned status {} fetching the full gist file", response.status()));
+        }
+
+        String::from_utf8(response.bytes().await?.to_vec()).map_err(|_| anyhow!("File is not valid UTF-8"))
+    }
+
+    /// The tag of the repository's latest published release, or `None` if it has no releases
+    pub async fn latest_release_tag(&self, owner: &str, repo: &str) -> Result<Option<String>> {
+        let release: Option<Release> = self
+            .get_json_optional(&format!("{}/repos/{}/{}/releases/latest", self.base_url, owner, repo))
+            .await
+            .context("Failed to look up the latest release")?;
+        Ok(release.map(|release| release.tag_name))
+    }
+
+    /// The repository's default branch and every branch and tag, for picking a `git_ref`.
+    /// Costs one request for the repository plus one per 100 branches and per 100 tags.
+    pub async fn list_refs(&self, owner: &str, repo: &str) -> Result<RepoRefs> {
+        let repo_info: RepoInfo = self.get_json(&format!("{}/repos/{}/{}", self.base_url, owner, repo)).await?;
+        let branches: Vec<NamedRef> = self
+            .get_json_pages(&format!("{}/repos/{}/{}/branches?per_page=100", self.base_url, owner, repo))
+            .await?;
+        let tags: Vec<NamedRef> = self
+            .get_json_pages(&format!("{}/repos/{}/{}/tags?per_page=100", self.base_url, owner, repo))
+            .await?;
+
+        Ok(RepoRefs {
+            default_branch: repo_info.default_branch,
+            branches: branches.into_iter().map(|branch| branch.name).collect(),
+            tags: tags.into_iter().map(|tag| tag.name).collect(),
+        })
+    }
+
+    /// The branch and commit a pull request was opened from. The head commit can be read
+    /// through the base repository even when the branch lives in a fork.
+    pub async fn pull_request_head(&self, owner: &str, repo: &str, number: u64) -> Result<PullRequestHead> {
+        let pull_request: Option<PullRequest> = self
+            .get_json_optional(&format!("{}/repos/{}/{}/pulls/{}", self.base_url, owner, repo, number))
+            .await
+            .with_context(|| format!("Failed to look up pull request #{} in {}/{}", number, owner, repo))?;
+        pull_request.map(|pull_request| pull_request.head).ok_or_else(|| {
+            anyhow!("Pull request #{} not found in {}/{}, or your token can't access the repository", number, owner, repo)
+        })
+    }
+
+    /// The files changed between `base` and `head`: GitHub's three-dot comparison, so the
+    /// changes made on `head` since it diverged from `base`.
+    ///
+    /// Costs one request. GitHub lists at most 300 changed files, so when that many come
+    /// back, the trees at the merge base and at `head` are listed and compared by blob SHA
+    /// instead. That finds every change but reports renames as a removal and an addition.
+    pub async fn compare(&self, owner: &str, repo: &str, base: &str, head: &str) -> Result<Vec<ChangedFile>> {
+        // `per_page` pages through the commits, which aren't needed; the files all come
+        // with the first page
+        let url = format!(
+            "{}/repos/{}/{}/compare/{}...{}?per_page=1",
+            self.base_url, owner, repo, encode_segment(base), encode_segment(head)
+        );
+        let comparison: Comparison = self.get_json(&url).await?;
+        if comparison.files.len() < COMPARE_FILE_LIMIT {
+            return Ok(comparison.files);
+        }
+
+        let merge_base = comparison
+            .merge_base_commit
+            .ok_or_else(|| anyhow!("GitHub listed {} changed files but no merge base for {}...{}", COMPARE_FILE_LIMIT, base, head))?;
+        tracing::warn!(owner, repo, base, head, "too many changed files for the compare API; comparing the trees instead");
+        let base_tree = self.fetch_tree(owner, repo, Some(&merge_base.sha)).await?;
+        let head_tree = self.fetch_tree(owner, repo, Some(head)).await?;
+        Ok(diff_trees(&base_tree, &head_tree))
+    }
+
+    /// The SHA of the last commit on `git_ref` (`None` meaning the default branch) made at
+    /// or before `at`, by committer date. Fails if the branch has no commits that old.
+    pub async fn commit_at(&self, owner: &str, repo: &str, git_ref: Option<&str>, at: DateTime<Utc>) -> Result<String> {
+        let git_ref = git_ref.unwrap_or(DEFAULT_REF);
+        let until = at.to_rfc3339_opts(SecondsFormat::Secs, true);
+        let mut url = reqwest::Url::parse(&format!("{}/repos/{}/{}/commits", self.base_url, owner, repo))?;
+        url.query_pairs_mut()
+            .append_pair("sha", git_ref)
+            .append_pair("until", &until)
+            .append_pair("per_page", "1");
+        let commits: Vec<CommitInfo> = self
+            .get_json(url.as_str())
+            .await
+            .with_context(|| format!("Failed to list the commits on {} in {}/{}", git_ref, owner, repo))?;
+        commits.into_iter().next().map(|commit| commit.sha).ok_or_else(|| {
+            anyhow!("{}/{} has no commits on {} at or before {}", owner, repo, git_ref, until)
+        })
+    }
+
+    /// The repository's `export.toml` at `git_ref`, or `None` if it doesn't have one
+    pub async fn fetch_repo_config(&self, owner: &str, repo: &str, git_ref: Option<&str>) -> Result<Option<RepoConfig>> {
+        let mut url = reqwest::Url::parse(&format!("{}/repos/{}/{}/contents/{}", self.base_url, owner, repo, REPO_CONFIG_FILE))?;
+        if let Some(git_ref) = git_ref {
+            url.query_pairs_mut().append_pair("ref", git_ref);
+        }
+        let response = self
+            .get_json_optional::<ContentsResponse>(url.as_str())
+            .await
+            .with_context(|| format!("Failed to fetch {}", REPO_CONFIG_FILE))?;
+
+        let file = match response {
+            None => return Ok(None),
+            Some(ContentsResponse::File(file)) => file,
+            Some(ContentsResponse::Directory(_)) => return Err(IsDirectoryError { path: REPO_CONFIG_FILE.to_string() }.into()),
+        };
+        let decoded = general_purpose::STANDARD.decode(file.content.replace('\n', ""))?;
+        let text = String::from_utf8(decoded).map_err(|_| anyhow!("{} is not valid UTF-8", REPO_CONFIG_FILE))?;
+        RepoConfig::parse(&text).map(Some)
+    }
+
+    /// The text pages of the repository's wiki, by path within it, or `None` if it has no
+    /// wiki. The REST API doesn't serve wikis, so this shallow-clones `owner/repo.wiki.git`
+    /// with the `git` command-line tool, which must be installed; it costs no API requests.
+    /// Pages are decoded as `options` says, but no other fetch options apply.
+    pub async fn fetch_wiki(&self, owner: &str, repo: &str, options: &FetchOptions) -> Result<Option<Vec<ExportedFile>>> {
+        let url = format!("{}/{}/{}.wiki.git", self.git_base_url, owner, repo);
+        let dir = std::env::temp_dir().join(format!("repo_exporter_wiki_{}_{}_{}", std::process::id(), owner, repo));
+        std::fs::remove_dir_all(&dir).ok();
+
+        let pages = match clone_wiki(&url, &self.access_token().await?, &dir).await {
+            Ok(true) => read_pages(&dir).map(Some),
+            Ok(false) => Ok(None),
+            Err(e) => Err(e),
+        };
+        std::fs::remove_dir_all(&dir).ok();
+
+        Ok(pages?.map(|pages| {
+            pages
+                .into_iter()
+                .map(|page| ExportedFile { content: normalized(&page.content, options), ..page })
+                .collect()
+        }))
+    }
+
+    /// The token's remaining core API budget. Checking it doesn't count against the limit.
+    pub async fn rate_limit(&self) -> Result<RateLimit> {
+        let response: RateLimitResponse = self.get_json(&format!("{}/rate_limit", self.base_url)).await?;
+        Ok(response.rate)
+    }
+
+    /// When the file at `path` was last committed to, or `None` if it has no history
+    async fn last_modified(&self, owner: &str, repo: &str, git_ref: &str, path: &str) -> Result<Option<DateTime<Utc>>> {
+        let cache_key = format!("{}/{}/{}/{}", owner, repo, git_ref, path);
+        if let Some(cached) = self.last_modified_cache.lock().unwrap().get(&cache_key) {
+            return Ok(*cached);
+        }
+
+        let mut url = reqwest::Url::parse(&format!("{}/repos/{}/{}/commits", self.base_url, owner, repo))?;
+        url.query_pairs_mut()
+            .append_pair("path", path)
+            .append_pair("sha", git_ref)
+            .append_pair("per_page", "1");
+        let commits: Vec<CommitInfo> = self
+            .get_json(url.as_str())
+            .await
+            .with_context(|| format!("Failed to list the commits for {}", path))?;
+        let modified = commits
+            .first()
+            .and_then(|commit| commit.commit.committer.as_ref())
+            .map(|committer| committer.date);
+
+        self.last_modified_cache.lock().unwrap().insert(cache_key, modified);
+        Ok(modified)
+    }
+
+    /// Every entry in the repository's tree at `git_ref` (`None` meaning the default
+    /// branch), directories included, without fetching any file contents. Entries come
+    /// unfiltered; none of the `FetchOptions` rules are applied.
+    ///
+    /// Costs one request, unless the tree is too large for GitHub to list at once, in
+    /// which case it's listed one directory at a time.
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn fetch_tree(&self, owner: &str, repo: &str, git_ref: Option<&str>) -> Result<Vec<GitTreeEntry>> {
+        let git_ref = git_ref.unwrap_or(DEFAULT_REF);
+        let tree = self.get_tree(owner, repo, git_ref, true).await?;
+        if !tree.truncated {
+            return Ok(tree.tree);
+        }
+        tracing::warn!(owner, repo, "tree too large to list in one request; listing it one directory at a time");
+        self.list_directories(owner, repo, git_ref, "").await
+    }
+
+    /// Like `fetch_tree`, but only lists the directory `subpath` and what's under it,
+    /// plus the entries at the repository root (so root ignore files are still found).
+    /// Paths are relative to the repository root, as in `fetch_tree`.
+    ///
+    /// Descends to `subpath` one non-recursive tree listing per directory, then lists the
+    /// subtree by its SHA, so the rest of a large repository is never listed. A subtree
+    /// too large to list at once is walked one tree at a time.
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn fetch_subtree(&self, owner: &str, repo: &str, git_ref: Option<&str>, subpath: &str) -> Result<Vec<GitTreeEntry>> {
+        let git_ref = git_ref.unwrap_or(DEFAULT_REF);
+        let root = self.get_tree(owner, repo, git_ref, false).await?.tree;
+
+        let mut listing = root.clone();
+        let mut prefix = String::new();
+        for segment in subpath.split('/') {
+            let Some(directory) = listing.iter().find(|entry| entry.kind == "tree" && entry.path == segment) else {
+                return Err(anyhow!("{}/{} has no directory {} at {}", owner, repo, subpath, git_ref));
             };
+            let sha = directory.sha.clone();
+            prefix = if prefix.is_empty() { segment.to_string() } else { format!("{}/{}", prefix, segment) };
+            if prefix == subpath {
+                let mut tree = root;
+                tree.extend(self.list_subtree(owner, repo, &sha, &prefix).await?);
+                return Ok(tree);
+            }
+            listing = self.get_tree(owner, repo, &sha, false).await?.tree;
         }
+        Err(anyhow!("{}/{} has no directory {} at {}", owner, repo, subpath, git_ref))
+    }
 
-        let tree: GitTreeResponse = response.json().await?;
-        let mut files = Vec::new();
+    /// Every entry under the tree `sha`, with paths prefixed by `prefix` (the tree's own
+    /// path). One recursive listing, or a non-recursive one per directory if it's truncated.
+    async fn list_subtree(&self, owner: &str, repo: &str, sha: &str, prefix: &str) -> Result<Vec<GitTreeEntry>> {
+        let subtree = self.get_tree(owner, repo, sha, true).await?;
+        if !subtree.truncated {
+            return Ok(subtree
+                .tree
+                .into_iter()
+                .map(|entry| GitTreeEntry { path: format!("{}/{}", prefix, entry.path), ..entry })
+                .collect());
+        }
 
-        let eligible_files: Vec<_> = tree.tree.iter()
-            .filter(|entry| entry.kind == "blob" && !should_skip_path(&entry.path))
-            .collect();
+        tracing::warn!(%prefix, "subtree too large to list in one request; listing it one directory at a time");
+        let mut tree = Vec::new();
+        let mut pending = vec![(sha.to_string(), prefix.to_string())];
+        while let Some((sha, directory)) = pending.pop() {
+            for entry in self.get_tree(owner, repo, &sha, false).await?.tree {
+                let entry = GitTreeEntry { path: format!("{}/{}", directory, entry.path), ..entry };
+                if entry.kind == "tree" {
+                    pending.push((entry.sha.clone(), entry.path.clone()));
+                }
+                tree.push(entry);
+            }
+        }
+        Ok(tree)
+    }
+
+    /// One Git Trees API listing of `tree_ish` (a ref or tree SHA)
+    async fn get_tree(&self, owner: &str, repo: &str, tree_ish: &str, recursive: bool) -> Result<GitTreeResponse> {
+        let mut url = format!("{}/repos/{}/{}/git/trees/{}", self.base_url, owner, repo, tree_ish);
+        if recursive {
+            url.push_str("?recursive=1");
+        }
 
-        println!("Found {} files to process", eligible_files.len());
+        let response = self.send_get(&url).await?;
+        if response.status() == reqwest::StatusCode::CONFLICT {
+            return Err(EmptyRepositoryError {
+                owner: owner.to_string(),
+                repo: repo.to_string(),
+            }
+            .into());
+        }
+        if !response.status().is_success() {
+            return Err(api_error(response).await);
+        }
 
-        for (i, entry) in eligible_files.iter().enumerate() {
-            println!("📄 Processing file {}/{}: {}", i + 1, eligible_files.len(), entry.path);
+        Ok(response.json().await?)
+    }
+
+    /// The tree `options` asks for: the whole repository, or just `options.subpath`
+    pub async fn tree_for(&self, owner: &str, repo: &str, options: &FetchOptions) -> Result<Vec<GitTreeEntry>> {
+        match &options.subpath {
+            Some(subpath) => self.fetch_subtree(owner, repo, options.git_ref.as_deref(), subpath).await,
+            None => self.fetch_tree(owner, repo, options.git_ref.as_deref()).await,
+        }
+    }
 
-            if let Ok(content) = self.fetch_file_content(owner, repo, &entry.path).await {
-                files.push((entry.path.clone(), content));
+    /// Builds the tree under `root` (`""` for the whole repository) by walking the Contents
+    /// API one directory at a time, for trees too large for the recursive listing. Costs a
+    /// request per directory, plus one per extra page of a large directory. Submodules are
+    /// left out, as in the recursive listing.
+    async fn list_directories(&self, owner: &str, repo: &str, git_ref: &str, root: &str) -> Result<Vec<GitTreeEntry>> {
+        let mut tree = Vec::new();
+        let mut pending = vec![root.to_string()];
+        while let Some(directory) = pending.pop() {
+            let mut url = reqwest::Url::parse(&format!("{}/repos/{}/{}/contents/{}", self.base_url, owner, repo, encode_path(&directory)))?;
+            if git_ref != DEFAULT_REF {
+                url.query_pairs_mut().append_pair("ref", git_ref);
             }
 
-            tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
+            for entry in self.get_json_pages::<DirectoryEntry>(url.as_str()).await? {
+                let (kind, mode) = match entry.kind.as_str() {
+                    "dir" => {
+                        pending.push(entry.path.clone());
+                        ("tree", "040000")
+                    }
+                    "file" => ("blob", "100644"),
+                    "symlink" => ("blob", SYMLINK_MODE),
+                    _ => continue,
+                };
+                tree.push(GitTreeEntry {
+                    path: entry.path,
+                    mode: mode.to_string(),
+                    kind: kind.to_string(),
+                    sha: entry.sha,
+                    size: entry.size,
+                });
+            }
         }
 
-        Ok(files)
+        Ok(tree)
     }
 
-    async fn fetch_file_content(&self, owner: &str, repo: &str, path: &str) -> Result<String> {
-        let content_url = format!(
-            "https://api.github.com/repos/{}/{}/contents/{}",
-            owner, repo, path
+    /// Tree entries that pass the skip rules, include/exclude patterns and root ignore
+    /// files, and weren't fetched already. `since` is applied separately, as it needs a
+    /// request per entry.
+    async fn eligible_entries<'t>(
+        &self,
+        owner: &str,
+        repo: &str,
+        tree: &'t [GitTreeEntry],
+        options: &FetchOptions,
+    ) -> Result<Vec<&'t GitTreeEntry>> {
+        filter_entries(tree, options, &self.root_ignores(owner, repo, tree, options).await?)
+    }
+
+    /// The patterns from the root ignore files `options` honors, for `filter_entries`
+    async fn root_ignores(&self, owner: &str, repo: &str, tree: &[GitTreeEntry], options: &FetchOptions) -> Result<[PathPatterns; 2]> {
+        let export_ignore = if options.skip.honor_export_ignore {
+            self.fetch_root_patterns(owner, repo, tree, ".gitattributes", parse_export_ignore, options).await?
+        } else {
+            PathPatterns::empty()
+        };
+        let exporter_ignore = if options.skip.honor_exporter_ignore {
+            self.fetch_root_patterns(owner, repo, tree, EXPORTER_IGNORE_FILE, parse_ignore_file, options).await?
+        } else {
+            PathPatterns::empty()
+        };
+        Ok([export_ignore, exporter_ignore])
+    }
+
+    /// Loads exclusion patterns from a file at the repository root (e.g. `.gitattributes`).
+    /// A missing or unreadable file yields no patterns, and malformed ones are warned
+    /// about and skipped, since the repository's owner wrote them rather than the user.
+    async fn fetch_root_patterns(
+        &self,
+        owner: &str,
+        repo: &str,
+        tree: &[GitTreeEntry],
+        file_name: &str,
+        parse: fn(&str) -> Vec<String>,
+        options: &FetchOptions,
+    ) -> Result<PathPatterns> {
+        let exists = tree.iter()
+            .any(|entry| entry.kind == "blob" && entry.path == file_name);
+
+        if !exists {
+            return Ok(PathPatterns::empty());
+        }
+
+        match self.fetch_file_content(owner, repo, file_name, options).await {
+            Ok(content) => Ok(PathPatterns::lenient(&parse(&content), file_name)),
+            Err(_) => Ok(PathPatterns::empty()),
+        }
+    }
+
+    #[tracing::instrument(level = "debug", skip(self, options))]
+    async fn fetch_file_content(
+        &self,
+        owner: &str,
+        repo: &str,
+        path: &str,
+        options: &FetchOptions,
+    ) -> Result<String> {
+        let text = match self.fetch_contents_api(owner, repo, path, options.git_ref.as_deref()).await {
+            Ok(text) => text,
+            // Raw content is served up to the API limit, so only files over it aren't worth retrying
+            Err(e) if options.raw_fallback
+                && !e.is::<IsDirectoryError>()
+                && e.downcast_ref::<FileTooLargeError>().is_none_or(|e| e.limit < API_CONTENT_LIMIT) =>
+            {
+                tracing::warn!(%path, error = %e, "contents API failed, retrying via raw content");
+                self.fetch_raw_content(owner, repo, path, options.git_ref()).await?
+            }
+            Err(e) => return Err(e),
+        };
+
+        Ok(normalized(&text, options))
+    }
+
+    /// Fetches a file from `raw.githubusercontent.com`, which serves the bytes directly
+    /// rather than base64-wrapped JSON
+    async fn fetch_raw_content(&self, owner: &str, repo: &str, path: &str, git_ref: &str) -> Result<String> {
+        let raw_url = format!(
+            "{}/{}/{}/{}/{}",
+            self.raw_base_url, owner, repo, git_ref, encode_path(path)
         );
 
+        let request = self.client
+            .get(&raw_url)
+            .header(AUTHORIZATION, self.authorization().await?)
+            .header(USER_AGENT, &self.user_agent);
+        let response = self.send_with_retries(request).await?;
+
+        if !response.status().is_success() {
+            return Err(anyhow!("Raw content request returned status: {}", response.status()));
+        }
+
+        let bytes = response.bytes().await?;
+        String::from_utf8(bytes.to_vec()).map_err(|_| anyhow!("File is not valid UTF-8 text"))
+    }
+
+    /// Whether `url` is served by GitHub, and so may be sent the token: the API's own
+    /// origin, or an https `github.com` or `githubusercontent.com` host
+    fn is_github_host(&self, url: &reqwest::Url) -> bool {
+        let same_origin = reqwest::Url::parse(&self.base_url).is_ok_and(|base| base.origin() == url.origin());
+        let github = url.scheme() == "https"
+            && url.host_str().is_some_and(|host| {
+                host == "github.com" || host.ends_with(".github.com") || host.ends_with(".githubusercontent.com")
+            });
+        same_origin || github
+    }
+
+    /// The `Authorization` header value, minting a fresh installation token first when
+    /// authenticating as an app whose current token is missing or about to expire
+    async fn authorization(&self) -> Result<String> {
+        Ok(self.auth_scheme.header_value(&self.access_token().await?))
+    }
+
+    /// The token to authenticate with: the one given to `new`, or a current
+    /// installation token when authenticating as an app
+    async fn access_token(&self) -> Result<String> {
+        let Some(app) = &self.app else {
+            return Ok(self.token.clone());
+        };
+
+        let mut current = app.current.lock().await;
+        if current.as_ref().is_none_or(|token| token.needs_refresh(Utc::now())) {
+            *current = Some(self.mint_installation_token(&app.credentials).await?);
+        }
+        Ok(current.as_ref().map(|token| token.token.clone()).unwrap_or_default())
+    }
+
+    async fn mint_installation_token(&self, credentials: &AppCredentials) -> Result<InstallationToken> {
+        let url = format!("{}/app/installations/{}/access_tokens", self.base_url, credentials.installation_id);
         let response = self.client
-            .get(&content_url)
-            .bearer_auth(&self.token)
-            .header("User-Agent", "Rust-GitHubClient")
+            .post(&url)
+            .header(AUTHORIZATION, AuthScheme::Bearer.header_value(&credentials.jwt(Utc::now())?))
+            .header(USER_AGENT, &self.user_agent)
             .send()
             .await?;
 
-        // Handle rate limit and other error cases...
-        // (Move the existing logic here)
+        let status = response.status();
+        match status {
+            reqwest::StatusCode::UNAUTHORIZED => Err(anyhow!(
+                "GitHub rejected the app credentials (401); check GITHUB_APP_ID and the private key"
+            )),
+            reqwest::StatusCode::NOT_FOUND => Err(anyhow!(
+                "GitHub App installation {} not found (404); check GITHUB_APP_INSTALLATION_ID",
+                credentials.installation_id
+            )),
+            status if !status.is_success() => {
+                Err(anyhow!("GitHub API returned status {} minting an installation token", status))
+            }
+            _ => {
+                tracing::debug!("minted a GitHub App installation token");
+                Ok(response.json().await?)
+            }
+        }
+    }
 
-        if response.status().is_success() {
-            let file: FileContent = response.json().await?;
-            if file.encoding == "base64" {
-                match general_purpose::STANDARD.decode(file.content.replace('\n', "")) {
-                    Ok(decoded) => {
-                        if let Ok(text) = String::from_utf8(decoded) {
-                            return Ok(text);
-                        }
-                    }
-                    Err(_) => {}
-                }
+    /// Like `get_json` for a listing, following `Link: rel="next"` headers through every page
+    async fn get_json_pages<T: serde::de::DeserializeOwned>(&self, url: &str) -> Result<Vec<T>> {
+        let mut items = Vec::new();
+        let mut next = Some(url.to_string());
+        while let Some(url) = next {
+            let response = self.send_get(&url).await?;
+            if !response.status().is_success() {
+                return Err(api_error(response).await);
             }
+
+            next = response
+                .headers()
+                .get(reqwest::header::LINK)
+                .and_then(|link| link.to_str().ok())
+                .and_then(next_page_url);
+            items.extend(response.json::<Vec<T>>().await?);
         }
+        Ok(items)
+    }
 
-        Err(anyhow!("Failed to fetch file content"))
+    async fn get_json<T: serde::de::DeserializeOwned>(&self, url: &str) -> Result<T> {
+        let response = self.send_get(url).await?;
+        if !response.status().is_success() {
+            return Err(api_error(response).await);
+        }
+        Ok(response.json().await?)
+    }
+
+    /// Like `get_json`, but `None` when GitHub answers 404, for things a repository may
+    /// simply not have
+    async fn get_json_optional<T: serde::de::DeserializeOwned>(&self, url: &str) -> Result<Option<T>> {
+        let response = self.send_get(url).await?;
+        if response.status() == reqwest::StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+        if !response.status().is_success() {
+            return Err(api_error(response).await);
+        }
+        Ok(Some(response.json().await?))
+    }
+
+    /// Sends an authenticated GET request to `url`
+    async fn send_get(&self, url: &str) -> Result<reqwest::Response> {
+        Ok(self.client
+            .get(url)
+            .header(AUTHORIZATION, self.authorization().await?)
+            .header(USER_AGENT, &self.user_agent)
+            .send()
+            .await?)
     }
-}
\ No newline at end of file
+
+    /// Fetches a blob by SHA and decodes it as text. Used for symlinks, since the
+    /// contents API resolves links that point inside the repository.
+    async fn fetch_blob_text(&self, owner: &str, repo: &str, sha: &str) -> Result<String> {
+        let blob: GitBlob = self
+            .get_json(&format!("{}/repos/{}/{}/git/blobs/{}", self.base_url, owner, repo, sha))
+            .await?;
+        if blob.encoding != "base64" {
+            return Err(anyhow!("Unsupported blob encoding: {}", blob.encoding));
+        }
+
+        let decoded = general_purpose::STANDARD.decode(blob.content.replace('\n', ""))?;
+        String::from_utf8(decoded).map_err(|_| anyhow!("Blob is not valid UTF-8 text"))
+    }
+
+    /// Sends `request`, trying again up to `RetrySettings::max_attempts` times in all.
+    ///
+    /// When GitHub answers 403 or 429 with a `Retry-After` header (a secondary rate limit,
+    /// as opposed to running out of the hourly quota), waits that long, up to
+    /// `RetrySettings::max_delay` so a huge header can't stall the export. Failed
+    /// connections, timeouts and server errors back off exponentially instead. Anything
+    /// else, and the last attempt's outcome, is returned as is.
+    async fn send_with_retries(&self, request: reqwest::RequestBuilder) -> Result<reqwest::Response> {
+        let mut attempt = 1;
+        while attempt < self.retry.max_attempts {
+            let Some(retry) = request.try_clone() else {
+                break;
+            };
+            let wait = match retry.send().await {
+                Ok(response) => match retry_after(&response) {
+                    Some(asked) => {
+                        let wait = asked.min(self.retry.max_delay);
+                        if wait < asked {
+                            tracing::warn!(
+                                url = %response.url(),
+                                asked = asked.as_secs(),
+                                ?wait,
+                                "Retry-After is longer than the retry max delay; waiting only the max delay"
+                            );
+                        } else {
+                            tracing::warn!(url = %response.url(), seconds = wait.as_secs(), "secondary rate limit hit, waiting before retrying");
+                        }
+                        wait
+                    }
+                    None if response.status().is_server_error() => {
+                        let wait = self.retry.backoff(attempt);
+                        tracing::warn!(url = %response.url(), status = %response.status(), ?wait, "server error, retrying");
+                        wait
+                    }
+                    None => return Ok(response),
+                },
+                Err(e) if e.is_timeout() || e.is_connect() => {
+                    let wait = self.retry.backoff(attempt);
+                    tracing::warn!(error = %e, ?wait, "request failed, retrying");
+                    wait
+                }
+                Err(e) => return Err(e.into()),
+            };
+            tokio::time::sleep(wait).await;
+            attempt += 1;
+        }
+
+        Ok(request.send().await?)
+    }
+
+    /// `git_ref` is left off the request when `None`, so GitHub serves the default branch
+    async fn fetch_contents_api(&self, owner: &str, repo: &str, path: &str, git_ref: Option<&str>) -> Result<String> {
+        let content_url = format!(
+            "{}/repos/{}/{}/contents/{}",
+            self.base_url, owner, repo, encode_path(path)
+        );
+
+        let mut request = self.client.get(&content_url);
+        if let Some(git_ref) = git_ref {
+            request = request.query(&[("ref", git_ref)]);
+        }
+        let request = request
+            .header(AUTHORIZATION, self.authorization().await?)
+            .header(USER_AGENT, &self.user_agent);
+        let response = self.send_with_retries(request).await?;
+
+        if response.status() == reqwest::StatusCode::FORBIDDEN {
+            if let Ok(error) = response.json::<GitHubError>().await {
+                if error.is_too_large() {
+                    return Err(FileTooLargeError { path: path.to_string(), limit: API_CONTENT_LIMIT }.into());
+                }
+                return Err(anyhow!("Failed to fetch file content: {}", error));
+            }
+            return Err(anyhow!("Failed to fetch file content"));
+        }
+
+        if response.status().is_success() {
+            let file = match response.json::<ContentsResponse>().await? {
+                ContentsResponse::File(file) => file,
+                ContentsResponse::Directory(_) => return Err(IsDirectoryError { path: path.to_string() }.into()),
+            };
+            if file.encoding == "none" {
+                return Err(FileTooLargeError { path: path.to_string(), limit: INLINE_CONTENT_LIMIT }.into());
+            }
+            if file.encoding == "base64" {
+                if let Ok(decoded) = general_purpose::STANDARD.decode(file.content.replace('\n', "")) {
+                    if let Ok(text) = String::from_utf8(decoded) {
+                        return Ok(text);
+                    }
+                }
+            }
+        }
+
+        Err(anyhow!("Failed to fetch file content"))
+    }
+}
+
+/// Strips a BOM and, if enabled, converts CRLF line endings; `text` has already passed
+/// the UTF-8 check
+pub(crate) fn normalized(text: &str, options: &FetchOptions) -> String {
+    let text = strip_bom(text);
+    if options.normalize_line_endings {
+        normalize_line_endings(text)
+    } else {
+        text.to_string()
+    }
+}
+
+/// The blobs added, modified or removed going from `base` to `head`, as `ChangedFile`s
+/// sorted by path
+fn diff_trees(base: &[GitTreeEntry], head: &[GitTreeEntry]) -> Vec<ChangedFile> {
+    let blobs = |tree: &[GitTreeEntry]| -> HashMap<String, String> {
+        tree.iter().filter(|entry| entry.kind == "blob").map(|entry| (entry.path.clone(), entry.sha.clone())).collect()
+    };
+    let (base, head) = (blobs(base), blobs(head));
+    let changed = |filename: &str, status: &str| ChangedFile {
+        filename: filename.to_string(),
+        status: status.to_string(),
+        previous_filename: None,
+    };
+
+    let mut files: Vec<ChangedFile> = head
+        .iter()
+        .filter_map(|(path, sha)| match base.get(path) {
+            None => Some(changed(path, "added")),
+            Some(base_sha) if base_sha != sha => Some(changed(path, "modified")),
+            Some(_) => None,
+        })
+        .chain(base.keys().filter(|path| !head.contains_key(*path)).map(|path| changed(path, "removed")))
+        .collect();
+    files.sort_by(|a, b| a.filename.cmp(&b.filename));
+    files
+}
+
+/// The error for a failed API response, with GitHub's message when the body has one
+async fn api_error(response: reqwest::Response) -> anyhow::Error {
+    let status = response.status();
+    match response.json::<GitHubError>().await {
+        Ok(error) => anyhow!("GitHub API error ({}): {}", status, error),
+        Err(_) => anyhow!("GitHub API returned status: {}", status),
+    }
+}
+
+/// Whether the repository GitHub returned for `owner/repo` goes by another name now.
+/// Names are case-insensitive, so a request that only differs in case isn't a move.
+fn is_moved(repo_info: &RepoInfo, owner: &str, repo: &str) -> bool {
+    !repo_info.full_name.is_empty() && !repo_info.full_name.eq_ignore_ascii_case(&format!("{}/{}", owner, repo))
+}
+
+/// How long a 403 or 429 response asks to wait before retrying, in whole seconds
+/// as GitHub sends it
+fn retry_after(response: &reqwest::Response) -> Option<std::time::Duration> {
+    let status = response.status();
+    if status != reqwest::StatusCode::FORBIDDEN && status != reqwest::StatusCode::TOO_MANY_REQUESTS {
+        return None;
+    }
+    let seconds = response.headers().get(reqwest::header::RETRY_AFTER)?.to_str().ok()?.trim().parse().ok()?;
+    Some(std::time::Duration::from_secs(seconds))
+}
+
+/// Tree entries that pass the skip rules, path filters and `ignores` (patterns from the
+/// repository's own ignore files), and weren't fetched already. Every source of files
+/// (trees, walked directories, gists, Bitbucket) goes through this so they filter alike.
+pub(crate) fn filter_entries<'t>(
+    tree: &'t [GitTreeEntry],
+    options: &FetchOptions,
+    ignores: &[PathPatterns],
+) -> Result<Vec<&'t GitTreeEntry>> {
+    let includes = PathPatterns::new(&options.include)?;
+    let excludes = PathPatterns::new(&options.exclude)?;
+
+    Ok(tree.iter()
+        .filter(|entry| entry.kind == "blob" && !should_skip_path(&entry.path, &options.skip))
+        .filter(|entry| options.subpath.as_ref().is_none_or(|subpath| is_under(&entry.path, subpath)))
+        .filter(|entry| options.only_paths.as_ref().is_none_or(|only_paths| only_paths.contains(&entry.path)))
+        .filter(|entry| includes.is_empty() || includes.is_match(&entry.path))
+        .filter(|entry| options.max_depth.is_none_or(|max_depth| path_depth(&entry.path) <= max_depth))
+        .filter(|entry| !ignores.iter().any(|ignore| ignore.is_match(&entry.path)) && !excludes.is_match(&entry.path))
+        .filter(|entry| !options.already_fetched.contains(&entry.path))
+        .collect())
+}
+
+/// Separates the entries `is_generated_path` recognizes, unless they're to be exported
+pub(crate) fn split_generated<'t>(entries: Vec<&'t GitTreeEntry>, options: &FetchOptions) -> (Vec<&'t GitTreeEntry>, Vec<&'t GitTreeEntry>) {
+    if !options.skip.skip_generated {
+        return (entries, Vec::new());
+    }
+    let (generated, kept) = entries.into_iter().partition(|entry| is_generated_path(&entry.path));
+    (kept, generated)
+}
+
+/// Why fetched `content` isn't exported, if it breaks the byte or line caps or looks
+/// minified or binary
+pub(crate) fn content_skip_reason(content: &str, options: &FetchOptions) -> Option<SkipReason> {
+    size_limit_exceeded(content, options)
+        .or_else(|| minified(content, options))
+        .or_else(|| binary(content, options))
+}
+
+fn minified(content: &str, options: &FetchOptions) -> Option<SkipReason> {
+    if !options.skip.skip_generated {
+        return None;
+    }
+    looks_minified(content).map(|average_line_length| SkipReason::Minified { average_line_length })
+}
+
+fn binary(content: &str, options: &FetchOptions) -> Option<SkipReason> {
+    let ratio = looks_binary(content, options.binary_threshold?)?;
+    Some(SkipReason::Binary { control_percent: (ratio * 100.0).round() as usize })
+}
+
+/// The `rel="next"` URL in a `Link` header, e.g.
+/// `<https://api.github.com/...?page=2>; rel="next", <https://api.github.com/...?page=5>; rel="last"`
+fn next_page_url(link: &str) -> Option<String> {
+    link.split(',').find_map(|part| {
+        let (url, params) = part.split_once(';')?;
+        params
+            .split(';')
+            .any(|param| param.trim() == r#"rel="next""#)
+            .then(|| url.trim().trim_start_matches('<').trim_end_matches('>').to_string())
+    })
+}
+
+/// Runs `future` to completion, or drops it and returns `None` once `options.cancel` is
+/// cancelled
+pub(crate) async fn unless_cancelled<T>(options: &FetchOptions, future: impl Future<Output = T>) -> Option<T> {
+    match &options.cancel {
+        Some(cancel) => cancel.run_until_cancelled(future).await,
+        None => Some(future.await),
+    }
+}
+
+/// Checks the size the tree reports for an entry against the byte cap, so oversized files
+/// can be skipped before they're requested. Entries without a size pass.
+pub(crate) fn tree_size_exceeded(entry: &GitTreeEntry, options: &FetchOptions) -> Option<SkipReason> {
+    match (entry.size, options.max_file_size) {
+        (Some(bytes), Some(limit)) if bytes > limit => Some(SkipReason::TooLarge { bytes, limit }),
+        _ => None,
+    }
+}
+
+/// Checks decoded content against the byte and line caps
+fn size_limit_exceeded(content: &str, options: &FetchOptions) -> Option<SkipReason> {
+    if let Some(limit) = options.max_file_size {
+        if content.len() > limit {
+            return Some(SkipReason::TooLarge { bytes: content.len(), limit });
+        }
+    }
+
+    if let Some(limit) = options.max_lines {
+        // `lines()` splits on both LF and CRLF
+        let lines = content.lines().count();
+        if lines > limit {
+            return Some(SkipReason::TooManyLines { lines, limit });
+        }
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::SkipConfig;
+    use regex::Regex;
+    use tokio_util::sync::CancellationToken;
+    use wiremock::matchers::{header, method, path, query_param, query_param_is_missing};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    fn test_client(server: &MockServer) -> GitHubClient {
+        GitHubClient::new("token".to_string()).with_base_url(&server.uri())
+    }
+
+    /// Serves a recursive tree for `owner/repo` at `git_ref`, listing `(path, sha)` blobs
+    async fn mount_tree_at(server: &MockServer, git_ref: &str, blobs: &[(&str, &str)]) {
+        let entries: Vec<_> = blobs
+            .iter()
+            .map(|(p, sha)| serde_json::json!({ "path": p, "type": "blob", "sha": sha }))
+            .collect();
+
+        Mock::given(method("GET"))
+            .and(path(format!("/repos/owner/repo/git/trees/{}", git_ref)))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({ "tree": entries })))
+            .mount(server)
+            .await;
+    }
+
+    /// Serves a recursive tree for `owner/{repo}` listing `paths` as blobs
+    async fn mount_tree(server: &MockServer, repo: &str, paths: &[&str]) {
+        let entries: Vec<_> = paths
+            .iter()
+            .map(|p| serde_json::json!({ "path": p, "type": "blob" }))
+            .collect();
+
+        Mock::given(method("GET"))
+            .and(path(format!("/repos/owner/{}/git/trees/HEAD", repo)))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({ "tree": entries })))
+            .mount(server)
+            .await;
+    }
+
+    /// Serves `content` base64-encoded from the contents API, as GitHub does
+    async fn mount_file(server: &MockServer, repo: &str, file_path: &str, content: &[u8]) {
+        Mock::given(method("GET"))
+            .and(path(format!("/repos/owner/{}/contents/{}", repo, file_path)))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "content": general_purpose::STANDARD.encode(content),
+                "encoding": "base64"
+            })))
+            .mount(server)
+            .await;
+    }
+
+    fn paths(files: &[ExportedFile]) -> Vec<&str> {
+        files.iter().map(|f| f.path.as_str()).collect()
+    }
+
+    #[tokio::test]
+    async fn test_custom_user_agent() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/user"))
+            .and(header("user-agent", "acme-exporter/1.0"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({ "login": "octocat" })))
+            .mount(&server)
+            .await;
+
+        let client = test_client(&server).with_user_agent("acme-exporter/1.0");
+        assert_eq!(client.verify_token().await.unwrap().login, "octocat");
+
+        let client = test_client(&server).with_user_agent("  ");
+        assert_eq!(client.user_agent, DEFAULT_USER_AGENT);
+    }
+
+    #[tokio::test]
+    async fn test_builder_applies_settings() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/user"))
+            .and(header("authorization", "token secret"))
+            .and(header("user-agent", "acme-exporter/1.0"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({ "login": "octocat" })))
+            .mount(&server)
+            .await;
+
+        let client = GitHubClient::builder()
+            .token("secret")
+            .base_url(&server.uri())
+            .auth_scheme(AuthScheme::Token)
+            .user_agent("acme-exporter/1.0")
+            .retry(RetrySettings { max_attempts: 2, ..RetrySettings::default() })
+            .build()
+            .unwrap();
+        assert_eq!(client.verify_token().await.unwrap().login, "octocat");
+        assert_eq!(client.retry.max_attempts, 2);
+
+        assert!(GitHubClient::builder().base_url(&server.uri()).build().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_request_timeout() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/user"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_json(serde_json::json!({ "login": "octocat" }))
+                    .set_delay(std::time::Duration::from_secs(2)),
+            )
+            .mount(&server)
+            .await;
+
+        let settings = HttpSettings { timeout: std::time::Duration::from_millis(100), ..HttpSettings::default() };
+        let error = test_client(&server).with_http_settings(&settings).unwrap().verify_token().await.unwrap_err();
+        assert!(format!("{:#}", error).contains("timed out"), "{:#}", error);
+    }
+
+    #[tokio::test]
+    async fn test_pull_request_head() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/repos/owner/repo/pulls/7"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "number": 7,
+                "head": { "ref": "fix-typo", "sha": "abc123", "label": "contributor:fix-typo" }
+            })))
+            .mount(&server)
+            .await;
+
+        let client = test_client(&server);
+        let head = client.pull_request_head("owner", "repo", 7).await.unwrap();
+        assert_eq!(head, PullRequestHead {
+            branch: "fix-typo".to_string(),
+            sha: "abc123".to_string(),
+            label: "contributor:fix-typo".to_string(),
+        });
+
+        let error = client.pull_request_head("owner", "repo", 8).await.unwrap_err();
+        assert!(error.to_string().contains("Pull request #8 not found in owner/repo"));
+
+        Mock::given(method("GET"))
+            .and(path("/repos/owner/repo/pulls/9"))
+            .respond_with(ResponseTemplate::new(403).set_body_json(serde_json::json!({ "message": "API rate limit exceeded" })))
+            .mount(&server)
+            .await;
+        let error = client.pull_request_head("owner", "repo", 9).await.unwrap_err();
+        assert_eq!(error.to_string(), "Failed to look up pull request #9 in owner/repo");
+        assert!(format!("{:#}", error).contains("API rate limit exceeded"));
+    }
+
+    #[tokio::test]
+    async fn test_compare_lists_changed_files_in_one_request() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/repos/owner/repo/compare/release%2F1.0...v1.1"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "merge_base_commit": { "sha": "base" },
+                "files": [
+                    { "filename": "docs/new.md", "status": "renamed", "previous_filename": "docs/old.md" },
+                    { "filename": "gone.rs", "status": "removed" }
+                ]
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let files = test_client(&server).compare("owner", "repo", "release/1.0", "v1.1").await.unwrap();
+
+        assert_eq!(files.len(), 2);
+        assert_eq!(files[0].previous_filename.as_deref(), Some("docs/old.md"));
+        assert!(files[1].is_removed());
+    }
+
+    #[tokio::test]
+    async fn test_compare_diffs_trees_past_the_file_limit() {
+        let server = MockServer::start().await;
+        let listed: Vec<_> = (0..COMPARE_FILE_LIMIT)
+            .map(|i| serde_json::json!({ "filename": format!("src/{:03}.rs", i), "status": "modified" }))
+            .collect();
+        Mock::given(method("GET"))
+            .and(path("/repos/owner/repo/compare/v1.0...v1.1"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "merge_base_commit": { "sha": "base" },
+                "files": listed
+            })))
+            .mount(&server)
+            .await;
+        mount_tree_at(&server, "base", &[("kept.rs", "1"), ("changed.rs", "1"), ("gone.rs", "1")]).await;
+        mount_tree_at(&server, "v1.1", &[("kept.rs", "1"), ("changed.rs", "2"), ("new.rs", "1")]).await;
+
+        let files = test_client(&server).compare("owner", "repo", "v1.0", "v1.1").await.unwrap();
+
+        let changes: Vec<_> = files.iter().map(|file| (file.filename.as_str(), file.status.as_str())).collect();
+        assert_eq!(changes, vec![("changed.rs", "modified"), ("gone.rs", "removed"), ("new.rs", "added")]);
+    }
+
+    #[tokio::test]
+    async fn test_moved_to_follows_rename_redirect() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/repos/owner/old-name"))
+            .respond_with(ResponseTemplate::new(301)
+                .insert_header("location", format!("{}/repositories/42", server.uri()).as_str())
+                .set_body_json(serde_json::json!({
+                    "message": "Moved Permanently",
+                    "url": format!("{}/repositories/42", server.uri())
+                })))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/repositories/42"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "full_name": "new-owner/new-name",
+                "default_branch": "main"
+            })))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/repos/owner/repo"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "full_name": "owner/repo",
+                "default_branch": "main"
+            })))
+            .mount(&server)
+            .await;
+
+        let client = test_client(&server);
+        assert_eq!(
+            client.moved_to("owner", "old-name").await.unwrap(),
+            Some(("new-owner".to_string(), "new-name".to_string()))
+        );
+        assert_eq!(client.moved_to("owner", "repo").await.unwrap(), None);
+        let error = client.moved_to("owner", "missing").await.unwrap_err();
+        assert_eq!(error.to_string(), "GitHub API returned status: 404 Not Found");
+
+        // The provenance request follows the same redirect, so it notices the move too
+        Mock::given(method("GET"))
+            .and(path("/repos/owner/old-name/commits/main"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "sha": "0123abcd",
+                "commit": { "message": "Rename" }
+            })))
+            .mount(&server)
+            .await;
+        let provenance = client.fetch_provenance("owner", "old-name", None).await.unwrap();
+        assert_eq!(provenance.moved_to.as_deref(), Some("new-owner/new-name"));
+    }
+
+    #[tokio::test]
+    async fn test_latest_release_tag() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/repos/owner/released/releases/latest"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({ "tag_name": "v1.2.0" })))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/repos/owner/blocked/releases/latest"))
+            .respond_with(ResponseTemplate::new(403).set_body_json(serde_json::json!({
+                "message": "Resource protected by organization SAML enforcement."
+            })))
+            .mount(&server)
+            .await;
+
+        let client = test_client(&server);
+        assert_eq!(client.latest_release_tag("owner", "released").await.unwrap(), Some("v1.2.0".to_string()));
+        assert_eq!(client.latest_release_tag("owner", "unreleased").await.unwrap(), None);
+
+        let error = client.latest_release_tag("owner", "blocked").await.unwrap_err();
+        assert_eq!(error.to_string(), "Failed to look up the latest release");
+        assert!(format!("{:#}", error).contains("SAML enforcement"));
+    }
+
+    #[tokio::test]
+    async fn test_license_file() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/repos/owner/repo/license"))
+            .and(query_param("ref", "main"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "path": "LICENSE",
+                "content": general_purpose::STANDARD.encode("MIT License\n"),
+                "encoding": "base64",
+                "license": { "spdx_id": "MIT", "name": "MIT License" }
+            })))
+            .mount(&server)
+            .await;
+
+        let client = test_client(&server);
+        let file = client.license_file("owner", "repo", Some("main")).await.unwrap();
+        assert_eq!(file, Some(ExportedFile::new("LICENSE", "MIT License\n")));
+        assert_eq!(client.license_file("owner", "unlicensed", None).await.unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn test_fetch_provenance() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/repos/owner/repo"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "default_branch": "main",
+                "description": "Export GitHub repositories to Markdown",
+                "archived": true,
+                "license": { "key": "mit", "spdx_id": "MIT", "name": "MIT License" }
+            })))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/repos/owner/repo/commits/main"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "sha": "0123abcd",
+                "commit": { "message": "Initial commit\n\nSets up the exporter." }
+            })))
+            .mount(&server)
+            .await;
+
+        let provenance = test_client(&server).fetch_provenance("owner", "repo", None).await.unwrap();
+        assert_eq!(provenance, Provenance {
+            git_ref: None,
+            default_branch: "main".to_string(),
+            commit_sha: "0123abcd".to_string(),
+            commit_subject: Some("Initial commit".to_string()),
+            description: Some("Export GitHub repositories to Markdown".to_string()),
+            archived: true,
+            license: Some(License { spdx_id: Some("MIT".to_string()), name: "MIT License".to_string() }),
+            moved_to: None,
+        });
+
+        // A ref is a single path segment, however many `/`, `#` or `%` it has
+        Mock::given(method("GET"))
+            .and(path("/repos/owner/repo/commits/fix%2Fissue%2312"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "sha": "4567cdef",
+                "commit": { "message": "Fix #12" }
+            })))
+            .mount(&server)
+            .await;
+        let provenance = test_client(&server).fetch_provenance("owner", "repo", Some("fix/issue#12")).await.unwrap();
+        assert_eq!(provenance.commit_sha, "4567cdef");
+
+        Mock::given(method("GET"))
+            .and(path("/repos/owner/disabled"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "default_branch": "main",
+                "disabled": true
+            })))
+            .mount(&server)
+            .await;
+        let error = test_client(&server).fetch_provenance("owner", "disabled", None).await.unwrap_err();
+        assert!(error.is::<RepositoryDisabledError>());
+    }
+
+    #[tokio::test]
+    async fn test_repo_languages_sorted_and_cached() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/repos/owner/repo/languages"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "C": 1200, "C++": 48000, "CMake": 300
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = test_client(&server);
+        assert_eq!(client.repo_languages("owner", "repo").await.unwrap(), vec!["C++", "C", "CMake"]);
+        assert_eq!(client.repo_languages("owner", "repo").await.unwrap(), vec!["C++", "C", "CMake"]);
+    }
+
+    #[tokio::test]
+    async fn test_app_installation_token_minted_and_refreshed() {
+        let server = MockServer::start().await;
+        // Already inside the refresh margin, so every request mints a new token
+        Mock::given(method("POST"))
+            .and(path("/app/installations/42/access_tokens"))
+            .respond_with(ResponseTemplate::new(201).set_body_json(serde_json::json!({
+                "token": "ghs_installation",
+                "expires_at": (Utc::now() + chrono::Duration::minutes(1)).to_rfc3339()
+            })))
+            .expect(2)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/repos/owner/repo/languages"))
+            .and(header("authorization", "Bearer ghs_installation"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({ "Rust": 100 })))
+            .mount(&server)
+            .await;
+
+        let client = test_client(&server).with_app_credentials(super::super::app::tests::test_credentials());
+        assert!(client.verify_token().await.unwrap().login.contains("installation 42"));
+        assert_eq!(client.repo_languages("owner", "repo").await.unwrap(), vec!["Rust"]);
+    }
+
+    #[tokio::test]
+    async fn test_max_depth_composes_with_patterns() {
+        let server = MockServer::start().await;
+        mount_tree(&server, "repo", &["README.md", "a/b.rs", "a/b/c.rs", "a/b/c/d.rs", "a/b/skip.rs"]).await;
+        for file in ["README.md", "a/b.rs", "a/b/c.rs"] {
+            mount_file(&server, "repo", file, b"x").await;
+        }
+
+        let client = test_client(&server);
+        let fetch = |max_depth, include: &[&str]| FetchOptions {
+            max_depth: Some(max_depth),
+            include: include.iter().map(|p| p.to_string()).collect(),
+            exclude: vec!["skip.rs".to_string()],
+            ..FetchOptions::default()
+        };
+
+        let fetched = client.fetch_repo_files("owner", "repo", &fetch(2, &[])).await.unwrap();
+        assert_eq!(paths(&fetched.files), vec!["README.md", "a/b.rs", "a/b/c.rs"]);
+
+        let fetched = client.fetch_repo_files("owner", "repo", &fetch(0, &[])).await.unwrap();
+        assert_eq!(paths(&fetched.files), vec!["README.md"]);
+
+        let fetched = client.fetch_repo_files("owner", "repo", &fetch(1, &["*.rs"])).await.unwrap();
+        assert_eq!(paths(&fetched.files), vec!["a/b.rs"]);
+    }
+
+    #[tokio::test]
+    async fn test_files_too_large_for_contents_api_are_skipped_not_failed() {
+        let server = MockServer::start().await;
+        mount_tree(&server, "repo", &["README.md", "data/dump.sql", "data/huge.csv"]).await;
+        mount_file(&server, "repo", "README.md", b"# Repo").await;
+        Mock::given(method("GET"))
+            .and(path("/repos/owner/repo/contents/data/dump.sql"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "content": "",
+                "encoding": "none"
+            })))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/repos/owner/repo/contents/data/huge.csv"))
+            .respond_with(ResponseTemplate::new(403).set_body_json(serde_json::json!({
+                "message": "This API returns blobs up to 100 MB in size. The requested blob is too large to fetch via the API.",
+                "errors": [{ "resource": "Blob", "field": "data", "code": "too_large" }]
+            })))
+            .mount(&server)
+            .await;
+
+        let fetched = test_client(&server).fetch_repo_files("owner", "repo", &FetchOptions::default()).await.unwrap();
+        assert_eq!(paths(&fetched.files), vec!["README.md"]);
+        assert!(fetched.failed.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_other_unsupported_errors_still_fail_the_file() {
+        let server = MockServer::start().await;
+        mount_tree(&server, "repo", &["data/huge.csv"]).await;
+        Mock::given(method("GET"))
+            .and(path("/repos/owner/repo/contents/data/huge.csv"))
+            .respond_with(ResponseTemplate::new(403).set_body_json(serde_json::json!({
+                "message": "Unsupported media type for this endpoint"
+            })))
+            .mount(&server)
+            .await;
+
+        let fetched = test_client(&server).fetch_repo_files("owner", "repo", &FetchOptions::default()).await.unwrap();
+        assert!(fetched.files.is_empty());
+        assert_eq!(fetched.failed.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_paths_are_percent_encoded_in_contents_urls() {
+        let server = MockServer::start().await;
+        mount_tree(&server, "repo", &["docs/my file (draft).md", "notes/#1?.md"]).await;
+        mount_file(&server, "repo", "docs/my%20file%20%28draft%29.md", b"# Draft").await;
+        mount_file(&server, "repo", "notes/%231%3F.md", b"# One").await;
+
+        let fetched = test_client(&server).fetch_repo_files("owner", "repo", &FetchOptions::default()).await.unwrap();
+        assert_eq!(paths(&fetched.files), vec!["docs/my file (draft).md", "notes/#1?.md"]);
+        assert!(fetched.failed.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_path_that_is_a_directory_is_walked_not_failed() {
+        let server = MockServer::start().await;
+        mount_tree(&server, "repo", &["README.md", "docs"]).await;
+        mount_file(&server, "repo", "README.md", b"# Repo").await;
+        Mock::given(method("GET"))
+            .and(path("/repos/owner/repo/contents/docs"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!([
+                { "path": "docs/guide.md", "type": "file", "sha": "a1", "size": 7 },
+                { "path": "docs/api", "type": "dir", "sha": "b2" }
+            ])))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/repos/owner/repo/contents/docs/api"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!([
+                { "path": "docs/api/index.md", "type": "file", "sha": "c3", "size": 5 }
+            ])))
+            .mount(&server)
+            .await;
+        mount_file(&server, "repo", "docs/guide.md", b"# Guide").await;
+        mount_file(&server, "repo", "docs/api/index.md", b"# API").await;
+
+        let fetched = test_client(&server).fetch_repo_files("owner", "repo", &FetchOptions::default()).await.unwrap();
+        assert_eq!(paths(&fetched.files), vec!["README.md", "docs/api/index.md", "docs/guide.md"]);
+        assert!(fetched.failed.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_walked_directory_files_are_filtered_and_capped() {
+        #[derive(Default)]
+        struct Totals(std::sync::Mutex<Vec<usize>>);
+        impl ProgressReporter for Totals {
+            fn on_start(&self, total: usize) {
+                self.0.lock().unwrap().push(total);
+            }
+            fn on_file_start(&self, _path: &str) {}
+            fn on_file_done(&self, _path: &str, _bytes: usize) {}
+            fn on_file_skipped(&self, _path: &str, _reason: &SkipReason) {}
+        }
+
+        let server = MockServer::start().await;
+        mount_tree(&server, "repo", &["README.md", "docs"]).await;
+        mount_file(&server, "repo", "README.md", b"# Repo").await;
+        Mock::given(method("GET"))
+            .and(path("/repos/owner/repo/contents/docs"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!([
+                { "path": "docs/guide.md", "type": "file", "sha": "a1", "size": 7 },
+                { "path": "docs/notes.md", "type": "file", "sha": "b2", "size": 7 },
+                { "path": "docs/secret.env", "type": "file", "sha": "c3", "size": 9 },
+                { "path": "docs/package-lock.json", "type": "file", "sha": "d4", "size": 2 }
+            ])))
+            .mount(&server)
+            .await;
+        mount_file(&server, "repo", "docs/guide.md", b"# Guide").await;
+        mount_file(&server, "repo", "docs/notes.md", b"# Notes").await;
+        for skipped in ["docs/secret.env", "docs/package-lock.json"] {
+            Mock::given(method("GET"))
+                .and(path(format!("/repos/owner/repo/contents/{}", skipped)))
+                .respond_with(ResponseTemplate::new(200))
+                .expect(0)
+                .mount(&server)
+                .await;
+        }
+
+        let totals = Arc::new(Totals::default());
+        let client = test_client(&server).with_progress(totals.clone());
+        let options = FetchOptions { exclude: vec!["*.env".to_string()], ..FetchOptions::default() };
+        let fetched = client.fetch_repo_files("owner", "repo", &options).await.unwrap();
+        assert_eq!(paths(&fetched.files), vec!["README.md", "docs/guide.md", "docs/notes.md"]);
+        assert_eq!(*totals.0.lock().unwrap(), vec![2, 4]);
+
+        // The directory's place under the cap goes to its first file
+        let options = FetchOptions { max_files: Some(2), ..options };
+        let fetched = client.fetch_repo_files("owner", "repo", &options).await.unwrap();
+        assert_eq!(paths(&fetched.files), vec!["README.md", "docs/guide.md"]);
+    }
+
+    #[tokio::test]
+    async fn test_secondary_rate_limit_retried_after_waiting() {
+        let server = MockServer::start().await;
+        mount_tree(&server, "repo", &["README.md"]).await;
+        Mock::given(method("GET"))
+            .and(path("/repos/owner/repo/contents/README.md"))
+            .respond_with(ResponseTemplate::new(403).insert_header("retry-after", "0").set_body_json(serde_json::json!({
+                "message": "You have exceeded a secondary rate limit."
+            })))
+            .up_to_n_times(2)
+            .expect(2)
+            .mount(&server)
+            .await;
+        mount_file(&server, "repo", "README.md", b"# Repo").await;
+
+        let fetched = test_client(&server).fetch_repo_files("owner", "repo", &FetchOptions::default()).await.unwrap();
+        assert_eq!(paths(&fetched.files), vec!["README.md"]);
+        assert!(fetched.failed.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_retry_after_is_capped_by_the_max_delay() {
+        let server = MockServer::start().await;
+        mount_tree(&server, "repo", &["README.md"]).await;
+        Mock::given(method("GET"))
+            .and(path("/repos/owner/repo/contents/README.md"))
+            .respond_with(ResponseTemplate::new(429).insert_header("retry-after", "3600"))
+            .up_to_n_times(1)
+            .expect(1)
+            .mount(&server)
+            .await;
+        mount_file(&server, "repo", "README.md", b"# Repo").await;
+
+        let client = GitHubClient::builder()
+            .token("token")
+            .base_url(&server.uri())
+            .retry(RetrySettings { max_attempts: 2, base_delay: Duration::ZERO, max_delay: Duration::from_millis(10) })
+            .build()
+            .unwrap();
+        let options = FetchOptions::default();
+        let fetch = client.fetch_repo_files("owner", "repo", &options);
+        let fetched = tokio::time::timeout(Duration::from_secs(5), fetch).await.unwrap().unwrap();
+        assert_eq!(paths(&fetched.files), vec!["README.md"]);
+    }
+
+    #[tokio::test]
+    async fn test_server_errors_retried_with_backoff() {
+        let server = MockServer::start().await;
+        mount_tree(&server, "repo", &["README.md"]).await;
+        Mock::given(method("GET"))
+            .and(path("/repos/owner/repo/contents/README.md"))
+            .respond_with(ResponseTemplate::new(502))
+            .up_to_n_times(1)
+            .expect(1)
+            .mount(&server)
+            .await;
+        mount_file(&server, "repo", "README.md", b"# Repo").await;
+
+        let mut settings = HttpSettings::default();
+        settings.retry.base_delay = std::time::Duration::ZERO;
+        let fetched = test_client(&server).with_http_settings(&settings).unwrap().fetch_repo_files("owner", "repo", &FetchOptions::default()).await.unwrap();
+        assert_eq!(paths(&fetched.files), vec!["README.md"]);
+
+        // With a single attempt, the error is recorded rather than retried
+        mount_tree(&server, "flaky", &["README.md"]).await;
+        Mock::given(method("GET"))
+            .and(path("/repos/owner/flaky/contents/README.md"))
+            .respond_with(ResponseTemplate::new(502))
+            .expect(1)
+            .mount(&server)
+            .await;
+        settings.retry.max_attempts = 1;
+        let fetched = test_client(&server).with_http_settings(&settings).unwrap().fetch_repo_files("owner", "flaky", &FetchOptions::default()).await.unwrap();
+        assert_eq!(fetched.failed.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_generated_files_skipped_unless_included() {
+        let server = MockServer::start().await;
+        mount_tree(&server, "repo", &["Cargo.lock", "src/main.rs", "static/app.min.js", "static/bundle.js"]).await;
+        mount_file(&server, "repo", "Cargo.lock", b"# lockfile").await;
+        mount_file(&server, "repo", "src/main.rs", b"fn main() {}").await;
+        mount_file(&server, "repo", "static/app.min.js", b"var a=1;").await;
+        mount_file(&server, "repo", "static/bundle.js", "f(a,b);".repeat(300).as_bytes()).await;
+
+        let client = test_client(&server);
+        let fetched = client.fetch_repo_files("owner", "repo", &FetchOptions::default()).await.unwrap();
+        assert_eq!(paths(&fetched.files), vec!["src/main.rs"]);
+        assert!(fetched.failed.is_empty());
+
+        let options = FetchOptions {
+            skip: SkipConfig { skip_generated: false, ..SkipConfig::default() },
+            ..FetchOptions::default()
+        };
+        let fetched = client.fetch_repo_files("owner", "repo", &options).await.unwrap();
+        assert_eq!(fetched.files.len(), 4);
+        assert_eq!(client.estimate_requests("owner", "repo", &FetchOptions::default()).await.unwrap(), 1 + 2);
+    }
+
+    /// Signals that the request arrived, then never answers it in the test's lifetime
+    struct HangAfterNotifying(Arc<tokio::sync::Notify>);
+
+    impl wiremock::Respond for HangAfterNotifying {
+        fn respond(&self, _request: &wiremock::Request) -> ResponseTemplate {
+            self.0.notify_one();
+            ResponseTemplate::new(200).set_delay(Duration::from_secs(3600))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_cancel_aborts_in_flight_request() {
+        let server = MockServer::start().await;
+        mount_tree(&server, "repo", &["a.rs", "b.rs"]).await;
+        mount_file(&server, "repo", "a.rs", b"fn a() {}").await;
+        let requested = Arc::new(tokio::sync::Notify::new());
+        Mock::given(method("GET"))
+            .and(path("/repos/owner/repo/contents/b.rs"))
+            .respond_with(HangAfterNotifying(requested.clone()))
+            .mount(&server)
+            .await;
+
+        // Cancel once the request for b.rs has reached the server, and not before
+        let cancel = CancellationToken::new();
+        let options = FetchOptions { cancel: Some(cancel.clone()), ..FetchOptions::default() };
+        tokio::spawn(async move {
+            requested.notified().await;
+            cancel.cancel();
+        });
+
+        // The response never comes, so only the cancellation can end the fetch
+        let client = test_client(&server);
+        let fetch = client.fetch_repo_files("owner", "repo", &options);
+        let fetched = tokio::time::timeout(Duration::from_secs(30), fetch).await.unwrap().unwrap();
+        assert_eq!(paths(&fetched.files), vec!["a.rs"]);
+        assert!(fetched.failed.is_empty());
+        assert!(fetched.cancelled);
+    }
+
+    #[test]
+    fn test_next_page_url() {
+        let link = r#"<https://api.github.com/repositories/1/contents/?page=2>; rel="next", <https://api.github.com/repositories/1/contents/?page=3>; rel="last""#;
+        assert_eq!(next_page_url(link).as_deref(), Some("https://api.github.com/repositories/1/contents/?page=2"));
+        assert_eq!(next_page_url(r#"<https://api.github.com/x?page=1>; rel="prev""#), None);
+    }
+
+    #[tokio::test]
+    async fn test_truncated_tree_lists_directories_across_pages() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/repos/owner/repo/git/trees/HEAD"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "tree": [{ "path": "a.rs", "type": "blob" }],
+                "truncated": true
+            })))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/repos/owner/repo/contents/"))
+            .and(query_param_is_missing("page"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .insert_header("link", format!("<{}/repos/owner/repo/contents/?page=2>; rel=\"next\"", server.uri()).as_str())
+                    .set_body_json(serde_json::json!([
+                        { "path": "a.rs", "type": "file", "sha": "1" },
+                        { "path": "vendor", "type": "submodule", "sha": "2" }
+                    ])),
+            )
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/repos/owner/repo/contents/"))
+            .and(query_param("page", "2"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!([
+                { "path": "src", "type": "dir", "sha": "3" }
+            ])))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/repos/owner/repo/contents/src"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!([
+                { "path": "src/lib.rs", "type": "file", "sha": "4" }
+            ])))
+            .mount(&server)
+            .await;
+        mount_file(&server, "repo", "a.rs", b"fn a() {}").await;
+        mount_file(&server, "repo", "src/lib.rs", b"pub fn lib() {}").await;
+
+        let fetched = test_client(&server).fetch_repo_files("owner", "repo", &FetchOptions::default()).await.unwrap();
+        assert_eq!(paths(&fetched.files), vec!["a.rs", "src/lib.rs"]);
+        assert!(fetched.failed.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_fetch_wiki_clones_pages() {
+        let root = std::env::temp_dir().join(format!("repo_exporter_wiki_test_{}", std::process::id()));
+        let (work, wikis) = (root.join("work"), root.join("wikis").join("owner"));
+        std::fs::create_dir_all(work.join("guides")).unwrap();
+        std::fs::create_dir_all(&wikis).unwrap();
+        std::fs::write(work.join("Home.md"), "# Welcome\r\n").unwrap();
+        std::fs::write(work.join("guides").join("Setup.md"), "Run it.").unwrap();
+        let git = |dir: &std::path::Path, args: &[&str]| {
+            let status = std::process::Command::new("git")
+                .args(["-c", "user.name=test", "-c", "user.email=test@example.com"])
+                .args(args)
+                .current_dir(dir)
+                .output()
+                .unwrap()
+                .status;
+            assert!(status.success(), "git {:?}", args);
+        };
+        git(&work, &["init", "--quiet"]);
+        git(&work, &["add", "."]);
+        git(&work, &["commit", "--quiet", "-m", "pages"]);
+        git(&root, &["clone", "--quiet", "--bare", "work", "wikis/owner/repo.wiki.git"]);
+
+        let client = GitHubClient::new("token".to_string()).with_git_base_url(&format!("file://{}", root.join("wikis").display()));
+        let pages = client.fetch_wiki("owner", "repo", &FetchOptions::default()).await.unwrap().unwrap();
+        let missing = client.fetch_wiki("owner", "nowiki", &FetchOptions::default()).await.unwrap();
+        std::fs::remove_dir_all(&root).ok();
+
+        assert_eq!(pages, vec![ExportedFile::new("Home.md", "# Welcome\n"), ExportedFile::new("guides/Setup.md", "Run it.")]);
+        assert!(missing.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_fetch_tree_returns_raw_entries() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/repos/owner/repo/git/trees/v1.0"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "tree": [
+                    { "path": "node_modules", "type": "tree", "mode": "040000", "sha": "1" },
+                    { "path": "node_modules/x.js", "type": "blob", "mode": "100644", "sha": "2", "size": 12 }
+                ]
+            })))
+            .mount(&server)
+            .await;
+
+        let tree = test_client(&server).fetch_tree("owner", "repo", Some("v1.0")).await.unwrap();
+        let entries: Vec<_> = tree.iter().map(|entry| (entry.path.as_str(), entry.kind.as_str(), entry.size)).collect();
+        assert_eq!(entries, vec![("node_modules", "tree", None), ("node_modules/x.js", "blob", Some(12))]);
+    }
+
+    #[tokio::test]
+    async fn test_list_refs_follows_pages() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/repos/owner/repo"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({ "default_branch": "main" })))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/repos/owner/repo/branches"))
+            .and(query_param_is_missing("page"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .insert_header("link", format!("<{}/repos/owner/repo/branches?per_page=100&page=2>; rel=\"next\"", server.uri()).as_str())
+                    .set_body_json(serde_json::json!([{ "name": "dev" }, { "name": "main" }])),
+            )
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/repos/owner/repo/branches"))
+            .and(query_param("page", "2"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!([{ "name": "release/1.x" }])))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/repos/owner/repo/tags"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!([{ "name": "v1.1.0" }, { "name": "v1.0.0" }])))
+            .mount(&server)
+            .await;
+
+        let refs = test_client(&server).list_refs("owner", "repo").await.unwrap();
+        assert_eq!(refs.default_branch, "main");
+        assert_eq!(refs.branches, vec!["dev", "main", "release/1.x"]);
+        assert_eq!(refs.tags, vec!["v1.1.0", "v1.0.0"]);
+    }
+
+    #[tokio::test]
+    async fn test_subpath_lists_only_its_subtree() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/repos/owner/repo/git/trees/HEAD"))
+            .and(query_param_is_missing("recursive"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "tree": [
+                    { "path": "README.md", "type": "blob", "sha": "1" },
+                    { "path": "crates", "type": "tree", "sha": "2" },
+                    { "path": "docs", "type": "tree", "sha": "3" }
+                ]
+            })))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/repos/owner/repo/git/trees/2"))
+            .and(query_param_is_missing("recursive"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "tree": [
+                    { "path": "cli", "type": "tree", "sha": "4" },
+                    { "path": "core", "type": "tree", "sha": "5" }
+                ]
+            })))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/repos/owner/repo/git/trees/5"))
+            .and(query_param("recursive", "1"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "tree": [
+                    { "path": "src", "type": "tree", "sha": "6" },
+                    { "path": "src/lib.rs", "type": "blob", "sha": "7" }
+                ]
+            })))
+            .mount(&server)
+            .await;
+        mount_file(&server, "repo", "crates/core/src/lib.rs", b"pub fn core() {}").await;
+
+        let client = test_client(&server);
+        let options = FetchOptions {
+            subpath: Some("crates/core".to_string()),
+            ..FetchOptions::default()
+        };
+        let files = client.fetch_repo_files("owner", "repo", &options).await.unwrap().files;
+        assert_eq!(files, vec![ExportedFile::new("crates/core/src/lib.rs", "pub fn core() {}")]);
+        // The root, crates and the crates/core subtree, then the one file
+        assert_eq!(server.received_requests().await.unwrap().len(), 4);
+        assert_eq!(client.estimate_requests("owner", "repo", &options).await.unwrap(), 4);
+
+        let error = client.fetch_subtree("owner", "repo", None, "crates/web").await.unwrap_err();
+        assert!(error.to_string().contains("has no directory crates/web"));
+    }
+
+    #[tokio::test]
+    async fn test_content_pattern() {
+        let server = MockServer::start().await;
+        mount_tree(&server, "repo", &["a.rs", "b.rs", "c.rs"]).await;
+        mount_file(&server, "repo", "a.rs", b"// TODO: tidy up").await;
+        mount_file(&server, "repo", "b.rs", b"fn b() {}").await;
+        mount_file(&server, "repo", "c.rs", b"unsafe { todo!() }").await;
+
+        let options = FetchOptions {
+            content_pattern: Some(Regex::new("TODO|unsafe").unwrap()),
+            ..FetchOptions::default()
+        };
+        let fetched = test_client(&server).fetch_repo_files("owner", "repo", &options).await.unwrap();
+        assert_eq!(paths(&fetched.files), vec!["a.rs", "c.rs"]);
+        assert_eq!(fetched.unmatched, 1);
+        assert!(fetched.failed.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_estimate_requests() {
+        let server = MockServer::start().await;
+        mount_tree(&server, "repo", &[".exporterignore", "README.md", "fixtures/a.json", "src/lib.rs", "app.exe"]).await;
+        mount_file(&server, "repo", ".exporterignore", b"fixtures/\n").await;
+
+        let client = test_client(&server);
+        // Tree, .exporterignore, then .exporterignore, README.md and src/lib.rs
+        assert_eq!(client.estimate_requests("owner", "repo", &FetchOptions::default()).await.unwrap(), 5);
+
+        let options = FetchOptions {
+            include: vec!["src/**".to_string()],
+            ..FetchOptions::default()
+        };
+        assert_eq!(client.estimate_requests("owner", "repo", &options).await.unwrap(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_fetch_gist() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/gists/aa5a315d"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "id": "aa5a315d",
+                "owner": { "login": "tidynest" },
+                "files": {
+                    "notes.md": { "content": "# Notes\r\n", "truncated": false, "raw_url": "" },
+                    "big.rs": { "content": "fn partial", "truncated": true, "raw_url": format!("{}/raw/big.rs", server.uri()) }
+                }
+            })))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/raw/big.rs"))
+            .respond_with(ResponseTemplate::new(200).set_body_string("fn full() {}"))
+            .mount(&server)
+            .await;
+
+        let gist = test_client(&server).fetch_gist("aa5a315d", &FetchOptions::default()).await.unwrap();
+        assert_eq!(gist.owner, "tidynest");
+        assert_eq!(paths(&gist.files.files), vec!["big.rs", "notes.md"]);
+        assert_eq!(gist.files.files[0].content, "fn full() {}");
+        assert_eq!(gist.files.files[1].content, "# Notes\n");
+    }
+
+    #[tokio::test]
+    async fn test_gist_filters_names_and_keeps_the_token_on_github() {
+        let server = MockServer::start().await;
+        let elsewhere = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/gists/aa5a315d"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "id": "aa5a315d",
+                "owner": { "login": "tidynest" },
+                "files": {
+                    "notes.md": { "content": "# Notes\n", "truncated": false, "raw_url": "" },
+                    "big.rs": { "truncated": true, "raw_url": format!("{}/raw/big.rs", elsewhere.uri()) }
+                }
+            })))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/raw/big.rs"))
+            .respond_with(ResponseTemplate::new(200).set_body_string("fn full() {}"))
+            .mount(&elsewhere)
+            .await;
+
+        let options = FetchOptions { exclude: vec!["*.md".to_string()], ..FetchOptions::default() };
+        let gist = test_client(&server).fetch_gist("aa5a315d", &options).await.unwrap();
+        assert_eq!(paths(&gist.files.files), vec!["big.rs"]);
+
+        let requests = elsewhere.received_requests().await.unwrap();
+        assert_eq!(requests.len(), 1);
+        assert!(!requests[0].headers.contains_key("authorization"));
+    }
+
+    #[tokio::test]
+    async fn test_rate_limit() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/rate_limit"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "resources": { "core": { "limit": 5000, "remaining": 4820, "reset": 1700000720, "used": 180 } },
+                "rate": { "limit": 5000, "remaining": 4820, "reset": 1700000720, "used": 180 }
+            })))
+            .mount(&server)
+            .await;
+
+        let rate_limit = test_client(&server).rate_limit().await.unwrap();
+        let now = DateTime::from_timestamp(1700000000, 0).unwrap();
+        assert_eq!(rate_limit.summary(now), "4820/5000 remaining, resets in 12m");
+
+        let later = RateLimit { remaining: 4700, ..rate_limit };
+        assert_eq!(later.consumed_since(&rate_limit), Some(120));
+        let after_reset = RateLimit { reset: now + chrono::Duration::hours(1), ..later };
+        assert_eq!(after_reset.consumed_since(&rate_limit), None);
+    }
+
+    #[test]
+    fn test_size_limit_exceeded() {
+        let options = FetchOptions {
+            max_file_size: Some(10),
+            max_lines: Some(2),
+            ..FetchOptions::default()
+        };
+
+        assert_eq!(size_limit_exceeded("a\r\nb", &options), None);
+        assert_eq!(
+            size_limit_exceeded("a\r\nb\r\nc", &options),
+            Some(SkipReason::TooManyLines { lines: 3, limit: 2 })
+        );
+        assert_eq!(
+            size_limit_exceeded("0123456789A", &options),
+            Some(SkipReason::TooLarge { bytes: 11, limit: 10 })
+        );
+    }
+
+    #[tokio::test]
+    async fn test_fetch_repo_files_decodes_and_filters() {
+        let server = MockServer::start().await;
+        mount_tree(&server, "repo", &[
+            "README.md",
+            "src/main.rs",
+            "app.exe",
+            "target/debug/app",
+            "assets/logo.png",
+            "data/huge.csv",
+        ]).await;
+        mount_file(&server, "repo", "README.md", b"# Demo\n").await;
+        mount_file(&server, "repo", "src/main.rs", b"fn main() {}\n").await;
+        // Not valid UTF-8, so it can't be exported as text
+        mount_file(&server, "repo", "assets/logo.png", &[0x89, 0x50, 0x4e, 0x47, 0xff, 0xfe]).await;
+        mount_file(&server, "repo", "data/huge.csv", &[b'x'; 64]).await;
+
+        let options = FetchOptions {
+            max_file_size: Some(32),
+            ..FetchOptions::default()
+        };
+        let fetched = test_client(&server).fetch_repo_files("owner", "repo", &options).await.unwrap();
+
+        assert_eq!(fetched.files, vec![
+            ExportedFile::new("README.md", "# Demo\n"),
+            ExportedFile::new("src/main.rs", "fn main() {}\n"),
+        ]);
+        // Undecodable files are reported as failures; oversized ones are deliberate skips
+        let failed: Vec<_> = fetched.failed.iter().map(|f| f.path.as_str()).collect();
+        assert_eq!(failed, vec!["assets/logo.png"]);
+
+        // Skipped paths are never requested
+        let requested: Vec<_> = server
+            .received_requests()
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|request| request.url.path().to_string())
+            .collect();
+        assert!(!requested.iter().any(|p| p.ends_with("app.exe") || p.contains("target/")));
+    }
+
+    #[tokio::test]
+    async fn test_files_over_size_limit_by_tree_size_are_never_requested() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/repos/owner/repo/git/trees/HEAD"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({ "tree": [
+                { "path": "README.md", "type": "blob", "size": 7 },
+                { "path": "data/huge.csv", "type": "blob", "size": 4096 }
+            ] })))
+            .mount(&server)
+            .await;
+        mount_file(&server, "repo", "README.md", b"# Demo\n").await;
+        Mock::given(method("GET"))
+            .and(path("/repos/owner/repo/contents/data/huge.csv"))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(0)
+            .mount(&server)
+            .await;
+
+        let options = FetchOptions {
+            max_file_size: Some(32),
+            ..FetchOptions::default()
+        };
+        let fetched = test_client(&server).fetch_repo_files("owner", "repo", &options).await.unwrap();
+        assert_eq!(paths(&fetched.files), vec!["README.md"]);
+        assert!(fetched.failed.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_fetch_repo_files_empty_repository() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/repos/owner/empty/git/trees/HEAD"))
+            .respond_with(ResponseTemplate::new(409).set_body_json(serde_json::json!({
+                "message": "Git Repository is empty.",
+                "documentation_url": "https://docs.github.com/rest/git/trees#get-a-tree"
+            })))
+            .mount(&server)
+            .await;
+
+        let error = test_client(&server)
+            .fetch_repo_files("owner", "empty", &FetchOptions::default())
+            .await
+            .unwrap_err();
+
+        let empty = error.downcast_ref::<EmptyRepositoryError>().expect("empty repository error");
+        assert_eq!(empty.repo, "empty");
+        assert_eq!(error.to_string(), "repository owner/empty is empty, nothing to export");
+    }
+
+    #[tokio::test]
+    async fn test_api_error_includes_documentation_url() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/repos/owner/repo/git/trees/HEAD"))
+            .respond_with(ResponseTemplate::new(403).set_body_json(serde_json::json!({
+                "message": "Resource protected by organization SAML enforcement.",
+                "documentation_url": "https://docs.github.com/articles/authenticating-to-a-github-organization-with-saml-single-sign-on/"
+            })))
+            .mount(&server)
+            .await;
+
+        let error = test_client(&server).fetch_tree("owner", "repo", None).await.unwrap_err();
+
+        assert_eq!(
+            error.to_string(),
+            "GitHub API error (403 Forbidden): Resource protected by organization SAML enforcement. \
+             (see https://docs.github.com/articles/authenticating-to-a-github-organization-with-saml-single-sign-on/)"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_raw_fallback_when_contents_api_fails() {
+        let server = MockServer::start().await;
+        mount_tree(&server, "repo", &["src/main.rs"]).await;
+        Mock::given(method("GET"))
+            .and(path("/repos/owner/repo/contents/src/main.rs"))
+            .respond_with(ResponseTemplate::new(502))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/raw/owner/repo/HEAD/src/main.rs"))
+            .respond_with(ResponseTemplate::new(200).set_body_string("fn main() {}\n"))
+            .mount(&server)
+            .await;
+
+        // Without retries, so the 502 goes straight to the fallback
+        let settings = HttpSettings { retry: RetrySettings { max_attempts: 1, ..RetrySettings::default() }, ..HttpSettings::default() };
+        let client = test_client(&server)
+            .with_http_settings(&settings)
+            .unwrap()
+            .with_raw_base_url(&format!("{}/raw", server.uri()));
+
+        let files = client.fetch_repo_files("owner", "repo", &FetchOptions::default()).await.unwrap().files;
+        assert!(files.is_empty());
+
+        let options = FetchOptions {
+            raw_fallback: true,
+            ..FetchOptions::default()
+        };
+        let files = client.fetch_repo_files("owner", "repo", &options).await.unwrap().files;
+        assert_eq!(files, vec![ExportedFile::new("src/main.rs", "fn main() {}\n")]);
+    }
+
+    #[tokio::test]
+    async fn test_since_filters_by_last_commit_date() {
+        let server = MockServer::start().await;
+        mount_tree(&server, "repo", &["old.rs", "new.rs"]).await;
+        for (file, date) in [("old.rs", "2024-01-01T00:00:00Z"), ("new.rs", "2025-06-01T00:00:00Z")] {
+            Mock::given(method("GET"))
+                .and(path("/repos/owner/repo/commits"))
+                .and(query_param("path", file))
+                .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!([{
+                    "sha": "abc123",
+                    "commit": { "message": "change", "committer": { "date": date } }
+                }])))
+                .expect(1)
+                .mount(&server)
+                .await;
+        }
+        mount_file(&server, "repo", "new.rs", b"fn new() {}").await;
+
+        let client = test_client(&server);
+        let options = FetchOptions {
+            since: Some("2025-01-01T00:00:00Z".parse().unwrap()),
+            ..FetchOptions::default()
+        };
+
+        let files = client.fetch_repo_files("owner", "repo", &options).await.unwrap().files;
+        assert_eq!(paths(&files), vec!["new.rs"]);
+
+        // The second run is answered from the per-path cache (each commits mock expects one call)
+        let files = client.fetch_repo_files("owner", "repo", &options).await.unwrap().files;
+        assert_eq!(files.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_commit_at_resolves_last_commit_before() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/repos/owner/repo/commits"))
+            .and(query_param("sha", "main"))
+            .and(query_param("until", "2025-01-01T23:59:59Z"))
+            .and(query_param("per_page", "1"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!([{
+                "sha": "abc123",
+                "commit": { "message": "Last of the year", "committer": { "date": "2024-12-31T18:00:00Z" } }
+            }])))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/repos/owner/repo/commits"))
+            .and(query_param("sha", "HEAD"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!([])))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/repos/owner/repo/commits"))
+            .and(query_param("sha", "gone"))
+            .respond_with(ResponseTemplate::new(404).set_body_json(serde_json::json!({ "message": "No commit found for SHA: gone" })))
+            .mount(&server)
+            .await;
+
+        let client = test_client(&server);
+        let at = DateTime::parse_from_rfc3339("2025-01-01T23:59:59Z").unwrap().with_timezone(&Utc);
+        assert_eq!(client.commit_at("owner", "repo", Some("main"), at).await.unwrap(), "abc123");
+
+        let error = client.commit_at("owner", "repo", None, at).await.unwrap_err();
+        assert!(error.to_string().contains("no commits on HEAD at or before 2025-01-01T23:59:59Z"));
+
+        let error = client.commit_at("owner", "repo", Some("gone"), at).await.unwrap_err();
+        assert_eq!(error.to_string(), "Failed to list the commits on gone in owner/repo");
+        assert!(format!("{:#}", error).contains("No commit found for SHA: gone"));
+    }
+
+    #[tokio::test]
+    async fn test_exporterignore_and_excludes() {
+        let server = MockServer::start().await;
+        mount_tree(&server, "repo", &[".exporterignore", "docs/guide.md", "fixtures/big.json", "src/lib.rs"]).await;
+        mount_file(&server, "repo", ".exporterignore", b"# generated test data\nfixtures/\n").await;
+        mount_file(&server, "repo", "src/lib.rs", b"pub fn lib() {}").await;
+
+        let options = FetchOptions {
+            exclude: vec!["*.md".to_string(), ".exporterignore".to_string()],
+            ..FetchOptions::default()
+        };
+
+        let files = test_client(&server).fetch_repo_files("owner", "repo", &options).await.unwrap().files;
+        assert_eq!(paths(&files), vec!["src/lib.rs"]);
+    }
+
+    #[tokio::test]
+    async fn test_includes_narrow_before_excludes() {
+        let server = MockServer::start().await;
+        mount_tree(&server, "repo", &["Cargo.toml", "README.md", "src/lib.rs", "src/generated.rs"]).await;
+        mount_file(&server, "repo", "Cargo.toml", b"[package]").await;
+        mount_file(&server, "repo", "src/lib.rs", b"pub fn lib() {}").await;
+
+        let options = FetchOptions {
+            include: vec!["*.rs".to_string(), "Cargo.toml".to_string()],
+            exclude: vec!["src/generated.rs".to_string()],
+            ..FetchOptions::default()
+        };
+
+        let files = test_client(&server).fetch_repo_files("owner", "repo", &options).await.unwrap().files;
+        assert_eq!(paths(&files), vec!["Cargo.toml", "src/lib.rs"]);
+    }
+
+    #[tokio::test]
+    async fn test_content_normalization() {
+        let server = MockServer::start().await;
+        mount_tree(&server, "repo", &["win.txt"]).await;
+        mount_file(&server, "repo", "win.txt", "\u{feff}line one\r\nline two\r\n".as_bytes()).await;
+
+        let client = test_client(&server);
+
+        let files = client.fetch_repo_files("owner", "repo", &FetchOptions::default()).await.unwrap().files;
+        assert_eq!(files[0].content, "line one\nline two\n");
+
+        let options = FetchOptions {
+            normalize_line_endings: false,
+            ..FetchOptions::default()
+        };
+        let files = client.fetch_repo_files("owner", "repo", &options).await.unwrap().files;
+        assert_eq!(files[0].content, "line one\r\nline two\r\n");
+    }
+
+    #[tokio::test]
+    async fn test_binary_looking_text_is_skipped() {
+        let server = MockServer::start().await;
+        mount_tree(&server, "repo", &["data.idx", "notes.txt"]).await;
+        mount_file(&server, "repo", "data.idx", "IDX\0\0\0\x01\0\0\0\x02\0\0\0\x03".as_bytes()).await;
+        mount_file(&server, "repo", "notes.txt", b"plain text\n").await;
+
+        let client = test_client(&server);
+
+        let files = client.fetch_repo_files("owner", "repo", &FetchOptions::default()).await.unwrap().files;
+        assert_eq!(paths(&files), vec!["notes.txt"]);
+
+        let options = FetchOptions {
+            binary_threshold: None,
+            ..FetchOptions::default()
+        };
+        let files = client.fetch_repo_files("owner", "repo", &options).await.unwrap().files;
+        assert_eq!(paths(&files), vec!["data.idx", "notes.txt"]);
+    }
+
+    #[tokio::test]
+    async fn test_max_total_bytes_stops_in_path_order() {
+        let server = MockServer::start().await;
+        mount_tree(&server, "repo", &["c.txt", "a.txt", "b.txt"]).await;
+        mount_file(&server, "repo", "a.txt", b"aaaa").await;
+        mount_file(&server, "repo", "b.txt", b"bbbb").await;
+        mount_file(&server, "repo", "c.txt", b"cc").await;
+
+        let options = FetchOptions {
+            max_total_bytes: Some(7),
+            ..FetchOptions::default()
+        };
+
+        // b.txt would bring the total to 8, so it and everything after it is omitted,
+        // even though c.txt alone would still fit
+        let files = test_client(&server).fetch_repo_files("owner", "repo", &options).await.unwrap().files;
+        assert_eq!(paths(&files), vec!["a.txt"]);
+    }
+
+    #[tokio::test]
+    async fn test_max_files_keeps_the_first_in_path_order() {
+        let server = MockServer::start().await;
+        mount_tree(&server, "repo", &["c.txt", "a.txt", "b.txt"]).await;
+        mount_file(&server, "repo", "a.txt", b"a").await;
+        mount_file(&server, "repo", "b.txt", b"b").await;
+
+        let options = FetchOptions {
+            max_files: Some(2),
+            ..FetchOptions::default()
+        };
+        let client = test_client(&server);
+
+        let files = client.fetch_repo_files("owner", "repo", &options).await.unwrap().files;
+        assert_eq!(paths(&files), vec!["a.txt", "b.txt"]);
+        assert_eq!(client.estimate_requests("owner", "repo", &options).await.unwrap(), 3);
+        let requested = server.received_requests().await.unwrap();
+        assert!(!requested.iter().any(|request| request.url.path().ends_with("c.txt")));
+    }
+
+    #[tokio::test]
+    async fn test_symlink_entries_export_their_target() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/repos/owner/repo/git/trees/HEAD"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "tree": [
+                    { "path": "README.md", "mode": "100644", "type": "blob", "sha": "aaa" },
+                    { "path": "docs/README.md", "mode": "120000", "type": "blob", "sha": "bbb" }
+                ]
+            })))
+            .mount(&server)
+            .await;
+        mount_file(&server, "repo", "README.md", b"# Readme").await;
+        Mock::given(method("GET"))
+            .and(path("/repos/owner/repo/git/blobs/bbb"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "content": general_purpose::STANDARD.encode("../README.md"),
+                "encoding": "base64"
+            })))
+            .mount(&server)
+            .await;
+
+        let files = test_client(&server).fetch_repo_files("owner", "repo", &FetchOptions::default()).await.unwrap().files;
+        assert_eq!(files, vec![
+            ExportedFile::new("README.md", "# Readme"),
+            ExportedFile::symlink("docs/README.md", "../README.md"),
+        ]);
+    }
+}
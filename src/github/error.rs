@@ -0,0 +1,75 @@
+// ============= src/github/error.rs =============
+use std::fmt;
+
+/// Returned when the repository exists but has no commits yet (GitHub answers the
+/// tree request with `409 Git Repository is empty`).
+///
+/// Callers can downcast an `anyhow::Error` to this to treat it as "nothing to export"
+/// rather than a failure.
+#[derive(Debug)]
+pub struct EmptyRepositoryError {
+    pub owner: String,
+    pub repo: String,
+}
+
+impl fmt::Display for EmptyRepositoryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "repository {}/{} is empty, nothing to export", self.owner, self.repo)
+    }
+}
+
+impl std::error::Error for EmptyRepositoryError {}
+
+/// Returned for files GitHub won't serve through the contents API because of their size.
+/// Over 1 MB the content is left out of the response, and over 100 MB the request is
+/// refused outright (`403` with a `too_large` error).
+///
+/// `GitHubClient::stream_repo_files` reports these as `SkipReason::TooLarge` rather than
+/// as failures.
+#[derive(Debug)]
+pub struct FileTooLargeError {
+    pub path: String,
+    /// The size GitHub stopped at, in bytes
+    pub limit: usize,
+}
+
+impl fmt::Display for FileTooLargeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} is over the {} byte limit of the GitHub contents API", self.path, self.limit)
+    }
+}
+
+impl std::error::Error for FileTooLargeError {}
+
+/// Returned when GitHub has disabled the repository (e.g. for a terms-of-service
+/// violation or unpaid billing), so its contents can't be fetched
+#[derive(Debug)]
+pub struct RepositoryDisabledError {
+    pub owner: String,
+    pub repo: String,
+}
+
+impl fmt::Display for RepositoryDisabledError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "repository {}/{} has been disabled by GitHub, so it can't be exported", self.owner, self.repo)
+    }
+}
+
+impl std::error::Error for RepositoryDisabledError {}
+
+/// Returned when a path fetched as a file is a directory (the contents API answers with
+/// a listing instead of a file).
+///
+/// `GitHubClient::stream_repo_files` exports the files inside it instead.
+#[derive(Debug)]
+pub struct IsDirectoryError {
+    pub path: String,
+}
+
+impl fmt::Display for IsDirectoryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} is a directory, not a file", self.path)
+    }
+}
+
+impl std::error::Error for IsDirectoryError {}
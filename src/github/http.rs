@@ -0,0 +1,90 @@
+// ============= src/github/http.rs =============
+use anyhow::{Context, Result};
+use reqwest::{Client, Proxy};
+use std::time::Duration;
+
+/// Applies to every request unless overridden, so a hung connection can't block forever
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// How often file fetches are retried, and how long they wait in between
+#[derive(Debug, Clone, PartialEq)]
+pub struct RetrySettings {
+    /// Tries per request, including the first; `1` disables retries
+    pub max_attempts: u32,
+    /// Wait before the first retry of a failed connection or server error, doubling for
+    /// each retry after that
+    pub base_delay: Duration,
+    /// The most to wait between tries, including a `Retry-After` header from a secondary
+    /// rate limit; a longer header is cut down to this with a warning.
+    pub max_delay: Duration,
+}
+
+impl Default for RetrySettings {
+    fn default() -> Self {
+        Self {
+            max_attempts: 4,
+            base_delay: Duration::from_secs(1),
+            max_delay: Duration::from_secs(30),
+        }
+    }
+}
+
+impl RetrySettings {
+    /// How long to wait before trying again after `attempt` (1-based) failed
+    pub fn backoff(&self, attempt: u32) -> Duration {
+        let factor = 2u32.saturating_pow(attempt.saturating_sub(1));
+        self.base_delay.saturating_mul(factor).min(self.max_delay)
+    }
+}
+
+/// Timeouts, proxy and retries for the HTTP client behind `GitHubClient`
+#[derive(Debug, Clone)]
+pub struct HttpSettings {
+    /// Limit for each wait on the server: for the response to start, and then between
+    /// chunks of its body. A stalled transfer fails, but a large file that keeps arriving
+    /// is never cut off however long it takes in all.
+    pub timeout: Duration,
+    /// Limit for establishing a connection; `None` uses `timeout`
+    pub connect_timeout: Option<Duration>,
+    /// Send requests through this proxy. Without one, the standard `HTTPS_PROXY`,
+    /// `HTTP_PROXY` and `NO_PROXY` variables still apply.
+    pub proxy: Option<Proxy>,
+    /// How failed and rate-limited file fetches are retried
+    pub retry: RetrySettings,
+}
+
+impl Default for HttpSettings {
+    fn default() -> Self {
+        Self {
+            timeout: DEFAULT_TIMEOUT,
+            connect_timeout: None,
+            proxy: None,
+            retry: RetrySettings::default(),
+        }
+    }
+}
+
+impl HttpSettings {
+    pub(crate) fn client(&self) -> Result<Client> {
+        let mut builder = Client::builder()
+            .connect_timeout(self.connect_timeout.unwrap_or(self.timeout))
+            .read_timeout(self.timeout);
+        if let Some(proxy) = &self.proxy {
+            builder = builder.proxy(proxy.clone());
+        }
+        builder.build().context("Failed to initialize the HTTP client")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_backoff_doubles_up_to_the_cap() {
+        let retry = RetrySettings { max_attempts: 10, base_delay: Duration::from_millis(500), max_delay: Duration::from_secs(3) };
+        let waits: Vec<_> = (1..=5).map(|attempt| retry.backoff(attempt).as_millis()).collect();
+        assert_eq!(waits, vec![500, 1000, 2000, 3000, 3000]);
+        assert_eq!(retry.backoff(100), Duration::from_secs(3));
+    }
+}
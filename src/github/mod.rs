@@ -1,5 +1,18 @@
 // ============= src/github/mod.rs =============
+pub mod app;
+pub mod auth;
 pub mod client;
+pub mod error;
+pub mod http;
+pub mod options;
 pub mod types;
+pub mod wiki;
 
-pub use client::GitHubClient;
+pub use app::AppCredentials;
+pub use auth::AuthScheme;
+pub use client::{GitHubClient, GitHubClientBuilder};
+pub use error::{EmptyRepositoryError, FileTooLargeError, IsDirectoryError, RepositoryDisabledError};
+pub use http::{HttpSettings, RetrySettings};
+pub use options::FetchOptions;
+pub use types::{ChangedFile, ExportedFile, FailedFile, FetchItem, Gist, GitTreeEntry, License, Provenance, PullRequestHead, RateLimit, RepoFiles, RepoRefs};
+pub use wiki::WIKI_DIR;
@@ -0,0 +1,131 @@
+// ============= src/github/options.rs =============
+use crate::utils::SkipConfig;
+use chrono::{DateTime, Utc};
+use regex::Regex;
+use sha2::{Digest, Sha256};
+use std::collections::HashSet;
+use tokio_util::sync::CancellationToken;
+
+pub const DEFAULT_MAX_FILE_SIZE: usize = 1024 * 1024;
+/// Share of control characters beyond which decoded text is treated as binary
+pub const DEFAULT_BINARY_THRESHOLD: f64 = 0.1;
+/// What GitHub resolves to the default branch's tip
+pub const DEFAULT_REF: &str = "HEAD";
+/// `git_ref` value that `Exporter` resolves to the repository's latest release tag
+pub const LATEST_RELEASE_REF: &str = "latest";
+
+/// Settings that control which files `GitHubClient` fetches
+#[derive(Debug, Clone)]
+pub struct FetchOptions {
+    /// Branch, tag or commit SHA to export; `None` exports the default branch
+    pub git_ref: Option<String>,
+    /// Export `git_ref` as it was at this time: `Exporter` replaces the ref with the SHA
+    /// of its last commit at or before it. Costs one extra API request.
+    pub at: Option<DateTime<Utc>>,
+    pub skip: SkipConfig,
+    /// Gitignore-style patterns; when non-empty, only matching paths are exported
+    /// (the CLI's `--include` and `--lang`). Exclusions still apply on top.
+    pub include: Vec<String>,
+    /// Gitignore-style patterns for paths to leave out (the CLI's `--exclude`)
+    pub exclude: Vec<String>,
+    /// Only export files at most this many directories deep; `0` keeps just the
+    /// files at the repository root
+    pub max_depth: Option<usize>,
+    /// Only export this directory (e.g. `crates/core`, without slashes at either end).
+    /// Only its subtree and the root directory are listed, rather than the whole tree;
+    /// `max_depth` still counts from the repository root.
+    pub subpath: Option<String>,
+    /// Only consider these paths, e.g. the files changed between two refs; every
+    /// other filter still applies
+    pub only_paths: Option<HashSet<String>>,
+    /// Paths fetched by an earlier, interrupted run; they aren't requested again
+    pub already_fetched: HashSet<String>,
+    /// Retry files the contents API fails on via `raw.githubusercontent.com`
+    pub raw_fallback: bool,
+    /// Only fetch files whose last commit is at or after this time.
+    /// Costs one commits-API request per eligible file.
+    pub since: Option<DateTime<Utc>>,
+    /// Convert CRLF line endings to LF after decoding (a leading BOM is always stripped)
+    pub normalize_line_endings: bool,
+    /// Skip files larger than this many bytes once decoded (1 MB by default)
+    pub max_file_size: Option<usize>,
+    /// Skip files with more lines than this once decoded
+    pub max_lines: Option<usize>,
+    /// Skip decoded files where more than this share of the characters (10% by default)
+    /// are control characters, as binary data that happens to be valid UTF-8;
+    /// `None` keeps them
+    pub binary_threshold: Option<f64>,
+    /// Stop once the next file would push the export past this many bytes.
+    /// Files are fetched in path order, so the cut-off is deterministic.
+    pub max_total_bytes: Option<usize>,
+    /// Only fetch this many files, the first in path order; the rest are reported as
+    /// omitted. Applied after every filter, including `since`.
+    pub max_files: Option<usize>,
+    /// Only export files whose decoded content matches. Checked after each file is
+    /// downloaded, so it saves no API requests.
+    pub content_pattern: Option<Regex>,
+    /// Stops fetching when cancelled, aborting any request in flight. The files fetched
+    /// so far are kept and `RepoFiles::cancelled` is set, rather than an error returned.
+    pub cancel: Option<CancellationToken>,
+}
+
+impl FetchOptions {
+    /// The ref to request, falling back to `HEAD` (the default branch)
+    pub fn git_ref(&self) -> &str {
+        self.git_ref.as_deref().unwrap_or(DEFAULT_REF)
+    }
+
+    /// A short digest of the settings that decide which files an export holds: the skip
+    /// rules, path filters, size limits and the content pattern. Checkpoints record it so
+    /// a run is only resumed under the same ones. `since` is left out, since a relative
+    /// `--since 7d` resolves to a different time on every run.
+    pub fn filter_digest(&self) -> String {
+        let filters = format!(
+            "{:?}",
+            (
+                &self.skip,
+                &self.include,
+                &self.exclude,
+                self.max_depth,
+                &self.subpath,
+                self.normalize_line_endings,
+                self.max_file_size,
+                self.max_lines,
+                self.binary_threshold,
+                self.content_pattern.as_ref().map(Regex::as_str),
+            )
+        );
+        Sha256::digest(filters.as_bytes())[..8].iter().map(|byte| format!("{:02x}", byte)).collect()
+    }
+
+    /// Whether `cancel` has been cancelled
+    pub fn is_cancelled(&self) -> bool {
+        self.cancel.as_ref().is_some_and(CancellationToken::is_cancelled)
+    }
+}
+
+impl Default for FetchOptions {
+    fn default() -> Self {
+        Self {
+            git_ref: None,
+            at: None,
+            skip: SkipConfig::default(),
+            include: Vec::new(),
+            exclude: Vec::new(),
+            max_depth: None,
+            subpath: None,
+            only_paths: None,
+            already_fetched: HashSet::new(),
+            raw_fallback: false,
+            since: None,
+            normalize_line_endings: true,
+            max_file_size: Some(DEFAULT_MAX_FILE_SIZE),
+            max_lines: None,
+            binary_threshold: Some(DEFAULT_BINARY_THRESHOLD),
+            max_total_bytes: None,
+            max_files: None,
+            content_pattern: None,
+            cancel: None,
+        }
+    }
+}
@@ -1,16 +1,118 @@
 // ============= src/github/types.rs =============
-use serde::Deserialize;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fmt;
 
 #[derive(Debug, Deserialize)]
 pub struct GitTreeResponse {
     pub tree: Vec<GitTreeEntry>,
+    /// GitHub cut the recursive listing short (over 100,000 entries or 7 MB)
+    #[serde(default)]
+    pub truncated: bool,
 }
 
-#[derive(Debug, Deserialize)]
+/// Git file mode for symbolic links; the blob holds the link target
+pub const SYMLINK_MODE: &str = "120000";
+
+/// An entry in a repository's git tree: a file (`"blob"`) or directory (`"tree"`)
+#[derive(Debug, Clone, Deserialize)]
 pub struct GitTreeEntry {
     pub path: String,
+    #[serde(default)]
+    pub mode: String,
     #[serde(rename = "type")]
     pub kind: String, // "blob" or "tree"
+    #[serde(default)]
+    pub sha: String,
+    /// Blob size in bytes; GitHub leaves it out for trees
+    #[serde(default)]
+    pub size: Option<usize>,
+}
+
+impl GitTreeEntry {
+    pub fn is_symlink(&self) -> bool {
+        self.kind == "blob" && self.mode == SYMLINK_MODE
+    }
+}
+
+/// A fetched file, ready to be written by the export writers
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExportedFile {
+    pub path: String,
+    pub content: String,
+    /// Set for symbolic links, whose content isn't exported
+    pub symlink_target: Option<String>,
+}
+
+impl ExportedFile {
+    pub fn new(path: impl Into<String>, content: impl Into<String>) -> Self {
+        Self {
+            path: path.into(),
+            content: content.into(),
+            symlink_target: None,
+        }
+    }
+
+    pub fn symlink(path: impl Into<String>, target: impl Into<String>) -> Self {
+        Self {
+            path: path.into(),
+            content: String::new(),
+            symlink_target: Some(target.into()),
+        }
+    }
+}
+
+/// A file that couldn't be fetched or decoded, and so is missing from the export
+#[derive(Debug, Clone, PartialEq)]
+pub struct FailedFile {
+    pub path: String,
+    pub error: String,
+}
+
+/// One item from `GitHubClient::stream_repo_files`
+#[derive(Debug, Clone, PartialEq)]
+pub enum FetchItem {
+    File(ExportedFile),
+    Failed(FailedFile),
+    /// A file left out because its content doesn't match `FetchOptions::content_pattern`
+    Unmatched(String),
+}
+
+/// Everything `GitHubClient::fetch_repo_files` fetched, plus the files it couldn't
+#[derive(Debug, Default)]
+pub struct RepoFiles {
+    pub files: Vec<ExportedFile>,
+    pub failed: Vec<FailedFile>,
+    /// Files fetched but left out by `FetchOptions::content_pattern`
+    pub unmatched: usize,
+    /// Fetching stopped early because `FetchOptions::cancel` was cancelled
+    pub cancelled: bool,
+}
+
+/// One entry of a Contents API directory listing
+#[derive(Debug, Deserialize)]
+pub struct DirectoryEntry {
+    pub path: String,
+    /// "file", "dir", "symlink" or "submodule"
+    #[serde(rename = "type")]
+    pub kind: String,
+    pub sha: String,
+    #[serde(default)]
+    pub size: Option<usize>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct GitBlob {
+    pub content: String,
+    pub encoding: String,
+}
+
+/// What the contents API returns: a file, or the listing of a directory
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+pub enum ContentsResponse {
+    File(FileContent),
+    Directory(Vec<DirectoryEntry>),
 }
 
 #[derive(Debug, Deserialize)]
@@ -19,9 +121,247 @@ pub struct FileContent {
     pub encoding: String,
 }
 
+#[derive(Debug, Deserialize)]
+pub struct RepoInfo {
+    /// `owner/repo` as it's named now
+    #[serde(default)]
+    pub full_name: String,
+    pub default_branch: String,
+    pub description: Option<String>,
+    #[serde(default)]
+    pub archived: bool,
+    #[serde(default)]
+    pub disabled: bool,
+    /// `None` when GitHub detected no license
+    #[serde(default)]
+    pub license: Option<License>,
+}
+
+/// A repository's license as GitHub detected it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct License {
+    /// e.g. `MIT`; `NOASSERTION` when GitHub found a license it couldn't identify
+    pub spdx_id: Option<String>,
+    pub name: String,
+}
+
+impl License {
+    /// The SPDX id, unless GitHub couldn't identify the license
+    pub fn identified(&self) -> Option<&str> {
+        self.spdx_id.as_deref().filter(|id| *id != "NOASSERTION")
+    }
+}
+
+/// The `/repos/{owner}/{repo}/license` response: the license file, base64-encoded
+#[derive(Debug, Deserialize)]
+pub struct LicenseFile {
+    pub path: String,
+    pub content: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Release {
+    pub tag_name: String,
+}
+
+/// A branch or tag in a `/branches` or `/tags` listing
+#[derive(Debug, Deserialize)]
+pub struct NamedRef {
+    pub name: String,
+}
+
+/// A repository's branches and tags, as returned by `GitHubClient::list_refs`
+#[derive(Debug, Clone, PartialEq)]
+pub struct RepoRefs {
+    pub default_branch: String,
+    /// Branch and tag names in the order GitHub lists them
+    pub branches: Vec<String>,
+    pub tags: Vec<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Comparison {
+    /// At most 300 files, all on the first page
+    #[serde(default)]
+    pub files: Vec<ChangedFile>,
+    /// Where the head ref diverged from the base ref
+    pub merge_base_commit: Option<CommitRef>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CommitRef {
+    pub sha: String,
+}
+
+/// A file changed between two refs, as returned by `GitHubClient::compare`
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct ChangedFile {
+    /// The path at the head ref (or, for a removed file, where it was)
+    pub filename: String,
+    /// "added", "removed", "modified", "renamed", "copied", "changed" or "unchanged"
+    pub status: String,
+    /// Where a renamed file was at the base ref
+    #[serde(default)]
+    pub previous_filename: Option<String>,
+}
+
+impl ChangedFile {
+    pub fn is_removed(&self) -> bool {
+        self.status == "removed"
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PullRequest {
+    pub head: PullRequestHead,
+}
+
+/// Where a pull request was opened from, as returned by `GitHubClient::pull_request_head`
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct PullRequestHead {
+    /// The branch name, which may be in a fork
+    #[serde(rename = "ref")]
+    pub branch: String,
+    pub sha: String,
+    /// `owner:branch`
+    pub label: String,
+}
+
+/// Which branch and exact commit an export was taken from
+#[derive(Debug, Clone, PartialEq)]
+pub struct Provenance {
+    /// The branch, tag or commit requested, if not the default branch
+    pub git_ref: Option<String>,
+    pub default_branch: String,
+    pub commit_sha: String,
+    /// The first line of the commit's message; `None` if it's blank
+    pub commit_subject: Option<String>,
+    pub description: Option<String>,
+    /// The repository is read-only and no longer maintained
+    pub archived: bool,
+    /// `None` when GitHub detected no license
+    pub license: Option<License>,
+    /// The repository's `owner/name` now, if it was renamed or transferred since the
+    /// name requested (which GitHub still redirects)
+    pub moved_to: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CommitInfo {
+    pub sha: String,
+    pub commit: CommitDetails,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CommitDetails {
+    pub message: String,
+    pub committer: Option<CommitSignature>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CommitSignature {
+    pub date: DateTime<Utc>,
+}
+
 #[derive(Debug, Deserialize)]
 pub struct GitHubError {
     pub message: String,
-    #[allow(dead_code)]
+    /// Link to the docs for the failed endpoint or the policy that blocked the request
     pub documentation_url: Option<String>,
-}
\ No newline at end of file
+    #[serde(default)]
+    pub errors: Vec<GitHubErrorDetail>,
+}
+
+impl GitHubError {
+    /// Whether GitHub refused to serve a file because of its size
+    pub fn is_too_large(&self) -> bool {
+        self.errors.iter().any(|detail| detail.code == "too_large")
+            || self.message.contains("The requested blob is too large")
+    }
+}
+
+/// The message, followed by the documentation link when GitHub sent one
+impl fmt::Display for GitHubError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.message)?;
+        if let Some(url) = &self.documentation_url {
+            write!(f, " (see {})", url)?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct GitHubErrorDetail {
+    #[serde(default)]
+    pub code: String,
+}
+#[derive(Debug, Deserialize)]
+pub struct AuthenticatedUser {
+    pub login: String,
+}
+
+/// What the configured token is allowed to do, as reported by `GitHubClient::verify_token`
+#[derive(Debug)]
+pub struct TokenInfo {
+    pub login: String,
+    pub scopes: Vec<String>,
+    /// `None` when GitHub doesn't report scopes (fine-grained and app tokens)
+    pub private_repo_access: Option<bool>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RateLimitResponse {
+    pub rate: RateLimit,
+}
+
+/// The token's core API budget, as reported by `GitHubClient::rate_limit`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+pub struct RateLimit {
+    pub limit: u32,
+    pub remaining: u32,
+    /// When `remaining` goes back up to `limit`
+    #[serde(with = "chrono::serde::ts_seconds")]
+    pub reset: DateTime<Utc>,
+}
+
+impl RateLimit {
+    /// e.g. "4820/5000 remaining, resets in 12m"
+    pub fn summary(&self, now: DateTime<Utc>) -> String {
+        let minutes = ((self.reset - now).num_seconds().max(0) + 59) / 60;
+        format!("{}/{} remaining, resets in {}m", self.remaining, self.limit, minutes)
+    }
+
+    /// Requests spent between `earlier` and this reading, or `None` if the budget
+    /// was reset in between and the difference means nothing
+    pub fn consumed_since(&self, earlier: &RateLimit) -> Option<u32> {
+        (self.reset == earlier.reset).then(|| earlier.remaining.saturating_sub(self.remaining))
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct GistResponse {
+    pub id: String,
+    /// `None` for anonymous gists
+    pub owner: Option<AuthenticatedUser>,
+    /// Keyed by file name
+    pub files: std::collections::BTreeMap<String, GistFile>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct GistFile {
+    /// Omitted or cut short (see `truncated`) for files over 1 MB
+    pub content: Option<String>,
+    #[serde(default)]
+    pub truncated: bool,
+    pub raw_url: String,
+}
+
+/// A gist's files, as returned by `GitHubClient::fetch_gist`
+#[derive(Debug)]
+pub struct Gist {
+    pub id: String,
+    /// The owner's login, or `anonymous`
+    pub owner: String,
+    pub files: RepoFiles,
+}
@@ -0,0 +1,99 @@
+// ============= src/github/wiki.rs =============
+use super::types::ExportedFile;
+use anyhow::{anyhow, Result};
+use base64::{engine::general_purpose, Engine as _};
+use std::path::Path;
+use tokio::process::Command;
+
+/// Directory a repository's wiki pages are exported under, apart from its own files
+pub const WIKI_DIR: &str = "_wiki";
+
+/// Shallow-clones `url` into `dir` with the `git` command-line tool, authenticating with
+/// `token`. Returns false if the server says there's no such repository, which is how a
+/// repository without a wiki (or whose wiki has no pages yet) shows up. Any other failure,
+/// such as a rejected token or a network error, is an error carrying git's message.
+pub(super) async fn clone_wiki(url: &str, token: &str, dir: &Path) -> Result<bool> {
+    let credentials = general_purpose::STANDARD.encode(format!("x-access-token:{}", token));
+    // Passed through the environment rather than the URL or arguments, which other
+    // users on the machine can see
+    let output = Command::new("git")
+        .args(["clone", "--depth", "1", "--quiet", url])
+        .arg(dir)
+        .env("GIT_TERMINAL_PROMPT", "0")
+        .env("GIT_CONFIG_COUNT", "1")
+        .env("GIT_CONFIG_KEY_0", "http.extraHeader")
+        .env("GIT_CONFIG_VALUE_0", format!("Authorization: Basic {}", credentials))
+        .output()
+        .await
+        .map_err(|e| anyhow!("Couldn't run git to clone the wiki: {}", e))?;
+
+    if output.status.success() {
+        return Ok(true);
+    }
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    if is_missing_repository(&stderr) {
+        tracing::debug!(stderr = %stderr.trim(), "no wiki to clone");
+        return Ok(false);
+    }
+    Err(anyhow!("git couldn't clone the wiki: {}", stderr.trim()))
+}
+
+/// Whether git's error output says the remote repository doesn't exist: `remote:
+/// Repository not found.` or `fatal: repository '…' not found` over HTTP, or `'…' does
+/// not appear to be a git repository` for a local path
+fn is_missing_repository(stderr: &str) -> bool {
+    stderr.lines().any(|line| {
+        let line = line.to_lowercase();
+        line.contains("repository not found")
+            || (line.starts_with("fatal: repository '") && line.ends_with("' not found"))
+            || line.ends_with("does not appear to be a git repository")
+    })
+}
+
+/// Every UTF-8 file under `dir`, except git's own, by `/`-separated path relative to `dir`
+pub(super) fn read_pages(dir: &Path) -> Result<Vec<ExportedFile>> {
+    let mut pages = Vec::new();
+    let mut pending = vec![dir.to_path_buf()];
+    while let Some(current) = pending.pop() {
+        for entry in std::fs::read_dir(&current)? {
+            let entry = entry?;
+            let file_type = entry.file_type()?;
+            if file_type.is_dir() {
+                if entry.file_name() != ".git" {
+                    pending.push(entry.path());
+                }
+                continue;
+            }
+            if !file_type.is_file() {
+                continue;
+            }
+            let Ok(content) = String::from_utf8(std::fs::read(entry.path())?) else {
+                continue;
+            };
+            let relative: Vec<_> = entry
+                .path()
+                .strip_prefix(dir)?
+                .components()
+                .map(|component| component.as_os_str().to_string_lossy().into_owned())
+                .collect();
+            pages.push(ExportedFile::new(relative.join("/"), content));
+        }
+    }
+
+    pages.sort_by(|a, b| a.path.cmp(&b.path));
+    Ok(pages)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_only_a_missing_repository_means_no_wiki() {
+        assert!(is_missing_repository("remote: Repository not found.\nfatal: repository 'https://github.com/o/r.wiki.git/' not found\n"));
+        assert!(is_missing_repository("fatal: repository 'https://ghe.example.com/o/r.wiki.git/' not found"));
+        assert!(is_missing_repository("fatal: '/srv/o/r.wiki.git' does not appear to be a git repository\nfatal: Could not read from remote repository."));
+        assert!(!is_missing_repository("fatal: Authentication failed for 'https://github.com/o/r.wiki.git/'"));
+        assert!(!is_missing_repository("fatal: unable to access 'https://github.com/o/r.wiki.git/': Could not resolve host: github.com"));
+    }
+}
@@ -0,0 +1,212 @@
+// ============= src/ignore.rs =============
+use anyhow::{anyhow, Result};
+use globset::{Glob, GlobBuilder, GlobSet, GlobSetBuilder};
+
+/// Name of the repository-root file listing paths to leave out of exports
+pub const EXPORTER_IGNORE_FILE: &str = ".exporterignore";
+
+/// A compiled set of gitignore-style path patterns.
+///
+/// Follows the usual gitignore conventions: a pattern without a slash matches at any
+/// depth, a leading `/` anchors it to the repository root, and a pattern that names a
+/// directory also matches everything beneath it.
+#[derive(Debug, Clone)]
+pub struct PathPatterns {
+    set: GlobSet,
+    len: usize,
+}
+
+impl PathPatterns {
+    pub fn new<S: AsRef<str>>(patterns: &[S]) -> Result<Self> {
+        let mut builder = GlobSetBuilder::new();
+        let mut len = 0;
+
+        for pattern in patterns {
+            let pattern = pattern.as_ref().trim();
+            if pattern.is_empty() {
+                continue;
+            }
+
+            for glob in compile_pattern(pattern)? {
+                builder.add(glob);
+            }
+            len += 1;
+        }
+
+        let set = builder
+            .build()
+            .map_err(|e| anyhow!("Failed to compile path patterns: {}", e))?;
+
+        Ok(Self { set, len })
+    }
+
+    /// Like `new`, but for patterns read from a repository's own files: a pattern that
+    /// doesn't compile is logged and left out rather than failing the whole set.
+    /// `source` names the file in the warning.
+    pub fn lenient<S: AsRef<str>>(patterns: &[S], source: &str) -> Self {
+        Self::new(&valid_patterns(patterns, source)).unwrap_or_else(|e| {
+            tracing::warn!(file = source, error = %e, "ignoring path patterns");
+            Self::empty()
+        })
+    }
+
+    pub fn empty() -> Self {
+        Self {
+            set: GlobSet::empty(),
+            len: 0,
+        }
+    }
+
+    pub fn is_match(&self, path: &str) -> bool {
+        self.len > 0 && self.set.is_match(path)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Number of source patterns (not expanded globs) in the set
+    pub fn len(&self) -> usize {
+        self.len
+    }
+}
+
+/// The `patterns` that compile, trimmed, logging and dropping the rest. `source` names
+/// the file they came from in the warning.
+pub fn valid_patterns<S: AsRef<str>>(patterns: &[S], source: &str) -> Vec<String> {
+    patterns
+        .iter()
+        .map(|pattern| pattern.as_ref().trim())
+        .filter(|pattern| match compile_pattern(pattern) {
+            Ok(_) => true,
+            Err(e) => {
+                tracing::warn!(file = source, error = %e, "ignoring invalid path pattern");
+                false
+            }
+        })
+        .map(str::to_string)
+        .collect()
+}
+
+/// The globs for one gitignore-style pattern
+fn compile_pattern(pattern: &str) -> Result<Vec<Glob>> {
+    expand_pattern(pattern)
+        .iter()
+        .map(|glob| {
+            GlobBuilder::new(glob)
+                .literal_separator(true)
+                .build()
+                .map_err(|e| anyhow!("Invalid path pattern '{}': {}", pattern, e))
+        })
+        .collect()
+}
+
+/// Translates one gitignore-style pattern into the globs that implement it
+fn expand_pattern(pattern: &str) -> Vec<String> {
+    let dir_only = pattern.ends_with('/');
+    let trimmed = pattern.trim_end_matches('/');
+
+    let base = if let Some(anchored) = trimmed.strip_prefix('/') {
+        anchored.to_string()
+    } else if trimmed.contains('/') || trimmed.starts_with("**") {
+        trimmed.to_string()
+    } else {
+        format!("**/{}", trimmed)
+    };
+
+    if dir_only {
+        vec![format!("{}/**", base)]
+    } else {
+        vec![format!("{}/**", base), base]
+    }
+}
+
+/// Extracts the patterns marked `export-ignore` from a `.gitattributes` file.
+///
+/// Explicitly unset (`-export-ignore`) and commented lines are ignored.
+pub fn parse_export_ignore(gitattributes: &str) -> Vec<String> {
+    gitattributes
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .filter_map(|line| {
+            let mut fields = line.split_whitespace();
+            let pattern = fields.next()?;
+            fields
+                .any(|attr| attr == "export-ignore" || attr == "export-ignore=true")
+                .then(|| pattern.to_string())
+        })
+        .collect()
+}
+
+/// Reads patterns from a gitignore-style file such as `.exporterignore`.
+///
+/// Blank lines and `#` comments are dropped. Negated (`!`) patterns aren't supported,
+/// since nothing excluded by another rule can be re-included; they're ignored.
+pub fn parse_ignore_file(content: &str) -> Vec<String> {
+    content
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#') && !line.starts_with('!'))
+        .map(str::to_string)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_export_ignore() {
+        let attributes = "\
+# release tarball hygiene
+/tests export-ignore
+*.rs text eol=lf
+.github/ export-ignore
+docs/internal.md -export-ignore
+*.snap  linguist-generated export-ignore
+";
+        assert_eq!(parse_export_ignore(attributes), vec!["/tests", ".github/", "*.snap"]);
+    }
+
+    #[test]
+    fn test_parse_ignore_file() {
+        let content = "# exporter rules\n\nfixtures/\n*.snap\n!keep.snap\n";
+        assert_eq!(parse_ignore_file(content), vec!["fixtures/", "*.snap"]);
+    }
+
+    #[test]
+    fn test_unanchored_pattern_matches_at_any_depth() {
+        let patterns = PathPatterns::new(&["*.snap"]).unwrap();
+        assert!(patterns.is_match("a.snap"));
+        assert!(patterns.is_match("src/snapshots/a.snap"));
+        assert!(!patterns.is_match("src/a.rs"));
+    }
+
+    #[test]
+    fn test_anchored_and_directory_patterns() {
+        let patterns = PathPatterns::new(&["/tests", ".github/"]).unwrap();
+        assert!(patterns.is_match("tests/integration.rs"));
+        assert!(!patterns.is_match("src/tests/unit.rs"));
+        assert!(patterns.is_match(".github/workflows/ci.yml"));
+        assert!(patterns.is_match("crates/foo/.github/CODEOWNERS"));
+    }
+
+    #[test]
+    fn test_lenient_keeps_the_valid_patterns() {
+        assert!(PathPatterns::new(&["*.snap", "src/[oops"]).is_err());
+
+        let patterns = PathPatterns::lenient(&["*.snap", "src/[oops", "/docs"], ".gitattributes");
+        assert_eq!(patterns.len(), 2);
+        assert!(patterns.is_match("a.snap"));
+        assert!(patterns.is_match("docs/guide.md"));
+        assert!(!patterns.is_match("src/main.rs"));
+    }
+
+    #[test]
+    fn test_empty_patterns_match_nothing() {
+        let patterns = PathPatterns::new::<&str>(&[]).unwrap();
+        assert!(patterns.is_empty());
+        assert!(!patterns.is_match("anything"));
+    }
+}
@@ -1,3 +1,4 @@
 // ============= src/input/mod.rs =============
 pub mod parser;
+pub mod since;
 
@@ -1,18 +1,132 @@
 // ============= src/input/parser.rs =============
-use anyhow::{anyhow, Result};
+use anyhow::{anyhow, Context, Result};
 use std::io::{self, Write};
 
+/// What an input names: a repository, a pull request, a gist, or a Bitbucket repository
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ExportTarget {
+    Repository { owner: String, repo: String },
+    PullRequest { owner: String, repo: String, number: u64 },
+    Gist { id: String },
+    Bitbucket { workspace: String, repo: String },
+}
+
 /// Parses repository input which can be either a GitHub URL or owner/repo format.
 ///
 /// Supports multiple input formats:
 /// - Full URL: https://github.com/tidynest/security_toolkit
 /// - Short URL: github.com/tidynest/security_toolkit
 /// - Owner/repo: tidynest/security_toolkit
+/// - Pull request URL: https://github.com/tidynest/security_toolkit/pull/123
+/// - Gist URL: https://gist.github.com/tidynest/aa5a315d61ae9438b18d (the owner is optional)
+/// - Bitbucket URL: https://bitbucket.org/workspace/repo
 /// - Interactive: tidynest (will prompt for repo name)
-#[allow(dead_code)]
-pub fn parse_repo_input(input: &str) -> Result<(String, String)> {
+pub fn parse_repo_input(input: &str) -> Result<ExportTarget> {
     let input = input.trim();
 
+    if let Some(id) = parse_gist_url(input)? {
+        return Ok(ExportTarget::Gist { id });
+    }
+    if let Some((owner, repo, number)) = parse_pull_request_url(input)? {
+        return Ok(ExportTarget::PullRequest { owner, repo, number });
+    }
+    if let Some((workspace, repo)) = parse_bitbucket_url(input)? {
+        return Ok(ExportTarget::Bitbucket { workspace, repo });
+    }
+
+    let (owner, repo) = parse_repository(input)?;
+    Ok(ExportTarget::Repository { owner, repo })
+}
+
+/// Parses a list of inputs, one per line, as read from `--repos-file` or `--stdin`.
+/// Blank lines and lines starting with `#` are ignored; a line that doesn't parse is an
+/// error naming its line number. So is a bare owner: there's no one to ask for the
+/// repository name, and with `--stdin` the list has already used up stdin.
+pub fn parse_repo_list(text: &str) -> Result<Vec<ExportTarget>> {
+    text.lines()
+        .enumerate()
+        .map(|(index, line)| (index + 1, line.trim()))
+        .filter(|(_, line)| !line.is_empty() && !line.starts_with('#'))
+        .map(|(number, line)| parse_list_entry(line).with_context(|| format!("line {}: {}", number, line)))
+        .collect()
+}
+
+fn parse_list_entry(line: &str) -> Result<ExportTarget> {
+    if !line.contains('/') {
+        return Err(anyhow!("Expected owner/repo or a URL, not just an owner"));
+    }
+    parse_repo_input(line)
+}
+
+/// The gist id from a `gist.github.com` URL, or `None` for anything else
+pub fn parse_gist_url(url: &str) -> Result<Option<String>> {
+    let url = url.trim();
+    let Some(path) = ["https://gist.github.com/", "http://gist.github.com/", "gist.github.com/"]
+        .iter()
+        .find_map(|prefix| url.strip_prefix(prefix))
+    else {
+        return Ok(None);
+    };
+
+    // Drop `#file-...` anchors and query strings; the id is the last path segment
+    let path = path.split(['#', '?']).next().unwrap_or_default();
+    let id = path
+        .trim_end_matches('/')
+        .trim_end_matches(".git")
+        .rsplit('/')
+        .next()
+        .unwrap_or_default();
+
+    if id.is_empty() || !id.chars().all(|c| c.is_ascii_alphanumeric()) {
+        return Err(anyhow!("Invalid gist URL. Expected format: https://gist.github.com/owner/<id>"));
+    }
+    Ok(Some(id.to_string()))
+}
+
+/// Owner, repository and number from a pull request URL such as
+/// `https://github.com/owner/repo/pull/123/files`, or `None` for anything else
+pub fn parse_pull_request_url(url: &str) -> Result<Option<(String, String, u64)>> {
+    let url = url.trim();
+    let Some(path) = ["https://github.com/", "http://github.com/", "github.com/"]
+        .iter()
+        .find_map(|prefix| url.strip_prefix(prefix))
+    else {
+        return Ok(None);
+    };
+
+    let path = path.split(['#', '?']).next().unwrap_or_default();
+    let segments: Vec<&str> = path.split('/').collect();
+    let [owner, repo, "pull", number, ..] = segments.as_slice() else {
+        return Ok(None);
+    };
+
+    let number = number
+        .parse()
+        .map_err(|_| anyhow!("Invalid pull request URL. Expected format: https://github.com/owner/repo/pull/<number>"))?;
+    let (owner, repo) = parse_owner_repo_path(&format!("{}/{}", owner, repo))?;
+    Ok(Some((owner, repo, number)))
+}
+
+/// Workspace and repository from a `bitbucket.org` URL such as
+/// `https://bitbucket.org/workspace/repo/src/main/`, or `None` for anything else
+pub fn parse_bitbucket_url(url: &str) -> Result<Option<(String, String)>> {
+    let url = url.trim();
+    let Some(path) = ["https://bitbucket.org/", "http://bitbucket.org/", "bitbucket.org/"]
+        .iter()
+        .find_map(|prefix| url.strip_prefix(prefix))
+    else {
+        return Ok(None);
+    };
+
+    let path = path.split(['#', '?']).next().unwrap_or_default();
+    let segments: Vec<&str> = path.split('/').collect();
+    let [workspace, repo, ..] = segments.as_slice() else {
+        return Err(anyhow!("Invalid Bitbucket URL. Expected format: https://bitbucket.org/workspace/repo"));
+    };
+    parse_owner_repo_path(&format!("{}/{}", workspace, repo)).map(Some)
+}
+
+fn parse_repository(input: &str) -> Result<(String, String)> {
     // Handle GitHub URLs
     if input.starts_with("https://github.com/") || input.starts_with("http://github.com/") {
         let path = input
@@ -34,8 +148,8 @@ pub fn parse_repo_input(input: &str) -> Result<(String, String)> {
     }
 
     // Handle just owner - prompt for repo name
-    print!("Enter repository name: ");
-    io::stdout().flush()?;
+    eprint!("Enter repository name: ");
+    io::stderr().flush()?;
     let mut repo = String::new();
     io::stdin().read_line(&mut repo)?;
     let repo = repo.trim();
@@ -43,6 +157,8 @@ pub fn parse_repo_input(input: &str) -> Result<(String, String)> {
     if repo.is_empty() {
         return Err(anyhow!("Repository name cannot be empty"));
     }
+    validate_name("owner", input)?;
+    validate_name("repository name", repo)?;
 
     Ok((input.to_string(), repo.to_string()))
 }
@@ -94,10 +210,29 @@ pub fn parse_owner_repo_path(path: &str) -> Result<(String, String)> {
     if owner.is_empty() || repo.is_empty() {
         return Err(anyhow!("Owner and repository name cannot be empty"));
     }
+    validate_name("owner", owner)?;
+    validate_name("repository name", repo)?;
 
     Ok((owner.to_string(), repo.to_string()))
 }
 
+/// Rejects names GitHub wouldn't allow, which would otherwise only show up as a 404:
+/// only ASCII letters, digits, `-`, `_` and `.` are allowed. Repositories may start with
+/// a dot (e.g. `.github`) but can't be `.` or `..`; owners can't start with `.` or `-`.
+fn validate_name(kind: &str, name: &str) -> Result<()> {
+    if let Some(c) = name.chars().find(|c| !(c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))) {
+        return Err(anyhow!(
+            "Invalid {} '{}': {:?} isn't allowed; GitHub names only use letters, digits, '-', '_' and '.'",
+            kind, name, c
+        ));
+    }
+    let reserved = if kind == "owner" { name.starts_with(['.', '-']) } else { name == "." || name == ".." };
+    if reserved {
+        return Err(anyhow!("Invalid {} '{}'", kind, name));
+    }
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -129,4 +264,74 @@ mod tests {
         let result = parse_owner_repo_path("invalid");
         assert!(result.is_err());
     }
+
+    #[test]
+    fn test_parse_owner_repo_path_rejects_invalid_names() {
+        let error = parse_owner_repo_path("tidynest/repo exporter").unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Invalid repository name 'repo exporter': ' ' isn't allowed; GitHub names only use letters, digits, '-', '_' and '.'"
+        );
+        assert!(parse_owner_repo_path("tidy nest/repo").is_err());
+        assert!(parse_owner_repo_path("tidynest/repo:name").is_err());
+
+        // A slash inside a name leaves too many path segments
+        assert!(parse_owner_repo_path("tidynest/repo/exporter").is_err());
+        assert!(parse_github_url("https://github.com/owner/repo/tree/main").is_err());
+
+        // Leading dots: fine for repositories like `.github`, not for owners
+        assert_eq!(parse_owner_repo_path("tidynest/.github").unwrap().1, ".github");
+        assert!(parse_owner_repo_path(".tidynest/repo").is_err());
+        assert!(parse_owner_repo_path("-tidynest/repo").is_err());
+        assert!(parse_owner_repo_path("tidynest/..").is_err());
+    }
+
+    #[test]
+    fn test_parse_repo_input_recognizes_gists() {
+        let gist = ExportTarget::Gist { id: "aa5a315d61ae9438b18d".to_string() };
+        assert_eq!(parse_repo_input("https://gist.github.com/tidynest/aa5a315d61ae9438b18d").unwrap(), gist);
+        assert_eq!(parse_repo_input("gist.github.com/aa5a315d61ae9438b18d/").unwrap(), gist);
+        assert_eq!(parse_repo_input("https://gist.github.com/tidynest/aa5a315d61ae9438b18d#file-main-rs").unwrap(), gist);
+        assert!(parse_repo_input("https://gist.github.com/").is_err());
+
+        assert_eq!(
+            parse_repo_input("https://github.com/owner/repo").unwrap(),
+            ExportTarget::Repository { owner: "owner".to_string(), repo: "repo".to_string() }
+        );
+    }
+
+    #[test]
+    fn test_parse_repo_input_recognizes_pull_requests() {
+        let pull_request = ExportTarget::PullRequest { owner: "owner".to_string(), repo: "repo".to_string(), number: 123 };
+        assert_eq!(parse_repo_input("https://github.com/owner/repo/pull/123").unwrap(), pull_request);
+        assert_eq!(parse_repo_input("github.com/owner/repo/pull/123/files").unwrap(), pull_request);
+        assert_eq!(parse_repo_input("https://github.com/owner/repo/pull/123#issuecomment-1").unwrap(), pull_request);
+        assert!(parse_repo_input("https://github.com/owner/repo/pull/abc").is_err());
+        assert!(parse_repo_input("https://github.com/owner/repo/issues/123").is_err());
+    }
+
+    #[test]
+    fn test_parse_repo_input_recognizes_bitbucket() {
+        let bitbucket = ExportTarget::Bitbucket { workspace: "team".to_string(), repo: "repo".to_string() };
+        assert_eq!(parse_repo_input("https://bitbucket.org/team/repo").unwrap(), bitbucket);
+        assert_eq!(parse_repo_input("bitbucket.org/team/repo.git").unwrap(), bitbucket);
+        assert_eq!(parse_repo_input("https://bitbucket.org/team/repo/src/main/README.md").unwrap(), bitbucket);
+        assert!(parse_repo_input("https://bitbucket.org/team").is_err());
+    }
+
+    #[test]
+    fn test_parse_repo_list() {
+        let text = "# Services\ntidynest/repo_exporter\n\n  https://github.com/owner/repo/pull/7  \n";
+        assert_eq!(parse_repo_list(text).unwrap(), vec![
+            ExportTarget::Repository { owner: "tidynest".to_string(), repo: "repo_exporter".to_string() },
+            ExportTarget::PullRequest { owner: "owner".to_string(), repo: "repo".to_string(), number: 7 },
+        ]);
+
+        let error = parse_repo_list("owner/repo\nhttps://github.com/owner/repo/issues/1\n").unwrap_err();
+        assert!(error.to_string().starts_with("line 2: "));
+
+        // A bare owner is refused rather than prompting for the repository name
+        let error = parse_repo_list("owner/repo\ntidynest\n").unwrap_err();
+        assert_eq!(format!("{:#}", error), "line 2: tidynest: Expected owner/repo or a URL, not just an owner");
+    }
 }
@@ -0,0 +1,116 @@
+// ============= src/input/since.rs =============
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Duration, NaiveDate, NaiveTime, Utc};
+
+/// Parses a `--since` value into a point in time.
+///
+/// Accepts:
+/// - Relative durations: `30m`, `12h`, `7d`, `2w`
+/// - Dates: `2025-01-31` (midnight UTC)
+/// - RFC 3339 timestamps: `2025-01-31T12:00:00Z`
+pub fn parse_since(input: &str, now: DateTime<Utc>) -> Result<DateTime<Utc>> {
+    parse_point(input, now, NaiveTime::MIN).ok_or_else(|| {
+        anyhow!(
+            "Invalid --since value '{}'. Expected a date (2025-01-31), a timestamp (2025-01-31T12:00:00Z) or a relative age (7d, 12h, 2w)",
+            input.trim()
+        )
+    })
+}
+
+/// Parses an `--at` value into a point in time. Takes the same forms as `parse_since`,
+/// except that a date means the end of that day (23:59:59 UTC), so `2025-01-01`
+/// includes the commits made on January 1st.
+pub fn parse_at(input: &str, now: DateTime<Utc>) -> Result<DateTime<Utc>> {
+    let end_of_day = NaiveTime::from_hms_opt(23, 59, 59).unwrap();
+    parse_point(input, now, end_of_day).ok_or_else(|| {
+        anyhow!(
+            "Invalid --at value '{}'. Expected a date (2025-01-31), a timestamp (2025-01-31T12:00:00Z) or a relative age (7d, 12h, 2w)",
+            input.trim()
+        )
+    })
+}
+
+/// A relative age, timestamp or date, with dates taken at `time_of_day` UTC
+fn parse_point(input: &str, now: DateTime<Utc>, time_of_day: NaiveTime) -> Option<DateTime<Utc>> {
+    let input = input.trim();
+
+    if let Some(duration) = parse_relative(input) {
+        return now.checked_sub_signed(duration);
+    }
+
+    if let Ok(timestamp) = DateTime::parse_from_rfc3339(input) {
+        return Some(timestamp.with_timezone(&Utc));
+    }
+
+    NaiveDate::parse_from_str(input, "%Y-%m-%d").ok().map(|date| date.and_time(time_of_day).and_utc())
+}
+
+/// A positive age such as `7d`; `None` for anything else, including ages too large
+/// to represent
+fn parse_relative(input: &str) -> Option<Duration> {
+    let unit = input.chars().last()?;
+    let amount: i64 = input[..input.len() - unit.len_utf8()].parse().ok()?;
+    if amount <= 0 {
+        return None;
+    }
+
+    match unit {
+        'm' => Duration::try_minutes(amount),
+        'h' => Duration::try_hours(amount),
+        'd' => Duration::try_days(amount),
+        'w' => Duration::try_weeks(amount),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn now() -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339("2025-03-15T12:00:00Z").unwrap().with_timezone(&Utc)
+    }
+
+    #[test]
+    fn test_parse_since_relative() {
+        assert_eq!(parse_since("7d", now()).unwrap(), now() - Duration::days(7));
+        assert_eq!(parse_since("12h", now()).unwrap(), now() - Duration::hours(12));
+        assert_eq!(parse_since("2w", now()).unwrap(), now() - Duration::weeks(2));
+    }
+
+    #[test]
+    fn test_parse_since_rejects_out_of_range_ages() {
+        assert!(parse_since("0d", now()).is_err());
+        assert!(parse_since("-3d", now()).is_err());
+        assert!(parse_since("99999999999999w", now()).is_err());
+        assert!(parse_since("9999999999999d", now()).is_err());
+        assert!(parse_at("99999999999999w", now()).is_err());
+    }
+
+    #[test]
+    fn test_parse_since_dates() {
+        assert_eq!(
+            parse_since("2025-01-31", now()).unwrap().to_rfc3339(),
+            "2025-01-31T00:00:00+00:00"
+        );
+        assert_eq!(
+            parse_since("2025-01-31T08:30:00+02:00", now()).unwrap().to_rfc3339(),
+            "2025-01-31T06:30:00+00:00"
+        );
+    }
+
+    #[test]
+    fn test_parse_at_takes_dates_at_end_of_day() {
+        assert_eq!(parse_at("2025-01-01", now()).unwrap().to_rfc3339(), "2025-01-01T23:59:59+00:00");
+        assert_eq!(parse_at("2025-01-01T08:00:00Z", now()).unwrap().to_rfc3339(), "2025-01-01T08:00:00+00:00");
+        assert_eq!(parse_at("2w", now()).unwrap(), now() - Duration::weeks(2));
+        assert!(parse_at("new year", now()).unwrap_err().to_string().contains("--at"));
+    }
+
+    #[test]
+    fn test_parse_since_invalid() {
+        assert!(parse_since("yesterday", now()).is_err());
+        assert!(parse_since("7y", now()).is_err());
+        assert!(parse_since("", now()).is_err());
+    }
+}
@@ -1,8 +1,18 @@
 // ============= src/lib.rs =============
 // Library exports and common items
+pub mod bitbucket;
+pub mod checkpoint;
 pub mod config;
 pub mod export;
+pub mod exporter;
 pub mod github;
+pub mod ignore;
 pub mod input;
+pub mod manifest;
+pub mod normalize;
+pub mod presets;
+pub mod progress;
+pub mod redact;
+pub mod repo_config;
 pub mod ui;
 pub mod utils;
\ No newline at end of file
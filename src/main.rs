@@ -1,56 +1,666 @@
 // ============= src/main.rs =============
-use anyhow::Result;
+use anyhow::{anyhow, bail, Context, Result};
+use chrono::Utc;
+use clap::Parser;
 use dotenvy::dotenv;
+use std::collections::HashSet;
+use std::io::IsTerminal;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tracing_subscriber::EnvFilter;
 
-mod config;
-mod export;
-mod github;
-mod input;
-mod ui;
-mod utils;
+mod cli;
 
-use config::Config;
-use export::export_to_markdown;
-use github::GitHubClient;
-use ui::get_repository_info;
+use cli::Cli;
+use repo_exporter::bitbucket::BitbucketClient;
+use repo_exporter::checkpoint::Checkpoint;
+use repo_exporter::config::Config;
+use repo_exporter::export::{gzip_sizes, ExportFormat, ExportOptions, JsonStyle};
+use repo_exporter::exporter::{Exporter, InterruptHandle, RepoOutcome, STDOUT_OUTPUT};
+use repo_exporter::github::options::{DEFAULT_BINARY_THRESHOLD, DEFAULT_MAX_FILE_SIZE};
+use repo_exporter::github::{EmptyRepositoryError, FailedFile, FetchOptions, RateLimit, RepositoryDisabledError};
+use repo_exporter::input::parser::{parse_repo_input, parse_repo_list, ExportTarget};
+use repo_exporter::input::since::{parse_at, parse_since};
+use repo_exporter::manifest::Manifest;
+use repo_exporter::presets::expand_presets;
+use repo_exporter::progress::ConsoleProgress;
+use repo_exporter::redact::Redactor;
+use repo_exporter::ui::{get_repository_info, plain_text, set_plain_output, PlainWriter};
+use repo_exporter::utils::SkipConfig;
+
+/// Exit status when the exports succeeded but a `--post-hook` command failed, to tell
+/// the two apart in scripts
+const HOOK_FAILED_EXIT_CODE: i32 = 3;
+
+/// How many files `--timing` lists
+const SLOWEST_FETCHES: usize = 10;
+
+/// `eprintln!` for status messages, which always go to stderr so stdout only ever
+/// carries an export (with `--stdout`) or a listing like `--list-refs`
+macro_rules! status {
+    () => {
+        status!("")
+    };
+    ($($arg:tt)*) => {
+        eprintln!("{}", plain_text(&format!($($arg)*)))
+    };
+}
 
 #[tokio::main]
 async fn main() -> Result<()> {
     dotenv().ok();
 
+    let cli = Cli::parse();
+    let output = if cli.stdout { Some(PathBuf::from(STDOUT_OUTPUT)) } else { cli.output.clone() };
+    let to_stdout = output.as_deref() == Some(Path::new(STDOUT_OUTPUT));
+    set_plain_output(cli.no_emoji);
+    init_tracing();
+
+    let formats = match (&cli.output, cli.formats.is_empty()) {
+        (Some(path), true) if !to_stdout => vec![format_from_output(path)?],
+        _ => cli.formats.clone(),
+    };
+    if to_stdout && formats.contains(&ExportFormat::Zip) {
+        bail!("Zip exports can't be written to stdout; pass --output <PATH> instead");
+    }
+    if to_stdout && formats.len() > 1 {
+        bail!("Only one format can be written to stdout; pass --output <PATH> to write several");
+    }
+    if cli.combined && formats.iter().any(|format| matches!(format, ExportFormat::Html | ExportFormat::Text)) {
+        bail!("--combined exports can be markdown, json or zip");
+    }
+    if cli.index && formats.iter().any(|format| matches!(format, ExportFormat::Html | ExportFormat::Zip)) {
+        bail!("--index writes markdown, text or json");
+    }
+    if cli.append && (to_stdout || formats.iter().any(|format| matches!(format, ExportFormat::Json | ExportFormat::Html | ExportFormat::Zip))) {
+        bail!("--append adds to a markdown or text file; pass --output <PATH> and --format markdown or text");
+    }
+    if to_stdout && cli.post_hook.is_some() {
+        bail!("--post-hook needs an export file; pass --output <PATH> instead of writing to stdout");
+    }
+    let since = cli.since.as_deref().map(|since| parse_since(since, Utc::now())).transpose()?;
+    let at = cli.at.as_deref().map(|at| parse_at(at, Utc::now())).transpose()?;
+    let previous_manifest = cli.incremental.as_deref().map(Manifest::load).transpose()?;
+
     let config = Config::load()?;
-    let client = GitHubClient::new(config.github_token);
+    if config.no_emoji {
+        set_plain_output(true);
+    }
+    config.validate()?;
+
+    let mut include = cli.includes.clone();
+    include.extend(expand_presets(&cli.langs)?);
+
+    let fetch_options = FetchOptions {
+        git_ref: cli.git_ref.clone(),
+        at,
+        skip: SkipConfig {
+            skip_generated: !cli.include_generated,
+            skip_hidden: !cli.include_hidden,
+            hidden_allowlist: if cli.allow_hidden.is_empty() {
+                SkipConfig::default().hidden_allowlist
+            } else {
+                cli.allow_hidden.clone()
+            },
+            ..SkipConfig::default()
+        },
+        raw_fallback: config.raw_fallback,
+        normalize_line_endings: config.normalize_line_endings,
+        since,
+        include,
+        exclude: cli.excludes.clone(),
+        subpath: cli.subpath.clone(),
+        max_depth: cli.max_depth,
+        max_file_size: Some(cli.max_file_size.unwrap_or(DEFAULT_MAX_FILE_SIZE)),
+        max_lines: cli.max_lines,
+        binary_threshold: Some(cli.binary_threshold.map_or(DEFAULT_BINARY_THRESHOLD, |percent| f64::from(percent) / 100.0)),
+        max_total_bytes: cli.max_total_bytes,
+        max_files: cli.max_files,
+        content_pattern: cli.content_grep.clone(),
+        ..FetchOptions::default()
+    };
+    // Nobody reads JSON piped into another program, so skip the indentation there
+    let json_style = cli.json_style.unwrap_or(if to_stdout && !std::io::stdout().is_terminal() {
+        JsonStyle::Compact
+    } else {
+        JsonStyle::Pretty
+    });
+    let export_options = ExportOptions {
+        dedupe: config.dedupe_files,
+        line_numbers: config.line_numbers,
+        code_block_style: config.code_block_style,
+        json_style,
+        front_matter: cli.front_matter,
+        wrap_width: cli.wrap.map(|width| width as usize),
+        filters: cli.filters.clone(),
+        normalize_unicode: cli.normalize_unicode,
+        flat_headings: cli.flat,
+        heading_offset: usize::from(cli.heading_offset),
+        fence_languages: cli.fence_languages,
+        fence_info: cli.fence_info.iter().cloned().collect(),
+        section_template: cli.section_template.clone(),
+        file_separator: cli.separator.clone(),
+        largest_files: cli.largest_files,
+        append: cli.append,
+        gzip: cli.gzip,
+        timestamp: config.source_date_epoch.map(|time| time.fixed_offset()),
+        ..ExportOptions::default()
+    };
+
+    let progress = Arc::new(if cli.timing { ConsoleProgress::new().with_timing() } else { ConsoleProgress::new() });
+    let mut builder = Exporter::builder(config.github_token)
+        .auth_scheme(config.auth_scheme)
+        .user_agent(&config.user_agent)
+        .http_settings(config.http.clone())
+        .progress(progress.clone())
+        .fetch_options(fetch_options)
+        .export_options(export_options)
+        .repo_config(!cli.ignore_repo_config)
+        .wiki(cli.wiki)
+        .checkpoint_dir(".");
+    if let Some(base_url) = &config.base_url {
+        builder = builder.base_url(base_url);
+    }
+    if let Some(app) = config.github_app.clone() {
+        builder = builder.app_credentials(app);
+    }
+    builder = builder.bitbucket(BitbucketClient::new(config.bitbucket_auth.clone()).with_http_settings(&config.http)?);
+    if !formats.is_empty() {
+        builder = builder.formats(formats);
+    }
+    if let Some(output) = output {
+        builder = builder.output(output);
+    }
+    if config.redact_secrets {
+        builder = builder.redactor(Redactor::default());
+    }
+    if let Some(manifest) = &cli.manifest {
+        builder = builder.manifest(manifest);
+    }
+    if let Some(previous) = previous_manifest {
+        builder = builder.incremental(previous);
+    }
+    let exporter = builder.build()?;
+    handle_ctrl_c(exporter.interrupt_handle());
+
+    status!("🚀 GitHub Repository Exporter");
+    status!("================================\n");
+
+    let token_info = match exporter.client().verify_token().await {
+        Ok(info) => info,
+        Err(e) => {
+            status!("❌ {}", e);
+            if config.github_app.is_some() {
+                status!("\nCheck the GitHub App id, private key and installation id.");
+            } else {
+                status!("\nCheck that GITHUB_TOKEN is set to a valid, unexpired token.");
+            }
+            return Ok(());
+        }
+    };
+
+    status!("🔑 Authenticated as {}", token_info.login);
+    if token_info.private_repo_access == Some(false) {
+        status!("⚠️  Your token can't see private repos (missing `repo` scope); only public repositories can be exported.");
+    }
+    // Best-effort: a failed check shouldn't stop the export
+    let rate_limit_before = exporter.client().rate_limit().await.ok();
+    if let Some(rate_limit) = &rate_limit_before {
+        status!("📊 API rate limit: {}", rate_limit.summary(Utc::now()));
+    }
+    status!();
+
+    let mut repos = repo_specs(&cli)?;
+    if repos.is_empty() {
+        if to_stdout {
+            bail!("Pass the repository as an argument when writing to stdout");
+        }
+        repos.extend(get_repository_info()?.into_iter().map(|(owner, repo)| ExportTarget::Repository { owner, repo }));
+    }
+    if repos.len() > 1 && !cli.combined && !cli.append && cli.output.is_some() && !to_stdout {
+        bail!("--output with several repositories needs --combined or --append, or each export would overwrite the last");
+    }
+
+    if cli.compare.is_some() && repos.iter().any(|target| !matches!(target, ExportTarget::Repository { .. })) {
+        bail!("--compare only supports GitHub repositories, not pull requests, gists or Bitbucket repositories");
+    }
+
+    if cli.estimate && repos.iter().any(|target| matches!(target, ExportTarget::Bitbucket { .. })) {
+        bail!("--estimate only supports GitHub repositories, pull requests and gists, not Bitbucket repositories");
+    }
+    if cli.index && repos.iter().any(|target| !matches!(target, ExportTarget::Repository { .. })) {
+        bail!("--index only supports GitHub repositories, not pull requests, gists or Bitbucket repositories");
+    }
+    if at.is_some() && repos.iter().any(|target| !matches!(target, ExportTarget::Repository { .. })) {
+        bail!("--at only supports GitHub repositories, not pull requests, gists or Bitbucket repositories");
+    }
+    if cli.manifest.is_some() || cli.incremental.is_some() {
+        if repos.len() > 1 {
+            bail!("--manifest and --incremental export one repository at a time");
+        }
+        if repos.iter().any(|target| matches!(target, ExportTarget::Gist { .. } | ExportTarget::Bitbucket { .. })) {
+            bail!("--manifest and --incremental only support GitHub repositories and pull requests, not gists or Bitbucket repositories");
+        }
+    }
+
+    // Without the flag, the export's own repository request still notices a move and
+    // logs it, so only following one costs an extra request per repository
+    if cli.follow_renames {
+        for target in repos.iter_mut() {
+            let (ExportTarget::Repository { owner, repo } | ExportTarget::PullRequest { owner, repo, .. }) = target else {
+                continue;
+            };
+            // A failed lookup is left for the export itself to report
+            if let Ok(Some((new_owner, new_repo))) = exporter.client().moved_to(owner, repo).await {
+                status!("↪️  {}/{} has moved to {}/{}; exporting it under the new name", owner, repo, new_owner, new_repo);
+                *owner = new_owner;
+                *repo = new_repo;
+            }
+        }
+    }
+
+    if let Some(since) = since {
+        status!("🕒 Only exporting files changed since {}", since.format("%Y-%m-%d %H:%M UTC"));
+    }
+
+    if cli.estimate {
+        return print_estimates(&exporter, &repos, rate_limit_before.as_ref()).await;
+    }
+    if cli.list_refs {
+        return print_refs(&exporter, &repos).await;
+    }
+
+    let outcomes = if cli.combined {
+        let repos = repos
+            .into_iter()
+            .map(|target| match target {
+                ExportTarget::Repository { owner, repo } => Ok((owner, repo)),
+                ExportTarget::PullRequest { .. } => Err(anyhow!("--combined only supports repositories, not pull requests")),
+                ExportTarget::Gist { .. } => Err(anyhow!("--combined only supports repositories, not gists")),
+                ExportTarget::Bitbucket { .. } => Err(anyhow!("--combined only supports GitHub repositories, not Bitbucket ones")),
+            })
+            .collect::<Result<Vec<_>>>()?;
+        status!("📂 Fetching {} repositories into one export...", repos.len());
+        let outcomes = exporter.run_combined(&repos).await?;
+        if cli.timing {
+            print_slowest_fetches(&progress);
+        }
+        outcomes
+    } else {
+        let mut outcomes = Vec::new();
+        for target in repos {
+            if exporter.interrupt_handle().is_interrupted() {
+                break;
+            }
+            outcomes.push(match target {
+                ExportTarget::Repository { owner, repo } => {
+                    let result = match &cli.compare {
+                        _ if cli.index => {
+                            status!("📂 Listing the files in {}/{}...", owner, repo);
+                            exporter.run_index(&owner, &repo, cli.index_lines).await
+                        }
+                        Some((base, head)) => {
+                            status!("📂 Fetching the files changed between {} and {} in {}/{}...", base, head, owner, repo);
+                            exporter.run_compare(&owner, &repo, base, head).await
+                        }
+                        None => {
+                            status!("📂 Fetching repository contents for {}/{}...", owner, repo);
+                            status!("🔍 Checking repository existence...");
+                            exporter.run(&owner, &repo).await
+                        }
+                    };
+                    RepoOutcome { owner, repo, result }
+                }
+                ExportTarget::PullRequest { owner, repo, number } => {
+                    status!("📂 Fetching pull request #{} of {}/{}...", number, owner, repo);
+                    let result = exporter.run_pull_request(&owner, &repo, number).await;
+                    RepoOutcome { owner, repo, result }
+                }
+                ExportTarget::Gist { id } => {
+                    status!("📂 Fetching gist {}...", id);
+                    exporter.run_gist(&id).await
+                }
+                ExportTarget::Bitbucket { workspace, repo } => {
+                    status!("📂 Fetching Bitbucket repository {}/{}...", workspace, repo);
+                    let result = exporter.run_bitbucket(&workspace, &repo).await;
+                    RepoOutcome { owner: workspace, repo, result }
+                }
+            });
+            if cli.timing {
+                print_slowest_fetches(&progress);
+            }
+        }
+        outcomes
+    };
+
+    let mut any_failed = false;
+    for outcome in &outcomes {
+        if outcomes.len() > 1 {
+            status!("\n📦 {}/{}", outcome.owner, outcome.repo);
+        }
+        any_failed |= !report_outcome(outcome, config.redact_secrets, cli.gzip);
+    }
+
+    if outcomes.len() > 1 {
+        print_batch_summary(&outcomes);
+    }
+
+    let mut hook_failed = false;
+    if let Some(hook) = &cli.post_hook {
+        let mut outputs: Vec<&Path> = outcomes
+            .iter()
+            .filter_map(|outcome| outcome.result.as_ref().ok())
+            .filter(|summary| !summary.interrupted)
+            .flat_map(|summary| summary.output.iter().chain(&summary.extra_outputs))
+            .map(PathBuf::as_path)
+            .collect();
+        // A combined export's files are shared by every repository
+        let mut seen = HashSet::new();
+        outputs.retain(|output| seen.insert(*output));
+        for output in outputs {
+            hook_failed |= !run_post_hook(hook, output).await;
+        }
+    }
+    if let Some(before) = rate_limit_before {
+        if let Ok(after) = exporter.client().rate_limit().await {
+            print_rate_limit_usage(&before, &after);
+        }
+    }
+
+    // 130 is the conventional status for a run stopped by SIGINT
+    if exporter.interrupt_handle().is_interrupted() {
+        std::process::exit(130);
+    }
+    if any_failed && cli.fail_on_error {
+        std::process::exit(1);
+    }
+    if hook_failed {
+        std::process::exit(HOOK_FAILED_EXIT_CODE);
+    }
+
+    Ok(())
+}
+
+/// Logs at info level unless `RUST_LOG` says otherwise, always to stderr so stdout only
+/// ever carries the export
+fn init_tracing() {
+    let filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("info"));
+    tracing_subscriber::fmt()
+        .with_env_filter(filter)
+        .with_target(false)
+        .without_time()
+        .with_writer(|| PlainWriter(std::io::stderr()))
+        .init();
+}
+
+/// On Ctrl-C, stops fetching so the files fetched so far are written out; a second
+/// Ctrl-C quits immediately
+fn handle_ctrl_c(interrupt: InterruptHandle) {
+    tokio::spawn(async move {
+        if tokio::signal::ctrl_c().await.is_err() {
+            return;
+        }
+        status!("\n⏹️  Interrupted; writing the files fetched so far (Ctrl-C again to quit now)...");
+        interrupt.interrupt();
 
-    println!("🚀 GitHub Repository Exporter");
-    println!("================================\n");
+        if tokio::signal::ctrl_c().await.is_ok() {
+            std::process::exit(130);
+        }
+    });
+}
 
-    let (owner, repo) = get_repository_info()?;
+/// The format `--output`'s extension names, for when `--format` isn't given
+fn format_from_output(path: &Path) -> Result<ExportFormat> {
+    let Some(extension) = path.extension().and_then(|extension| extension.to_str()) else {
+        bail!("Can't tell the format of {} without an extension; pass --format markdown, json, html, text or zip", path.display());
+    };
+    ExportFormat::from_extension(extension).ok_or_else(|| {
+        anyhow!("Can't tell the format of {} from '.{}'; pass --format markdown, json, html, text or zip", path.display(), extension)
+    })
+}
 
-    println!("📂 Fetching repository contents for {}/{}...", owner, repo);
+/// Repositories named on the command line, followed by those in `--repos-file` and then
+/// those listed on stdin with `--stdin`
+fn repo_specs(cli: &Cli) -> Result<Vec<ExportTarget>> {
+    let mut targets = cli.repos.iter().map(|spec| parse_repo_input(spec)).collect::<Result<Vec<_>>>()?;
 
-    match client.fetch_repo_files(&owner, &repo).await {
-        Ok(files) => {
-            if files.is_empty() {
-                println!("⚠️  No files found in the repository or all files were skipped.");
-                return Ok(());
+    if let Some(path) = &cli.repos_file {
+        let contents = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read repos file {}", path.display()))?;
+        targets.extend(parse_repo_list(&contents).with_context(|| format!("Invalid entry in repos file {}", path.display()))?);
+    }
+    if cli.stdin {
+        let contents = std::io::read_to_string(std::io::stdin()).context("Failed to read repositories from stdin")?;
+        let listed = parse_repo_list(&contents).context("Invalid entry on stdin")?;
+        if listed.is_empty() {
+            bail!("--stdin was given, but no repositories were listed on stdin");
+        }
+        targets.extend(listed);
+    }
+
+    Ok(targets)
+}
+
+/// Prints how a repository's export went; returns false if it failed or left files out
+fn report_outcome(outcome: &RepoOutcome, redact_secrets: bool, gzip: bool) -> bool {
+    match &outcome.result {
+        Ok(summary) => {
+            if summary.resumed_files > 0 {
+                status!("♻️  Resumed {} files from a previous run", summary.resumed_files);
             }
 
-            let output_file = export_to_markdown(&owner, &repo, files)?;
-            println!("✅ Export complete: {}", output_file);
+            match &summary.output {
+                Some(output_file) => {
+                    let destination = if output_file == Path::new(STDOUT_OUTPUT) {
+                        "stdout".to_string()
+                    } else {
+                        output_file.display().to_string()
+                    };
+                    if summary.interrupted {
+                        status!("⏹️  Partial export written to {}", destination);
+                    } else if output_file == Path::new(STDOUT_OUTPUT) {
+                        status!("✅ Export written to stdout");
+                    } else {
+                        status!("✅ Export complete: {}", destination);
+                    }
+                    for extra in &summary.extra_outputs {
+                        status!("   Also written: {}", extra.display());
+                    }
+                    if gzip {
+                        for path in summary.output.iter().chain(&summary.extra_outputs) {
+                            if let Ok(sizes) = gzip_sizes(path) {
+                                status!(
+                                    "🗜️  {}: {} bytes compressed to {} ({}%)",
+                                    path.display(),
+                                    sizes.uncompressed,
+                                    sizes.compressed,
+                                    sizes.compressed * 100 / sizes.uncompressed.max(1)
+                                );
+                            }
+                        }
+                    }
+                    if redact_secrets {
+                        status!("🔒 Redacted {} potential secret(s)", summary.redactions);
+                    }
+                }
+                None if summary.interrupted => status!("⏹️  Interrupted before any files were fetched."),
+                None => status!("⚠️  No files found in the repository or all files were skipped."),
+            }
+            if let Some(manifest) = &summary.manifest {
+                status!("🧾 Manifest written to {}", manifest.display());
+            }
+            if summary.unmatched_files > 0 {
+                status!("🔎 Left out {} file(s) whose content didn't match --content-grep", summary.unmatched_files);
+            }
+            if summary.interrupted {
+                status!("💾 Progress saved; run the export again to resume.");
+            }
+
+            if !summary.failed.is_empty() {
+                print_failed_files(&summary.failed);
+            }
+            summary.failed.is_empty() && !summary.interrupted
+        }
+        Err(e) if e.is::<EmptyRepositoryError>() => {
+            status!("📭 {}", e);
+            status!("✅ Exported 0 files");
+            true
+        }
+        Err(e) if e.is::<RepositoryDisabledError>() => {
+            status!("🚫 {}", e);
+            false
         }
         Err(e) => {
-            println!("❌ Failed to fetch repository: {}", e);
+            status!("❌ Failed to fetch repository: {}", e);
+            if Checkpoint::exists_for_repo(Path::new("."), &outcome.owner, &outcome.repo) {
+                status!("💾 Progress saved; run the export again to resume.");
+            }
             print_error_suggestions();
+            false
         }
     }
+}
+
+fn print_batch_summary(outcomes: &[RepoOutcome]) {
+    status!("\n📋 Summary");
+    for outcome in outcomes {
+        let status = match &outcome.result {
+            Ok(summary) if summary.interrupted => format!("⏹️  {} files (interrupted)", summary.files_exported),
+            Ok(summary) if summary.failed.is_empty() => format!("✅ {} files", summary.files_exported),
+            Ok(summary) => format!("⚠️  {} files, {} failed", summary.files_exported, summary.failed.len()),
+            Err(e) if e.is::<EmptyRepositoryError>() => "📭 empty".to_string(),
+            Err(e) if e.is::<RepositoryDisabledError>() => "🚫 disabled".to_string(),
+            Err(_) => "❌ failed".to_string(),
+        };
+        status!("  {}/{}: {}", outcome.owner, outcome.repo, status);
+    }
+}
 
+async fn print_estimates(exporter: &Exporter, repos: &[ExportTarget], rate_limit: Option<&RateLimit>) -> Result<()> {
+    let mut total = 0;
+    for target in repos {
+        let (name, estimate) = match target {
+            ExportTarget::Repository { owner, repo } => {
+                (format!("{}/{}", owner, repo), exporter.estimate_requests(owner, repo).await?)
+            }
+            // Plus one to look up the pull request's head; the tree is estimated at the default ref
+            ExportTarget::PullRequest { owner, repo, number } => {
+                (format!("{}/{}#{}", owner, repo, number), exporter.estimate_requests(owner, repo).await? + 1)
+            }
+            // Content comes inline with the gist itself
+            ExportTarget::Gist { id } => (format!("gist {}", id), 1),
+            ExportTarget::Bitbucket { .. } => unreachable!("--estimate rejects Bitbucket repositories"),
+        };
+        status!("📊 {}: this export will use ~{} API requests", name, estimate);
+        total += estimate;
+    }
+    if repos.len() > 1 {
+        status!("📊 Total: ~{} API requests", total);
+    }
+    if let Some(rate_limit) = rate_limit.filter(|rate_limit| (rate_limit.remaining as usize) < total) {
+        status!("⚠️  Only {} requests remain before the limit resets", rate_limit.remaining);
+    }
     Ok(())
 }
 
+/// Prints each repository's branches and tags, one per line, marking the default branch
+async fn print_refs(exporter: &Exporter, repos: &[ExportTarget]) -> Result<()> {
+    for (index, target) in repos.iter().enumerate() {
+        let (owner, repo) = match target {
+            ExportTarget::Repository { owner, repo } | ExportTarget::PullRequest { owner, repo, .. } => (owner, repo),
+            ExportTarget::Gist { .. } | ExportTarget::Bitbucket { .. } => {
+                bail!("--list-refs only supports GitHub repositories, not gists or Bitbucket repositories")
+            }
+        };
+        let refs = exporter.client().list_refs(owner, repo).await?;
+
+        if index > 0 {
+            println!();
+        }
+        if repos.len() > 1 {
+            println!("{}/{}", owner, repo);
+        }
+        println!("Branches:");
+        for branch in &refs.branches {
+            if *branch == refs.default_branch {
+                println!("* {} (default)", branch);
+            } else {
+                println!("  {}", branch);
+            }
+        }
+        println!("Tags:");
+        if refs.tags.is_empty() {
+            println!("  (none)");
+        }
+        for tag in &refs.tags {
+            println!("  {}", tag);
+        }
+    }
+    Ok(())
+}
+
+/// Lists the slowest file fetches since the last report, to tell whether a few large
+/// files, rate limiting or per-request latency dominate an export
+fn print_slowest_fetches(progress: &ConsoleProgress) {
+    let (slowest, total) = progress.take_slowest(SLOWEST_FETCHES);
+    if slowest.is_empty() {
+        return;
+    }
+
+    status!("\n⏱️  {:.1}s fetching files; slowest:", total.as_secs_f64());
+    for (path, elapsed) in slowest {
+        status!("  {:>8.2}s  {}", elapsed.as_secs_f64(), path);
+    }
+}
+
+fn print_rate_limit_usage(before: &RateLimit, after: &RateLimit) {
+    let summary = after.summary(Utc::now());
+    match after.consumed_since(before) {
+        Some(consumed) => status!("\n📊 This export used {} API requests ({})", consumed, summary),
+        None => status!("\n📊 API rate limit: {} (the limit reset during the export)", summary),
+    }
+}
+
+/// Runs `hook` through the shell with `output` as its last argument (unless the command
+/// refers to `$EXPORT_FILE` itself) and in `EXPORT_FILE`; returns false if it failed
+async fn run_post_hook(hook: &str, output: &Path) -> bool {
+    let (shell, flag, variable) = if cfg!(windows) { ("cmd", "/C", "%EXPORT_FILE%") } else { ("sh", "-c", "$EXPORT_FILE") };
+    let command = if hook.contains("EXPORT_FILE") { hook.to_string() } else { format!("{} \"{}\"", hook, variable) };
+
+    status!("🪝 Running post-export hook for {}", output.display());
+    let status = tokio::process::Command::new(shell)
+        .arg(flag)
+        .arg(&command)
+        .env("EXPORT_FILE", output)
+        .status()
+        .await;
+    match status {
+        Ok(status) if status.success() => {
+            status!("🪝 Post-export hook finished ({})", status);
+            true
+        }
+        Ok(status) => {
+            status!("❌ Post-export hook failed ({}); the export itself was written", status);
+            false
+        }
+        Err(e) => {
+            status!("❌ Couldn't run post-export hook: {}", e);
+            false
+        }
+    }
+}
+
+fn print_failed_files(failed: &[FailedFile]) {
+    status!("\n❗ {} file(s) could not be fetched and are missing from the export:", failed.len());
+    for failure in failed {
+        status!("  • {}: {}", failure.path, failure.error);
+    }
+}
+
 fn print_error_suggestions() {
-    println!("\nPossible causes:");
-    println!("  • Repository doesn't exist (check for typos)");
-    println!("  • Repository is private (check your GITHUB_TOKEN permissions)");
-    println!("  • Network issues or GitHub API is down");
-    println!("  • Rate limit exceeded");
-}
\ No newline at end of file
+    status!("\nPossible causes:");
+    status!("  • Repository doesn't exist (check for typos)");
+    status!("  • Repository is private (check your GITHUB_TOKEN permissions)");
+    status!("  • Network issues or GitHub API is down");
+    status!("  • Rate limit exceeded");
+}
@@ -0,0 +1,92 @@
+// ============= src/manifest.rs =============
+use anyhow::{anyhow, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
+use std::fs;
+use std::path::Path;
+
+/// The blob SHA of every file an export covered, written alongside it so a later export
+/// can leave out the files that haven't changed since.
+///
+/// Stored as pretty-printed JSON: the repository, the commit exported, and a map from
+/// path to blob SHA.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Manifest {
+    pub owner: String,
+    pub repo: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub commit_sha: Option<String>,
+    pub files: BTreeMap<String, String>,
+}
+
+impl Manifest {
+    pub fn new(owner: impl Into<String>, repo: impl Into<String>) -> Self {
+        Self { owner: owner.into(), repo: repo.into(), ..Self::default() }
+    }
+
+    pub fn load(path: &Path) -> Result<Self> {
+        let text = fs::read_to_string(path).with_context(|| format!("Couldn't read manifest {}", path.display()))?;
+        serde_json::from_str(&text).with_context(|| format!("{} is not a valid manifest", path.display()))
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        fs::write(path, serde_json::to_string_pretty(self)? + "\n")?;
+        Ok(())
+    }
+
+    /// Errors unless the manifest was written for `owner/repo`
+    pub fn check_repo(&self, owner: &str, repo: &str) -> Result<()> {
+        if self.owner.eq_ignore_ascii_case(owner) && self.repo.eq_ignore_ascii_case(repo) {
+            Ok(())
+        } else {
+            Err(anyhow!("The manifest is for {}/{}, not {}/{}", self.owner, self.repo, owner, repo))
+        }
+    }
+
+    /// Paths in `current` that are new or have a different SHA than here
+    pub fn changed_in(&self, current: &Manifest) -> HashSet<String> {
+        current
+            .files
+            .iter()
+            .filter(|(path, sha)| self.files.get(*path) != Some(*sha))
+            .map(|(path, _)| path.clone())
+            .collect()
+    }
+
+    /// Paths here that are missing from `current`, sorted
+    pub fn removed_in(&self, current: &Manifest) -> Vec<String> {
+        self.files.keys().filter(|path| !current.files.contains_key(*path)).cloned().collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn manifest(files: &[(&str, &str)]) -> Manifest {
+        Manifest {
+            files: files.iter().map(|(path, sha)| (path.to_string(), sha.to_string())).collect(),
+            ..Manifest::new("owner", "repo")
+        }
+    }
+
+    #[test]
+    fn test_changed_and_removed_and_round_trip() {
+        let previous = manifest(&[("README.md", "a1"), ("src/lib.rs", "b2"), ("src/old.rs", "c3")]);
+        let current = manifest(&[("README.md", "a1"), ("src/lib.rs", "b9"), ("src/new.rs", "d4")]);
+
+        let mut changed: Vec<_> = previous.changed_in(&current).into_iter().collect();
+        changed.sort();
+        assert_eq!(changed, vec!["src/lib.rs", "src/new.rs"]);
+        assert_eq!(previous.removed_in(&current), vec!["src/old.rs"]);
+
+        let path = std::env::temp_dir().join(format!("repo_exporter_manifest_{}.json", std::process::id()));
+        current.save(&path).unwrap();
+        let loaded = Manifest::load(&path);
+        fs::remove_file(&path).ok();
+        assert_eq!(loaded.unwrap(), current);
+
+        assert!(current.check_repo("Owner", "repo").is_ok());
+        assert!(current.check_repo("owner", "other").is_err());
+    }
+}
@@ -0,0 +1,49 @@
+// ============= src/normalize.rs =============
+use std::borrow::Cow;
+use unicode_normalization::{is_nfc_quick, IsNormalized, UnicodeNormalization};
+
+/// Removes a leading UTF-8 byte-order mark, which otherwise shows up as `\u{feff}`
+pub fn strip_bom(content: &str) -> &str {
+    content.strip_prefix('\u{feff}').unwrap_or(content)
+}
+
+/// Converts CRLF line endings to LF
+pub fn normalize_line_endings(content: &str) -> String {
+    content.replace("\r\n", "\n")
+}
+
+/// Converts text to Unicode Normalization Form C, so that e.g. `e` followed by a
+/// combining acute accent becomes a single `é`. Text already in NFC is borrowed as-is.
+pub fn to_nfc(content: &str) -> Cow<'_, str> {
+    if is_nfc_quick(content.chars()) == IsNormalized::Yes {
+        Cow::Borrowed(content)
+    } else {
+        Cow::Owned(content.nfc().collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_strip_bom() {
+        assert_eq!(strip_bom("\u{feff}fn main() {}"), "fn main() {}");
+        assert_eq!(strip_bom("fn main() {}"), "fn main() {}");
+        // Only a leading BOM is stripped
+        assert_eq!(strip_bom("a\u{feff}b"), "a\u{feff}b");
+    }
+
+    #[test]
+    fn test_normalize_line_endings() {
+        assert_eq!(normalize_line_endings("a\r\nb\r\n"), "a\nb\n");
+        // Lone carriage returns aren't line endings we rewrite
+        assert_eq!(normalize_line_endings("a\rb\n"), "a\rb\n");
+    }
+
+    #[test]
+    fn test_to_nfc() {
+        assert_eq!(to_nfc("cafe\u{301}"), "caf\u{e9}");
+        assert!(matches!(to_nfc("caf\u{e9}"), Cow::Borrowed(_)));
+    }
+}
@@ -0,0 +1,56 @@
+// ============= src/presets.rs =============
+use anyhow::{anyhow, Result};
+
+/// Named include-pattern sets for `--lang`, in gitignore syntax
+const LANGUAGE_PRESETS: [(&str, &[&str]); 5] = [
+    ("rust", &["*.rs", "Cargo.toml", "rust-toolchain", "rust-toolchain.toml", "rustfmt.toml", "clippy.toml"]),
+    ("python", &["*.py", "*.pyi", "pyproject.toml", "setup.py", "setup.cfg", "requirements*.txt"]),
+    ("go", &["*.go", "go.mod", "go.sum"]),
+    ("web", &["*.html", "*.css", "*.scss", "*.js", "*.mjs", "*.jsx", "*.ts", "*.tsx", "*.vue", "*.svelte", "package.json"]),
+    ("docs", &["*.md", "*.mdx", "*.rst", "*.adoc", "*.txt", "docs/", "README*", "LICENSE*", "CHANGELOG*"]),
+];
+
+/// Expands preset names into their include patterns. Presets combine, and unknown
+/// names are an error listing the ones available.
+pub fn expand_presets(names: &[String]) -> Result<Vec<String>> {
+    let mut patterns = Vec::new();
+
+    for name in names {
+        let key = name.trim().to_lowercase();
+        let (_, preset) = LANGUAGE_PRESETS
+            .iter()
+            .find(|(preset, _)| *preset == key)
+            .ok_or_else(|| anyhow!("Unknown language preset '{}'. Available: {}", name, preset_names().join(", ")))?;
+
+        for pattern in preset.iter() {
+            if !patterns.iter().any(|existing| existing == pattern) {
+                patterns.push(pattern.to_string());
+            }
+        }
+    }
+
+    Ok(patterns)
+}
+
+pub fn preset_names() -> Vec<&'static str> {
+    LANGUAGE_PRESETS.iter().map(|(name, _)| *name).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_expand_presets_combines() {
+        let patterns = expand_presets(&["rust".to_string(), "Docs".to_string()]).unwrap();
+        assert!(patterns.contains(&"*.rs".to_string()));
+        assert!(patterns.contains(&"Cargo.toml".to_string()));
+        assert!(patterns.contains(&"*.md".to_string()));
+    }
+
+    #[test]
+    fn test_expand_presets_unknown() {
+        let error = expand_presets(&["cobol".to_string()]).unwrap_err();
+        assert!(error.to_string().contains("Available: rust, python, go, web, docs"));
+    }
+}
@@ -0,0 +1,227 @@
+// ============= src/progress.rs =============
+use std::fmt;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// Why a file didn't make it into the export
+#[derive(Debug, Clone, PartialEq)]
+pub enum SkipReason {
+    /// The content request failed or the file couldn't be decoded as UTF-8 text
+    FetchFailed(String),
+    /// The decoded file exceeds the byte-size cap
+    TooLarge { bytes: usize, limit: usize },
+    /// The decoded file has more lines than the line-count cap
+    TooManyLines { lines: usize, limit: usize },
+    /// The decoded file doesn't match the content pattern
+    NoContentMatch,
+    /// The file name marks it as generated, e.g. a lockfile or source map
+    Generated,
+    /// The content looks minified: its lines average this many characters
+    Minified { average_line_length: usize },
+    /// The content decodes as UTF-8 but looks binary: this percentage of its characters
+    /// are control characters
+    Binary { control_percent: usize },
+}
+
+impl fmt::Display for SkipReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SkipReason::FetchFailed(error) => write!(f, "fetch failed: {}", error),
+            SkipReason::TooLarge { bytes, limit } => {
+                write!(f, "{} bytes exceeds the {} byte limit", bytes, limit)
+            }
+            SkipReason::TooManyLines { lines, limit } => {
+                write!(f, "{} lines exceeds the {} line limit", lines, limit)
+            }
+            SkipReason::NoContentMatch => write!(f, "content doesn't match the pattern"),
+            SkipReason::Generated => write!(f, "looks generated"),
+            SkipReason::Minified { average_line_length } => {
+                write!(f, "looks minified, lines average {} characters", average_line_length)
+            }
+            SkipReason::Binary { control_percent } => {
+                write!(f, "looks binary, {}% control characters", control_percent)
+            }
+        }
+    }
+}
+
+/// Receives progress events while repository files are fetched.
+///
+/// Implementations must be thread-safe: callbacks may arrive from concurrent fetches,
+/// so they shouldn't assume files start and finish in order.
+pub trait ProgressReporter: Send + Sync {
+    /// Called once the eligible file list is known, and again with the new total when a
+    /// path turns out to be a directory whose files are fetched too
+    fn on_start(&self, _total: usize) {}
+    fn on_file_start(&self, path: &str);
+    fn on_file_done(&self, path: &str, bytes: usize);
+    fn on_file_skipped(&self, path: &str, reason: &SkipReason);
+    /// Called when a file's content request completes, successfully or not, with how
+    /// long it took (including any wait for a rate limit to reset)
+    fn on_file_fetched(&self, _path: &str, _elapsed: Duration) {}
+    /// Called when the total size cap is reached; `omitted` files are left out, unfetched
+    fn on_total_limit_reached(&self, _omitted: usize) {}
+    /// Called before fetching when the file count cap leaves `omitted` files out
+    fn on_file_limit_reached(&self, _omitted: usize) {}
+}
+
+/// Reports nothing; the default for library use
+pub struct SilentProgress;
+
+impl ProgressReporter for SilentProgress {
+    fn on_file_start(&self, _path: &str) {}
+    fn on_file_done(&self, _path: &str, _bytes: usize) {}
+    fn on_file_skipped(&self, _path: &str, _reason: &SkipReason) {}
+}
+
+/// Logs one `tracing` event per file, as the CLI does: progress at info level,
+/// size-limit skips as warnings and fetch failures as errors. Files not matching the
+/// content pattern are usually most of them, so those are only logged at debug level.
+#[derive(Default)]
+pub struct ConsoleProgress {
+    total: AtomicUsize,
+    started: AtomicUsize,
+    /// How long each file took to fetch, when timing is on
+    timings: Option<Mutex<Vec<(String, Duration)>>>,
+}
+
+impl ConsoleProgress {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Also records how long each file takes to fetch, for `take_slowest`
+    pub fn with_timing(mut self) -> Self {
+        self.timings = Some(Mutex::default());
+        self
+    }
+
+    /// The `n` slowest fetches recorded since the last call, slowest first, and the
+    /// total time spent fetching. Empty unless timing is on.
+    pub fn take_slowest(&self, n: usize) -> (Vec<(String, Duration)>, Duration) {
+        let Some(timings) = &self.timings else {
+            return (Vec::new(), Duration::ZERO);
+        };
+        let mut timings = std::mem::take(&mut *timings.lock().unwrap());
+        let total = timings.iter().map(|(_, elapsed)| *elapsed).sum();
+        timings.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        timings.truncate(n);
+        (timings, total)
+    }
+}
+
+impl ProgressReporter for ConsoleProgress {
+    fn on_start(&self, total: usize) {
+        self.total.store(total, Ordering::Relaxed);
+        tracing::info!("Found {} files to process", total);
+    }
+
+    fn on_file_start(&self, path: &str) {
+        let index = self.started.fetch_add(1, Ordering::Relaxed) + 1;
+        let total = self.total.load(Ordering::Relaxed);
+        tracing::info!(index, total, path, "processing file");
+    }
+
+    fn on_file_done(&self, _path: &str, _bytes: usize) {}
+
+    fn on_file_fetched(&self, path: &str, elapsed: Duration) {
+        tracing::debug!(path, elapsed_ms = elapsed.as_millis() as u64, "fetched file");
+        if let Some(timings) = &self.timings {
+            timings.lock().unwrap().push((path.to_string(), elapsed));
+        }
+    }
+
+    fn on_file_skipped(&self, path: &str, reason: &SkipReason) {
+        match reason {
+            SkipReason::FetchFailed(_) => tracing::error!(path, %reason, "failed to fetch file"),
+            SkipReason::NoContentMatch => tracing::debug!(path, %reason, "skipped file"),
+            _ => tracing::warn!(path, %reason, "skipped file"),
+        }
+    }
+
+    fn on_total_limit_reached(&self, omitted: usize) {
+        tracing::warn!(omitted, "total size limit reached; omitting the remaining files");
+    }
+
+    fn on_file_limit_reached(&self, omitted: usize) {
+        tracing::warn!(omitted, "file limit reached; omitting the last files in path order");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+
+    #[derive(Default)]
+    struct RecordingProgress {
+        events: Mutex<Vec<String>>,
+    }
+
+    impl ProgressReporter for RecordingProgress {
+        fn on_file_start(&self, path: &str) {
+            self.events.lock().unwrap().push(format!("start {}", path));
+        }
+
+        fn on_file_done(&self, path: &str, bytes: usize) {
+            self.events.lock().unwrap().push(format!("done {} {}", path, bytes));
+        }
+
+        fn on_file_skipped(&self, path: &str, reason: &SkipReason) {
+            self.events.lock().unwrap().push(format!("skip {} {}", path, reason));
+        }
+    }
+
+    #[test]
+    fn test_reporter_is_shareable_across_threads() {
+        let progress = Arc::new(RecordingProgress::default());
+
+        let handles: Vec<_> = (0..4)
+            .map(|i| {
+                let progress = Arc::clone(&progress);
+                std::thread::spawn(move || {
+                    let path = format!("file{}.rs", i);
+                    progress.on_file_start(&path);
+                    progress.on_file_done(&path, i);
+                })
+            })
+            .collect();
+        for handle in handles {
+            handle.join().unwrap();
+        }
+
+        let events = progress.events.lock().unwrap();
+        assert_eq!(events.len(), 8);
+        assert!(events.contains(&"done file3.rs 3".to_string()));
+    }
+
+    #[test]
+    fn test_console_progress_counts_started_files() {
+        let progress = ConsoleProgress::new();
+        progress.on_start(2);
+        progress.on_file_start("a.rs");
+        progress.on_file_start("b.rs");
+        assert_eq!(progress.started.load(Ordering::Relaxed), 2);
+    }
+
+    #[test]
+    fn test_console_progress_takes_slowest_fetches() {
+        let progress = ConsoleProgress::new().with_timing();
+        progress.on_file_fetched("a.rs", Duration::from_millis(30));
+        progress.on_file_fetched("b.rs", Duration::from_millis(900));
+        progress.on_file_fetched("c.rs", Duration::from_millis(120));
+
+        let (slowest, total) = progress.take_slowest(2);
+        assert_eq!(slowest, vec![("b.rs".to_string(), Duration::from_millis(900)), ("c.rs".to_string(), Duration::from_millis(120))]);
+        assert_eq!(total, Duration::from_millis(1050));
+        assert!(progress.take_slowest(2).0.is_empty());
+        assert!(ConsoleProgress::new().take_slowest(2).0.is_empty());
+    }
+
+    #[test]
+    fn test_skip_reason_display() {
+        let reason = SkipReason::FetchFailed("404".to_string());
+        assert_eq!(reason.to_string(), "fetch failed: 404");
+    }
+}
@@ -0,0 +1,84 @@
+// ============= src/repo_config.rs =============
+use crate::export::{ContentFilter, ExportFormat};
+use crate::ignore::valid_patterns;
+use crate::redact::RedactionRule;
+use anyhow::{anyhow, Result};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+
+/// Repository-root file in which maintainers can set defaults for exports of their repo
+pub const REPO_CONFIG_FILE: &str = "export.toml";
+
+/// Export defaults a repository ships in `export.toml`, e.g.
+///
+/// ```toml
+/// format = "json"
+/// exclude = ["fixtures/", "*.snap"]
+/// filters = ["strip-comments"]
+///
+/// [redact]
+/// internal_host = '\binternal\.example\.com\b'
+/// ```
+///
+/// Everything the user sets themselves takes precedence.
+#[derive(Debug, Clone, Default)]
+pub struct RepoConfig {
+    /// Gitignore-style patterns to leave out, on top of the user's own exclusions.
+    /// Patterns that don't compile are logged and dropped, so they can't fail the export.
+    pub exclude: Vec<String>,
+    /// Content filters used when the user doesn't give any
+    pub filters: Vec<ContentFilter>,
+    /// Format used when the user picks neither a format nor an output path
+    pub format: Option<ExportFormat>,
+    /// Extra secret patterns, applied only when redaction is enabled
+    pub redact: Vec<RedactionRule>,
+}
+
+#[derive(Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct RawRepoConfig {
+    exclude: Vec<String>,
+    filters: Vec<String>,
+    format: Option<String>,
+    redact: BTreeMap<String, String>,
+}
+
+impl RepoConfig {
+    pub fn parse(text: &str) -> Result<Self> {
+        let raw: RawRepoConfig = toml::from_str(text).map_err(|e| anyhow!("Invalid {}: {}", REPO_CONFIG_FILE, e))?;
+
+        Ok(Self {
+            exclude: valid_patterns(&raw.exclude, REPO_CONFIG_FILE),
+            filters: raw.filters.iter().map(|filter| filter.parse()).collect::<Result<_>>()?,
+            format: raw.format.as_deref().map(str::parse).transpose()?,
+            redact: raw
+                .redact
+                .iter()
+                .map(|(name, pattern)| RedactionRule::new(name, pattern))
+                .collect::<Result<_>>()?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_repo_config() {
+        let config = RepoConfig::parse(
+            "format = \"json\"\nexclude = [\"fixtures/\"]\nfilters = [\"strip-comments\"]\n\n[redact]\ninternal_host = 'internal\\.example\\.com'\n",
+        )
+        .unwrap();
+        assert_eq!(config.format, Some(ExportFormat::Json));
+        assert_eq!(config.exclude, vec!["fixtures/"]);
+        assert_eq!(config.filters, vec![ContentFilter::StripComments]);
+        assert_eq!(config.redact[0].name, "internal_host");
+
+        assert!(RepoConfig::parse("").unwrap().format.is_none());
+        let config = RepoConfig::parse("exclude = [\"src/[oops\", \"*.snap\"]").unwrap();
+        assert_eq!(config.exclude, vec!["*.snap"]);
+        assert!(RepoConfig::parse("format = \"pdf\"").is_err());
+        assert!(RepoConfig::parse("includes = [\"src/\"]").is_err());
+    }
+}
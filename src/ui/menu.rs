@@ -1,19 +1,33 @@
 // ============= src/ui/menu.rs =============
-use crate::input::parser::{parse_github_url, parse_owner_repo_format};
+use super::plain::plain_text;
+use crate::input::parser::{parse_github_url, parse_owner_repo_format, parse_owner_repo_path};
 use anyhow::Result;
 use std::io::{self, Write};
 
-/// Interactive menu system for getting repository information
-pub fn get_repository_info() -> Result<(String, String)> {
-    println!("Select input method:");
-    println!("  1. Enter full GitHub URL");
-    println!("  2. Enter in format 'owner/repo'");
-    println!("  3. Enter owner and repo separately");
-    println!("  4. Exit\n");
+/// `eprintln!`, minus emoji in plain output mode; the menu stays off stdout
+macro_rules! say {
+    ($($arg:tt)*) => {
+        eprintln!("{}", plain_text(&format!($($arg)*)))
+    };
+}
+
+/// `eprint!` for prompts, minus emoji in plain output mode
+macro_rules! prompt {
+    ($($arg:tt)*) => {
+        eprint!("{}", plain_text(&format!($($arg)*)))
+    };
+}
+
+/// Interactive menu system for getting repository information.
+///
+/// Returns one repository, or several with option 5. Input that doesn't parse is
+/// reported and the menu shown again, so the user can retry.
+pub fn get_repository_info() -> Result<Vec<(String, String)>> {
+    print_options();
 
     loop {
-        print!("Choose an option (1-4): ");
-        io::stdout().flush()?;
+        prompt!("Choose an option (1-5): ");
+        io::stderr().flush()?;
 
         let mut choice = String::new();
         io::stdin().read_line(&mut choice)?;
@@ -22,73 +36,141 @@ pub fn get_repository_info() -> Result<(String, String)> {
         match choice {
             "1" => {
                 // Full GitHub URL
-                print!("\n🔗 Enter the full GitHub URL: ");
-                io::stdout().flush()?;
+                prompt!("\n🔗 Enter the full GitHub URL: ");
+                io::stderr().flush()?;
                 let mut url = String::new();
                 io::stdin().read_line(&mut url)?;
                 let url = url.trim();
 
                 if url.is_empty() {
-                    println!("❌ URL cannot be empty. Please try again.\n");
+                    say!("❌ URL cannot be empty. Please try again.\n");
                     continue;
                 }
 
-                return parse_github_url(url);
+                match parse_github_url(url) {
+                    Ok(repo) => return Ok(vec![repo]),
+                    Err(e) => retry(e),
+                }
             }
 
             "2" => {
                 // Owner/repo format
-                print!("\n📋 Enter in format 'owner/repo': ");
-                io::stdout().flush()?;
+                prompt!("\n📋 Enter in format 'owner/repo': ");
+                io::stderr().flush()?;
                 let mut input = String::new();
                 io::stdin().read_line(&mut input)?;
                 let input = input.trim();
 
                 if input.is_empty() {
-                    println!("❌ Input cannot be empty. Please try again.\n");
+                    say!("❌ Input cannot be empty. Please try again.\n");
                     continue;
                 }
 
-                return parse_owner_repo_format(input);
+                match parse_owner_repo_format(input) {
+                    Ok(repo) => return Ok(vec![repo]),
+                    Err(e) => retry(e),
+                }
             }
 
             "3" => {
                 // Separate owner and repo
-                print!("\n👤 Enter GitHub username/organization: ");
-                io::stdout().flush()?;
+                prompt!("\n👤 Enter GitHub username/organization: ");
+                io::stderr().flush()?;
                 let mut owner = String::new();
                 io::stdin().read_line(&mut owner)?;
                 let owner = owner.trim();
 
                 if owner.is_empty() {
-                    println!("❌ Username/organization cannot be empty. Please try again.\n");
+                    say!("❌ Username/organization cannot be empty. Please try again.\n");
                     continue;
                 }
 
-                print!("📁 Enter repository name: ");
-                io::stdout().flush()?;
+                prompt!("📁 Enter repository name: ");
+                io::stderr().flush()?;
                 let mut repo = String::new();
                 io::stdin().read_line(&mut repo)?;
                 let repo = repo.trim();
 
                 if repo.is_empty() {
-                    println!("❌ Repository name cannot be empty. Please try again.\n");
+                    say!("❌ Repository name cannot be empty. Please try again.\n");
                     continue;
                 }
 
-                println!("\n✅ Repository: {}/{}\n", owner, repo);
-                return Ok((owner.to_string(), repo.to_string()));
+                match parse_owner_repo_path(&format!("{}/{}", owner, repo)) {
+                    Ok((owner, repo)) => {
+                        say!("\n✅ Repository: {}/{}\n", owner, repo);
+                        return Ok(vec![(owner, repo)]);
+                    }
+                    Err(e) => retry(e),
+                }
             }
 
             "4" => {
-                println!("\n👋 Goodbye!");
+                say!("\n👋 Goodbye!");
                 std::process::exit(0);
             }
 
+            "5" => {
+                let repos = read_repo_list()?;
+                if repos.is_empty() {
+                    say!("❌ No valid repositories entered. Please try again.\n");
+                    continue;
+                }
+
+                say!("\n✅ {} repositories to export\n", repos.len());
+                return Ok(repos);
+            }
+
             _ => {
-                println!("❌ Invalid choice. Please enter 1, 2, 3, or 4.\n");
+                say!("❌ Invalid choice. Please enter 1, 2, 3, 4, or 5.\n");
                 continue;
             }
         }
     }
-}
\ No newline at end of file
+}
+
+fn print_options() {
+    say!("Select input method:");
+    say!("  1. Enter full GitHub URL");
+    say!("  2. Enter in format 'owner/repo'");
+    say!("  3. Enter owner and repo separately");
+    say!("  4. Exit");
+    say!("  5. Enter multiple repos\n");
+}
+
+/// Reports input that didn't parse and shows the menu again
+fn retry(error: anyhow::Error) {
+    say!("❌ {}. Please try again.\n", error);
+    print_options();
+}
+
+/// Reads GitHub URLs or `owner/repo` lines until a blank line (or end of input).
+/// Lines that don't parse are reported and left out rather than ending the list.
+fn read_repo_list() -> Result<Vec<(String, String)>> {
+    say!("\n📋 Enter one GitHub URL or 'owner/repo' per line, then a blank line to finish:");
+
+    let mut repos = Vec::new();
+    loop {
+        let mut line = String::new();
+        if io::stdin().read_line(&mut line)? == 0 {
+            break;
+        }
+        let line = line.trim();
+        if line.is_empty() {
+            break;
+        }
+
+        let parsed = if line.contains("github.com/") {
+            parse_github_url(line)
+        } else {
+            parse_owner_repo_format(line)
+        };
+        match parsed {
+            Ok(repo) if repos.contains(&repo) => say!("⚠️  {}/{} is already in the list", repo.0, repo.1),
+            Ok(repo) => repos.push(repo),
+            Err(e) => say!("❌ Skipping '{}': {}", line, e),
+        }
+    }
+
+    Ok(repos)
+}
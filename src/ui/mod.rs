@@ -1,4 +1,6 @@
 // ============= src/ui/mod.rs =============
 pub mod menu;
+pub mod plain;
 
-pub use menu::get_repository_info;
\ No newline at end of file
+pub use menu::get_repository_info;
+pub use plain::{plain_text, set_plain_output, PlainWriter};
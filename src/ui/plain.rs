@@ -0,0 +1,97 @@
+// ============= src/ui/plain.rs =============
+use std::borrow::Cow;
+use std::io::{self, Write};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+static PLAIN_OUTPUT: AtomicBool = AtomicBool::new(false);
+
+/// Emoji that carry meaning get an ASCII tag; the rest are decoration and are dropped
+const TAGS: &[(&str, &str)] = &[
+    ("✅", "[ok]"),
+    ("❌", "[error]"),
+    ("🚫", "[error]"),
+    ("⚠", "[warn]"),
+    ("❗", "[!]"),
+    ("⏹", "[stopped]"),
+];
+
+/// Follows some emoji to request their colourful form
+const VARIATION_SELECTOR: char = '\u{FE0F}';
+
+/// Switches user-facing messages to plain ASCII, for terminals and logs that mangle emoji
+pub fn set_plain_output(plain: bool) {
+    PLAIN_OUTPUT.store(plain, Ordering::Relaxed);
+}
+
+pub fn plain_output() -> bool {
+    PLAIN_OUTPUT.load(Ordering::Relaxed)
+}
+
+/// `text` as is, or with its emoji replaced by ASCII tags (`✅` → `[ok]`) or removed
+/// when plain output is on
+pub fn plain_text(text: &str) -> Cow<'_, str> {
+    if !plain_output() || text.is_ascii() {
+        return Cow::Borrowed(text);
+    }
+    Cow::Owned(strip_emoji(text))
+}
+
+fn strip_emoji(text: &str) -> String {
+    let mut plain = String::with_capacity(text.len());
+    let mut rest = text;
+
+    while let Some(c) = rest.chars().next() {
+        if let Some((emoji, tag)) = TAGS.iter().find(|(emoji, _)| rest.starts_with(emoji)) {
+            plain.push_str(tag);
+            // Emoji are often followed by two spaces to make up for their width
+            let after = rest[emoji.len()..].trim_start_matches(VARIATION_SELECTOR);
+            let trimmed = after.trim_start_matches(' ');
+            if trimmed.len() < after.len() {
+                plain.push(' ');
+            }
+            rest = trimmed;
+        } else if is_emoji(c) {
+            rest = rest[c.len_utf8()..].trim_start_matches(VARIATION_SELECTOR).trim_start_matches(' ');
+        } else {
+            plain.push(c);
+            rest = &rest[c.len_utf8()..];
+        }
+    }
+
+    plain
+}
+
+fn is_emoji(c: char) -> bool {
+    matches!(c as u32, 0x1F300..=0x1FAFF | 0x2600..=0x27BF | 0x2B00..=0x2BFF | 0x23E9..=0x23FF)
+}
+
+/// Passes everything written through `plain_text`, e.g. to use as a `tracing` writer.
+/// Expects each write to hold whole lines, as `tracing-subscriber` does.
+pub struct PlainWriter<W>(pub W);
+
+impl<W: Write> Write for PlainWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match std::str::from_utf8(buf) {
+            Ok(text) => self.0.write_all(plain_text(text).as_bytes())?,
+            Err(_) => self.0.write_all(buf)?,
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.0.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_strip_emoji() {
+        assert_eq!(strip_emoji("✅ Export complete: out.md"), "[ok] Export complete: out.md");
+        assert_eq!(strip_emoji("⚠️  Skipped a.bin (too large)"), "[warn] Skipped a.bin (too large)");
+        assert_eq!(strip_emoji("\n📊 This export used 12 API requests"), "\nThis export used 12 API requests");
+        assert_eq!(strip_emoji("Wrapped ↪ line"), "Wrapped ↪ line");
+    }
+}
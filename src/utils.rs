@@ -1,17 +1,187 @@
 // ============= src/utils.rs =============
+use percent_encoding::{utf8_percent_encode, AsciiSet, NON_ALPHANUMERIC};
+
+/// Controls which paths `should_skip_path` excludes from an export.
+///
+/// `SkipConfig::default()` matches the built-in exclusion rules; embedders can flip the
+/// toggles or add their own extensions and path prefixes.
+#[derive(Debug, Clone)]
+pub struct SkipConfig {
+    /// Skip version control metadata (`.git/`)
+    pub skip_vcs: bool,
+    /// Skip build output and dependency directories (`target/`, `node_modules/`, `dist/`,
+    /// `build/`) wherever they are, e.g. a workspace member's `crates/core/target/`
+    pub skip_build_dirs: bool,
+    /// Skip compiled binaries and shared libraries (`.exe`, `.dll`, `.so`, `.dylib`, `.bin`)
+    pub skip_binaries: bool,
+    /// Skip lockfiles, source maps and minified files (see `is_generated_path` and
+    /// `looks_minified`). Unlike the other rules, these skips are reported.
+    pub skip_generated: bool,
+    /// Skip files and directories whose name starts with `.` (e.g. `.github/`,
+    /// `.vscode/`), anywhere along the path. Off by default.
+    pub skip_hidden: bool,
+    /// Names kept despite `skip_hidden`, matched against each path component (e.g.
+    /// `.gitignore`, or `.github` to keep the whole directory)
+    pub hidden_allowlist: Vec<String>,
+    /// Additional file extensions to skip, with or without the leading dot (e.g. `"lock"`, `".min.js"`)
+    pub extra_extensions: Vec<String>,
+    /// Additional path prefixes to skip (e.g. `"vendor/"`)
+    pub extra_prefixes: Vec<String>,
+    /// Honor `export-ignore` patterns from the repository's root `.gitattributes`
+    pub honor_export_ignore: bool,
+    /// Honor patterns from the repository's root `.exporterignore`
+    pub honor_exporter_ignore: bool,
+}
+
+impl Default for SkipConfig {
+    fn default() -> Self {
+        Self {
+            skip_vcs: true,
+            skip_build_dirs: true,
+            skip_binaries: true,
+            skip_generated: true,
+            skip_hidden: false,
+            hidden_allowlist: DEFAULT_HIDDEN_ALLOWLIST.iter().map(|name| name.to_string()).collect(),
+            extra_extensions: Vec::new(),
+            extra_prefixes: Vec::new(),
+            honor_export_ignore: true,
+            honor_exporter_ignore: true,
+        }
+    }
+}
+
+/// Hidden files kept by default when `SkipConfig::skip_hidden` is set
+pub const DEFAULT_HIDDEN_ALLOWLIST: [&str; 2] = [".gitignore", ".gitattributes"];
+
+const BUILD_DIRS: [&str; 4] = ["target", "node_modules", "dist", "build"];
+const BINARY_EXTENSIONS: [&str; 5] = [".dll", ".so", ".dylib", ".exe", ".bin"];
+
+const GENERATED_SUFFIXES: [&str; 4] = [".min.js", ".min.css", ".js.map", ".css.map"];
+/// Lockfiles by exact name, since a `.lock` suffix alone also matches hand-written files
+const GENERATED_NAMES: [&str; 20] = [
+    "Cargo.lock",
+    "package-lock.json",
+    "npm-shrinkwrap.json",
+    "yarn.lock",
+    "pnpm-lock.yaml",
+    "bun.lock",
+    "bun.lockb",
+    "deno.lock",
+    "composer.lock",
+    "Gemfile.lock",
+    "Podfile.lock",
+    "Pipfile.lock",
+    "poetry.lock",
+    "uv.lock",
+    "pubspec.lock",
+    "mix.lock",
+    "flake.lock",
+    "packages.lock.json",
+    "gradle.lockfile",
+    "go.sum",
+];
+
+/// Files under this size are never treated as minified; short files can't tell much
+const MINIFIED_MIN_BYTES: usize = 1024;
+/// Average line length beyond which content is treated as minified
+const MINIFIED_LINE_LENGTH: usize = 300;
+
+/// Whether `path` names a file that's generated rather than written: lockfiles
+/// (`Cargo.lock`, `package-lock.json`), source maps and minified assets (`*.min.js`)
+pub fn is_generated_path(path: &str) -> bool {
+    let name = path.rsplit('/').next().unwrap_or(path);
+    GENERATED_SUFFIXES.iter().any(|suffix| name.ends_with(suffix)) || GENERATED_NAMES.contains(&name)
+}
+
+/// The average line length in characters if `content` looks minified (lines averaging
+/// over 300 characters), as bundlers produce whatever the file is called
+pub fn looks_minified(content: &str) -> Option<usize> {
+    if content.len() < MINIFIED_MIN_BYTES {
+        return None;
+    }
+    let average = content.chars().count() / content.lines().count().max(1);
+    (average > MINIFIED_LINE_LENGTH).then_some(average)
+}
+
+/// The share of `content`'s characters that are control characters, if it's more than
+/// `threshold` (a fraction, e.g. `0.1`). Text that decodes as UTF-8 but is mostly
+/// control characters is binary data in all but name. Tabs, line breaks, form feeds and
+/// the ESC that starts ANSI color codes (common in captured logs) are ordinary text and
+/// don't count.
+pub fn looks_binary(content: &str, threshold: f64) -> Option<f64> {
+    let mut total = 0;
+    let mut control = 0;
+    for c in content.chars() {
+        total += 1;
+        if c.is_control() && !matches!(c, '\t' | '\n' | '\r' | '\x0c' | '\x1b') {
+            control += 1;
+        }
+    }
+    if total == 0 {
+        return None;
+    }
+    let ratio = control as f64 / total as f64;
+    (ratio > threshold).then_some(ratio)
+}
+
 /// Helper function to determine if a path should be skipped during export
-pub fn should_skip_path(path: &str) -> bool {
-    path.starts_with("target/")
-        || path.starts_with("node_modules/")
-        || path.starts_with("dist/")
-        || path.contains("/.DS_Store")
-        || path.starts_with("build/")
-        || path.ends_with(".dll")
-        || path.ends_with(".so")
-        || path.ends_with(".dylib")
-        || path.ends_with(".exe")
-        || path.ends_with(".bin")
-        || path.starts_with(".git/")
+pub fn should_skip_path(path: &str, config: &SkipConfig) -> bool {
+    if path.contains("/.DS_Store") {
+        return true;
+    }
+
+    if config.skip_vcs && path.starts_with(".git/") {
+        return true;
+    }
+
+    // Any directory along the path, but not the file's own name
+    let mut dirs = path.split('/').rev().skip(1);
+    if config.skip_build_dirs && dirs.any(|dir| BUILD_DIRS.contains(&dir)) {
+        return true;
+    }
+
+    if config.skip_hidden
+        && path
+            .split('/')
+            .any(|component| component.starts_with('.') && !config.hidden_allowlist.iter().any(|allowed| allowed == component))
+    {
+        return true;
+    }
+
+    if config.skip_binaries && BINARY_EXTENSIONS.iter().any(|ext| path.ends_with(ext)) {
+        return true;
+    }
+
+    config.extra_prefixes.iter().any(|prefix| path.starts_with(prefix.as_str()))
+        || config.extra_extensions.iter().any(|ext| {
+            let ext = ext.trim_start_matches('.');
+            path.strip_suffix(ext).is_some_and(|rest| rest.ends_with('.'))
+        })
+}
+
+/// How many directories deep `path` is: `0` for a file at the root, `2` for `a/b/c.rs`
+pub fn path_depth(path: &str) -> usize {
+    path.matches('/').count()
+}
+
+/// Whether `path` is inside the directory `dir` (both `/`-separated, relative to the root)
+pub fn is_under(path: &str, dir: &str) -> bool {
+    path.strip_prefix(dir).is_some_and(|rest| rest.starts_with('/'))
+}
+
+/// Characters escaped in a URL path segment: everything but letters, digits and `-._~`
+const PATH_SEGMENT: &AsciiSet = &NON_ALPHANUMERIC.remove(b'-').remove(b'.').remove(b'_').remove(b'~');
+
+/// Percent-encodes each segment of `path` for use in a URL, keeping the `/`s between
+/// them, so names with spaces, `#`, `?` or non-ASCII characters survive intact
+pub fn encode_path(path: &str) -> String {
+    path.split('/').map(encode_segment).collect::<Vec<_>>().join("/")
+}
+
+/// Percent-encodes `segment` as a single URL path segment, `/`s included, e.g. a branch
+/// name like `feature/login`
+pub fn encode_segment(segment: &str) -> String {
+    utf8_percent_encode(segment, PATH_SEGMENT).to_string()
 }
 
 #[cfg(test)]
@@ -20,10 +190,125 @@ mod tests {
 
     #[test]
     fn test_should_skip_path() {
-        assert!(should_skip_path("target/debug/app"));
-        assert!(should_skip_path("node_modules/package/index.js"));
-        assert!(should_skip_path(".git/config"));
-        assert!(should_skip_path("app.exe"));
-        assert!(!should_skip_path("src/main.rs"));
+        let config = SkipConfig::default();
+        assert!(should_skip_path("target/debug/app", &config));
+        assert!(should_skip_path("node_modules/package/index.js", &config));
+        assert!(should_skip_path(".git/config", &config));
+        assert!(should_skip_path("app.exe", &config));
+        assert!(!should_skip_path("src/main.rs", &config));
+    }
+
+    #[test]
+    fn test_should_skip_path_hidden() {
+        assert!(!should_skip_path(".github/workflows/ci.yml", &SkipConfig::default()));
+
+        let config = SkipConfig { skip_hidden: true, ..SkipConfig::default() };
+        assert!(should_skip_path(".github/workflows/ci.yml", &config));
+        assert!(should_skip_path("web/.vscode/settings.json", &config));
+        assert!(should_skip_path(".editorconfig", &config));
+        assert!(!should_skip_path(".gitignore", &config));
+        assert!(!should_skip_path("crates/core/.gitignore", &config));
+        assert!(!should_skip_path("src/main.rs", &config));
+
+        let config = SkipConfig { hidden_allowlist: vec![".github".to_string()], ..config };
+        assert!(!should_skip_path(".github/workflows/ci.yml", &config));
+        assert!(should_skip_path(".gitignore", &config));
+    }
+
+    #[test]
+    fn test_should_skip_path_nested_build_dirs() {
+        let config = SkipConfig::default();
+        assert!(should_skip_path("crates/foo/target/debug/x", &config));
+        assert!(should_skip_path("web/node_modules/react/index.js", &config));
+        assert!(should_skip_path("packages/ui/dist/index.js", &config));
+        assert!(should_skip_path("android/app/build/outputs/app.apk", &config));
+        // Only whole directory names count, and never the file's own name
+        assert!(!should_skip_path("targets/config.rs", &config));
+        assert!(!should_skip_path("src/distributed/mod.rs", &config));
+        assert!(!should_skip_path("scripts/build", &config));
+        assert!(!should_skip_path("src/build.rs", &config));
+    }
+
+    #[test]
+    fn test_should_skip_path_toggles() {
+        let config = SkipConfig {
+            skip_vcs: false,
+            skip_build_dirs: false,
+            skip_binaries: false,
+            ..SkipConfig::default()
+        };
+        assert!(!should_skip_path("target/debug/app", &config));
+        assert!(!should_skip_path(".git/config", &config));
+        assert!(!should_skip_path("app.exe", &config));
+    }
+
+    #[test]
+    fn test_should_skip_path_extras() {
+        let config = SkipConfig {
+            extra_extensions: vec!["lock".to_string(), ".min.js".to_string()],
+            extra_prefixes: vec!["vendor/".to_string()],
+            ..SkipConfig::default()
+        };
+        assert!(should_skip_path("Cargo.lock", &config));
+        assert!(should_skip_path("static/app.min.js", &config));
+        assert!(should_skip_path("vendor/lib/mod.rs", &config));
+        assert!(!should_skip_path("src/block.rs", &config));
+        assert!(!should_skip_path("static/app.js", &config));
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_generated_files() {
+        for path in ["Cargo.lock", "web/package-lock.json", "static/app.min.js", "app.js.map", "site.css.map", "go.sum"] {
+            assert!(is_generated_path(path), "{}", path);
+        }
+        for path in ["src/lock.rs", "static/app.js", "src/heap.map", "deploy/db.lock", "docs/tree-lock.json"] {
+            assert!(!is_generated_path(path), "{}", path);
+        }
+
+        let minified = format!("var a=1;{}", "b(c,d);".repeat(200));
+        assert_eq!(looks_minified(&minified), Some(minified.len()));
+        assert_eq!(looks_minified(&"fn main() {}\n".repeat(200)), None);
+        assert_eq!(looks_minified("x".repeat(500).as_str()), None);
+        // Counted in characters, so multi-byte text isn't overcounted
+        assert_eq!(looks_minified(&format!("{}\n", "é".repeat(200)).repeat(5)), None);
+    }
+
+    #[test]
+    fn test_looks_binary() {
+        // A compiled protobuf descriptor and a NUL-padded record file: valid UTF-8, but not text
+        let descriptor = "\n\x0bhello.proto\x12\x05hello\"\x1c\n\x0cHelloRequest\x12\x0c\n\x04name\x18\x01 \x01(\t";
+        assert!(looks_binary(descriptor, 0.1).is_some());
+        let records = format!("HEADER{}", "\0\0\0\x01\0\0\0\x02".repeat(32));
+        assert!(looks_binary(&records, 0.1).unwrap() > 0.9);
+
+        // Source, tab-separated data and colored terminal output are text
+        assert_eq!(looks_binary("fn main() {\r\n\tprintln!(\"hi\");\r\n}\x0c\n", 0.1), None);
+        assert_eq!(looks_binary("name\tsize\nmain.rs\t12\n", 0.1), None);
+        assert_eq!(looks_binary("\x1b[32mok\x1b[0m test_parse_owner_repo_path passed\n", 0.1), None);
+        assert_eq!(looks_binary("", 0.1), None);
+
+        // Even when the escapes make up much of a short file
+        assert_eq!(looks_binary("\x1b[32mok\x1b[0m", 0.1), None);
+
+        // The threshold decides borderline cases
+        assert!(looks_binary("ok\x07\x07", 0.1).is_some());
+        assert_eq!(looks_binary("ok\x07\x07", 0.5), None);
+        assert_eq!(looks_binary(&records, 1.0), None);
+    }
+
+    #[test]
+    fn test_path_depth() {
+        assert_eq!(path_depth("README.md"), 0);
+        assert_eq!(path_depth("a/b.rs"), 1);
+        assert_eq!(path_depth("a/b/c/d.rs"), 3);
+    }
+
+    #[test]
+    fn test_encode_path() {
+        assert_eq!(encode_path("src/main.rs"), "src/main.rs");
+        assert_eq!(encode_path("docs/my file (draft).md"), "docs/my%20file%20%28draft%29.md");
+        assert_eq!(encode_path("notes/#1?.md"), "notes/%231%3F.md");
+        assert_eq!(encode_path("docs/résumé.md"), "docs/r%C3%A9sum%C3%A9.md");
+        assert_eq!(encode_segment("feature/login#2"), "feature%2Flogin%232");
+    }
+}